@@ -0,0 +1,270 @@
+use anchor_lang::{Discriminator, InstructionData, ToAccountMetas};
+use bytemuck::Pod;
+use raffle::account::{GlobalPool, Leaderboard, RafflePool};
+use raffle::constants::*;
+use solana_program_test::ProgramTest;
+use solana_sdk::{
+    account::Account, instruction::Instruction, pubkey::Pubkey, rent::Rent, signature::Signer,
+    signer::keypair::keypair_from_seed, system_program, sysvar, transaction::Transaction,
+};
+
+// the default compute budget of an instruction
+const COMPUTE_BUDGET: u64 = 200_000;
+// buys must leave room to be composed with other instructions
+const BUY_TICKETS_LIMIT: u64 = COMPUTE_BUDGET / 2;
+// draws must keep a tenth of the budget as headroom
+const REVEAL_WINNER_LIMIT: u64 = COMPUTE_BUDGET * 9 / 10;
+
+const BUY_FILL_LEVELS: [usize; 4] = [0, 500, 1000, MAX_ENTRANTS - 1];
+const REVEAL_WINNER_COUNTS: [u64; 4] = [1, 10, 25, MAX_WINNERS as u64];
+
+// fixed addresses keep the PDA bump searches, and so the measured units, stable between runs
+const RAFFLE: Pubkey = Pubkey::new_from_array([1; 32]);
+const CREATOR: Pubkey = Pubkey::new_from_array([2; 32]);
+const TREASURY: Pubkey = Pubkey::new_from_array([3; 32]);
+const UNUSED: Pubkey = Pubkey::new_from_array([4; 32]);
+const BUYER_SEED: [u8; 32] = [5; 32];
+// 2100-01-01, far enough out for ticket sales to stay open
+const SALES_END: i64 = 4_102_444_800;
+
+enum Bench {
+    BuyTickets { fill: usize },
+    RevealWinner { winner_count: u64 },
+}
+
+impl Bench {
+    fn name(&self) -> String {
+        match self {
+            Bench::BuyTickets { fill } => format!("buy_tickets, {} entrants", fill),
+            Bench::RevealWinner { winner_count } => {
+                format!("reveal_winner, {} winners", winner_count)
+            }
+        }
+    }
+
+    fn limit(&self) -> u64 {
+        match self {
+            Bench::BuyTickets { .. } => BUY_TICKETS_LIMIT,
+            Bench::RevealWinner { .. } => REVEAL_WINNER_LIMIT,
+        }
+    }
+
+    // add the accounts the instruction runs against and build it
+    fn setup(&self, program_test: &mut ProgramTest, buyer: &Pubkey) -> Instruction {
+        match self {
+            Bench::BuyTickets { fill } => buy_tickets(program_test, buyer, *fill),
+            Bench::RevealWinner { winner_count } => {
+                reveal_winner(program_test, buyer, *winner_count)
+            }
+        }
+    }
+}
+
+fn add_zero_copy<T: Discriminator + Pod>(
+    program_test: &mut ProgramTest,
+    address: Pubkey,
+    value: &T,
+) {
+    let mut data = T::discriminator().to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(value));
+    program_test.add_account(
+        address,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: raffle::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
+
+fn add_global_authority(program_test: &mut ProgramTest) -> (Pubkey, u8) {
+    let (global_authority, global_bump) =
+        Pubkey::find_program_address(&[GLOBAL_AUTHORITY_SEED.as_ref()], &raffle::ID);
+    let global_pool = GlobalPool {
+        treasury: TREASURY,
+        version: GLOBAL_VERSION,
+        ..GlobalPool::default()
+    };
+    add_zero_copy(program_test, global_authority, &global_pool);
+    (global_authority, global_bump)
+}
+
+// a SOL priced raffle whose first fill entrant slots hold a ticket each
+fn raffle_with_entrants(fill: usize) -> Box<RafflePool> {
+    let mut raffle = Box::new(RafflePool::default());
+    raffle.version = RAFFLE_VERSION;
+    raffle.creator = CREATOR;
+    raffle.set_max_entrants(MAX_ENTRANTS as u64).unwrap();
+    raffle.ticket_price_sol = 10_000_000;
+    raffle.end_timestamp = SALES_END;
+    for i in 0..fill {
+        let mut entrant = [0u8; 32];
+        entrant[..8].copy_from_slice(&(i as u64 + 1).to_le_bytes());
+        raffle.entrants[i] = Pubkey::new_from_array(entrant);
+        raffle.entrant_tickets[i] = 1;
+    }
+    raffle.entrant_count = fill as u64;
+    raffle.set_count(fill as u64);
+    raffle.tickets_remaining = (MAX_ENTRANTS - fill) as u64;
+    raffle
+}
+
+// a full leaderboard the buyer's ticket ranks first on, the costliest insertion
+fn full_leaderboard() -> Leaderboard {
+    let mut leaderboard = Leaderboard::default();
+    leaderboard.season = 1;
+    for i in 0..LEADERBOARD_SIZE {
+        let mut wallet = [0u8; 32];
+        wallet[..8].copy_from_slice(&(i as u64 + 1).to_le_bytes());
+        leaderboard.wallets[i] = Pubkey::new_from_array(wallet);
+    }
+    leaderboard.count = LEADERBOARD_SIZE as u64;
+    leaderboard
+}
+
+fn buy_tickets(program_test: &mut ProgramTest, buyer: &Pubkey, fill: usize) -> Instruction {
+    let (global_authority, global_bump) = add_global_authority(program_test);
+    add_zero_copy(program_test, RAFFLE, &*raffle_with_entrants(fill));
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &raffle::ID);
+    let (proceeds_vault, vault_bump) = pda(&[PROCEEDS_SEED.as_ref(), RAFFLE.as_ref()]);
+    let (fee_exemption, _) = pda(&[FEE_EXEMPTION_SEED.as_ref(), buyer.as_ref()]);
+    let (ticket_record, _) = pda(&[TICKET_RECORD_SEED.as_ref(), RAFFLE.as_ref(), buyer.as_ref()]);
+    let (user_profile, _) = pda(&[USER_PROFILE_SEED.as_ref(), buyer.as_ref()]);
+    let (creator_stats, _) = pda(&[CREATOR_STATS_SEED.as_ref(), CREATOR.as_ref()]);
+    let (leaderboard, _) = pda(&[LEADERBOARD_SEED.as_ref()]);
+    add_zero_copy(program_test, leaderboard, &full_leaderboard());
+    Instruction {
+        program_id: raffle::ID,
+        // SOL payments leave the token accounts untouched
+        accounts: raffle::accounts::BuyTickets {
+            buyer: *buyer,
+            raffle: RAFFLE,
+            global_authority,
+            treasury: TREASURY,
+            fee_exemption,
+            ticket_record,
+            user_profile,
+            creator_stats,
+            leaderboard,
+            proceeds_vault,
+            user_token_account: UNUSED,
+            creator_token_account: UNUSED,
+            treasury_token_account: UNUSED,
+            proceeds_token_account: UNUSED,
+            token_mint: UNUSED,
+            token_program: spl_token::id(),
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: raffle::instruction::BuyTickets {
+            global_bump,
+            vault_bump,
+            amount: 1,
+            nonce: [0; 32],
+            pay_with: PAY_SOL,
+        }
+        .data(),
+    }
+}
+
+fn reveal_winner(program_test: &mut ProgramTest, buyer: &Pubkey, winner_count: u64) -> Instruction {
+    let mut pool = raffle_with_entrants(MAX_ENTRANTS);
+    pool.end_timestamp = 0;
+    pool.set_winner_count(winner_count);
+    add_zero_copy(program_test, RAFFLE, &*pool);
+    Instruction {
+        program_id: raffle::ID,
+        accounts: raffle::accounts::RevealWinner {
+            buyer: *buyer,
+            raffle: RAFFLE,
+            slot_hashes: sysvar::slot_hashes::id(),
+        }
+        .to_account_metas(None),
+        data: raffle::instruction::RevealWinner { secret: [0; 32] }.data(),
+    }
+}
+
+// whether the instruction succeeds within the given compute units, on a fresh bank
+async fn succeeds(bench: &Bench, compute_units: u64) -> bool {
+    let buyer = keypair_from_seed(&BUYER_SEED).unwrap();
+    let mut program_test = ProgramTest::new("raffle", raffle::ID, None);
+    program_test.set_compute_max_units(compute_units);
+    program_test.add_account(
+        buyer.pubkey(),
+        Account {
+            lamports: 100_000_000_000,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let instruction = bench.setup(&mut program_test, &buyer.pubkey());
+    let mut context = program_test.start_with_context().await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &buyer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .is_ok()
+}
+
+// the fewest compute units the instruction succeeds with, none if it fails within the budget
+async fn measure(bench: &Bench) -> Option<u64> {
+    if !succeeds(bench, COMPUTE_BUDGET).await {
+        return None;
+    }
+    let (mut low, mut high) = (0, COMPUTE_BUDGET);
+    while high - low > 1 {
+        let mid = (low + high) / 2;
+        if succeeds(bench, mid).await {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+    Some(high)
+}
+
+// measure every benchmark and exit with an error when one exceeds its limit,
+// run from the workspace after anchor build: BPF_OUT_DIR=target/deploy cargo run -p raffle-bench
+#[tokio::main]
+async fn main() {
+    let benches = BUY_FILL_LEVELS
+        .iter()
+        .map(|&fill| Bench::BuyTickets { fill })
+        .chain(
+            REVEAL_WINNER_COUNTS
+                .iter()
+                .map(|&winner_count| Bench::RevealWinner { winner_count }),
+        );
+
+    let mut regressions = 0;
+    for bench in benches {
+        let limit = bench.limit();
+        match measure(&bench).await {
+            Some(units) if units <= limit => {
+                println!("{:<32} {:>7} CU  (limit {})", bench.name(), units, limit);
+            }
+            Some(units) => {
+                println!("{:<32} {:>7} CU  over limit {}", bench.name(), units, limit);
+                regressions += 1;
+            }
+            None => {
+                println!("{:<32} fails within {} CU", bench.name(), COMPUTE_BUDGET);
+                regressions += 1;
+            }
+        }
+    }
+    if regressions > 0 {
+        eprintln!("{} benchmark(s) over their compute limit", regressions);
+        std::process::exit(1);
+    }
+}
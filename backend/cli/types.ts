@@ -3,20 +3,249 @@ import { PublicKey } from '@solana/web3.js';
 
 export interface GlobalPool {
     superAdmin: PublicKey,
+    feeBps: anchor.BN,
+    treasury: PublicKey,
+    creationFee: anchor.BN,
+    stakingProgram: PublicKey,
+    stakeTierCount: anchor.BN,
+    stakeTierMins: anchor.BN[],
+    stakeTierBps: anchor.BN[],
+    referralBps: anchor.BN,
+    paused: anchor.BN,
+    version: anchor.BN,
+    totalRaffles: anchor.BN,
+    totalTickets: anchor.BN,
+    totalVolumeSol: anchor.BN,
+    totalReapBurned: anchor.BN,
+    totalFeesSol: anchor.BN,
+    activeRaffles: anchor.BN,
+    raffleId: anchor.BN,
+    pendingAdmin: PublicKey,
+}
+
+export interface WinnerSnapshot {
+    raffle: PublicKey,
+    creator: PublicKey,
+    takenAt: anchor.BN,
+    winners: PublicKey[],
+}
+
+export interface WhitelistProof {
+    raffle: PublicKey,
+    winner: PublicKey,
+    claimedAt: anchor.BN,
+}
+
+export interface FeeExemption {
+    wallet: PublicKey,
+    addedAt: anchor.BN,
+}
+
+export interface TicketReceipt {
+    raffle: PublicKey,
+    buyer: PublicKey,
+    paidSol: anchor.BN,
+    paidToken: anchor.BN,
+    tickets: anchor.BN,
+}
+
+export interface TicketRecord {
+    raffle: PublicKey,
+    buyer: PublicKey,
+    page: anchor.BN,
+    slot: anchor.BN,
+    tickets: anchor.BN,
+    paidSol: anchor.BN,
+    paidToken: anchor.BN,
+    firstPurchaseAt: anchor.BN,
+    lastPurchaseAt: anchor.BN,
+}
+
+export interface UserProfile {
+    wallet: PublicKey,
+    rafflesEntered: anchor.BN,
+    ticketsBought: anchor.BN,
+    wins: anchor.BN,
+    raffles: PublicKey[],
+    season: anchor.BN,
+    seasonTickets: anchor.BN,
+}
+
+export interface Leaderboard {
+    season: anchor.BN,
+    seasonStart: anchor.BN,
+    count: anchor.BN,
+    wallets: PublicKey[],
+    tickets: anchor.BN[],
+}
+
+export interface CreatorStats {
+    creator: PublicKey,
+    rafflesCreated: anchor.BN,
+    prizesDelivered: anchor.BN,
+    volumeSol: anchor.BN,
+    cancellations: anchor.BN,
+}
+
+export interface Referrer {
+    wallet: PublicKey,
+    registeredAt: anchor.BN,
+}
+
+export interface EmergencyWithdrawal {
+    raffle: PublicKey,
+    source: PublicKey,
+    destination: PublicKey,
+    amount: anchor.BN,
+    proposedAt: anchor.BN,
+    executableAt: anchor.BN,
+}
+
+export interface FreeEntry {
+    raffle: PublicKey,
+    entrant: PublicKey,
+    enteredAt: anchor.BN,
 }
 
 export interface RafflePool {
     creator: PublicKey,
     nftMint: PublicKey,
-    count: anchor.BN,
-    winnerCount: anchor.BN,
-    noRepeat: anchor.BN,
-    maxEntrants: anchor.BN,
+    count: number,
+    maxEntrants: number,
+    noRepeat: number,
+    winnerCount: number,
+    whitelisted: number,
+    status: number,
     endTimestamp: anchor.BN,
     ticketPriceReap: anchor.BN,
     ticketPriceSol: anchor.BN,
-    whitelisted: anchor.BN,
-    claimedWinner: anchor.BN[],
+    randomnessMode: anchor.BN,
+    randomness: PublicKey,
+    commitment: number[],
+    entropy: number[],
+    drawSeed: number[],
+    revealedCount: anchor.BN,
+    revealed: anchor.BN,
+    crankTip: anchor.BN,
+    prizeType: anchor.BN,
+    programmable: anchor.BN,
+    prizeAmount: anchor.BN,
+    prizeCount: anchor.BN,
+    merkleTree: PublicKey,
+    cnftDataHash: number[],
+    cnftCreatorHash: number[],
+    cnftNonce: anchor.BN,
+    cnftIndex: anchor.BN,
+    tierCount: anchor.BN,
+    tierEnds: anchor.BN[],
+    tierTypes: anchor.BN[],
+    tierAmounts: anchor.BN[],
+    tierMint: PublicKey,
+    tierEscrowed: anchor.BN,
+    editionBase: anchor.BN,
+    delegatedAccount: PublicKey,
+    prizeHash: number[],
+    bundleClaimed: anchor.BN,
+    wlMint: PublicKey,
+    wlEscrowed: anchor.BN,
+    paymentMint: PublicKey,
+    paymentDecimals: anchor.BN,
+    burnBps: anchor.BN,
+    treasuryBps: anchor.BN,
+    proceeds: anchor.BN,
+    splitCount: anchor.BN,
+    splitPayees: PublicKey[],
+    splitBps: anchor.BN[],
+    dealCount: anchor.BN,
+    dealSizes: anchor.BN[],
+    dealPaid: anchor.BN[],
+    windowCount: anchor.BN,
+    windowEnds: anchor.BN[],
+    windowReap: anchor.BN[],
+    windowSol: anchor.BN[],
+    auctionStart: anchor.BN,
+    auctionStep: anchor.BN,
+    floorPriceReap: anchor.BN,
+    floorPriceSol: anchor.BN,
+    buyoutPrice: anchor.BN,
+    boughtOut: anchor.BN,
+    refundPool: anchor.BN,
+    refunded: anchor.BN,
+    cancelled: anchor.BN,
+    refundable: anchor.BN,
+    heldFees: anchor.BN,
+    tokenProceeds: anchor.BN,
+    minEntrants: anchor.BN,
+    createdAt: anchor.BN,
+    startTimestamp: anchor.BN,
+    soldOut: anchor.BN,
+    claimDeadline: anchor.BN,
+    rerolls: anchor.BN,
+    openReceipts: anchor.BN,
+    paused: anchor.BN,
+    presaleEnd: anchor.BN,
+    presaleMint: PublicKey,
+    ticketsRemaining: anchor.BN,
+    salesEndTimestamp: anchor.BN,
+    drawAfterTimestamp: anchor.BN,
+    snipeWindow: anchor.BN,
+    maxExtensions: anchor.BN,
+    extensions: anchor.BN,
+    holderCollection: PublicKey,
+    holderDiscountBps: anchor.BN,
+    charity: PublicKey,
+    charityBps: anchor.BN,
+    royaltyBps: anchor.BN,
+    paymentOptions: anchor.BN,
+    usdPriceCents: anchor.BN,
+    priceFeed: PublicKey,
+    usdPegToken: anchor.BN,
+    prizeMints: PublicKey[],
+    claimedWinners: anchor.BN,
     winner: PublicKey[],
     entrants: PublicKey[],
-}
\ No newline at end of file
+    version: anchor.BN,
+    name: number[],
+    uri: number[],
+    prizeCollection: PublicKey,
+    entrantCount: anchor.BN,
+    entrantTickets: anchor.BN[],
+    pageCount: anchor.BN,
+    pageTickets: anchor.BN,
+    maxTicketsPerUser: anchor.BN,
+    winnerOrder: number[],
+    totalSolCollected: anchor.BN,
+    totalTokensBurned: anchor.BN,
+    uniqueEntrants: anchor.BN,
+    raffleId: anchor.BN,
+    endSlot: anchor.BN,
+}
+
+export interface EntrantPage {
+    raffle: PublicKey,
+    pageIndex: anchor.BN,
+    entrantCount: anchor.BN,
+    tickets: anchor.BN,
+    entrants: PublicKey[],
+    entrantTickets: anchor.BN[],
+}
+
+// the return data of get_raffle_state
+export interface RaffleState {
+    version: number,
+    status: number,
+    ticketsSold: anchor.BN,
+    ticketsRemaining: anchor.BN,
+    ticketPriceSol: anchor.BN,
+    ticketPriceReap: anchor.BN,
+    endTimestamp: anchor.BN,
+    winnerCount: anchor.BN,
+    winnersRevealed: anchor.BN,
+}
+
+// the return data of get_winning_odds, winProbability is a fraction of 1e9
+export interface WinningOdds {
+    tickets: anchor.BN,
+    totalTickets: anchor.BN,
+    winProbability: anchor.BN,
+}
@@ -1,32 +1,485 @@
 use anchor_lang::prelude::*;
+use solana_program::keccak;
+use std::cell::RefMut;
 use std::clone::Clone;
 use std::result::Result;
 
 use crate::constants::*;
 use crate::error::*;
 
-#[account]
+#[account(zero_copy)]
 #[derive(Default)]
 pub struct GlobalPool {
-    pub super_admin: Pubkey, // 32
+    pub super_admin: Pubkey,                     // 32
+    pub fee_bps: u64,                            // 8
+    pub treasury: Pubkey,                        // 32
+    pub creation_fee: u64,                       // 8
+    pub staking_program: Pubkey,                 // 32
+    pub stake_tier_count: u64,                   // 8
+    pub stake_tier_mins: [u64; MAX_STAKE_TIERS], // 8*4
+    pub stake_tier_bps: [u64; MAX_STAKE_TIERS],  // 8*4
+    pub referral_bps: u64,                       // 8
+    pub paused: u64,                             // 8
+    pub version: u64,                            // 8
+    // protocol statistics, kept up to date as raffles are created and tickets are sold
+    pub total_raffles: u64,     // 8
+    pub total_tickets: u64,     // 8
+    pub total_volume_sol: u64,  // 8
+    pub total_reap_burned: u64, // 8
+    pub total_fees_sol: u64,    // 8
+    pub active_raffles: u64,    // 8
+    // the last id assigned by allocate_raffle, registered raffles have the ids 1..=raffle_id
+    pub raffle_id: u64, // 8
+    // the super admin proposed by propose_admin, it takes over once it signs accept_admin
+    pub pending_admin: Pubkey, // 32
+}
+
+impl GlobalPool {
+    // raffle creation and ticket sales stop while the protocol is paused
+    pub fn check_not_paused(&self) -> Result<(), ProgramError> {
+        if self.paused == 1 {
+            return Err(RaffleError::ProtocolPaused.into());
+        }
+        Ok(())
+    }
+
+    // propose a new super admin, proposing the default key withdraws a pending proposal
+    pub fn propose_admin(&mut self, admin: Pubkey, new_admin: Pubkey) -> Result<(), ProgramError> {
+        if self.super_admin != admin {
+            return Err(RaffleError::NotSuperAdmin.into());
+        }
+        self.pending_admin = new_admin;
+        Ok(())
+    }
+
+    // hand the super admin role to the proposed admin, returning the previous admin
+    pub fn accept_admin(&mut self, admin: Pubkey) -> Result<Pubkey, ProgramError> {
+        if self.pending_admin == Pubkey::default() || self.pending_admin != admin {
+            return Err(RaffleError::NotPendingAdmin.into());
+        }
+        let previous_admin = self.super_admin;
+        self.super_admin = admin;
+        self.pending_admin = Pubkey::default();
+        Ok(previous_admin)
+    }
+
+    // the discount of the highest stake tier the staked amount reaches
+    pub fn stake_discount(&self, staked: u64) -> u64 {
+        (0..self.stake_tier_count as usize)
+            .rev()
+            .find(|&i| staked >= self.stake_tier_mins[i])
+            .map(|i| self.stake_tier_bps[i])
+            .unwrap_or(0)
+    }
+
+    // count a newly created raffle, it stays active until it is closed
+    pub fn record_raffle(&mut self) -> Result<(), ProgramError> {
+        self.total_raffles = self
+            .total_raffles
+            .checked_add(1)
+            .ok_or(RaffleError::AmountOverflow)?;
+        self.active_raffles += 1;
+        Ok(())
+    }
+
+    // assign the next raffle id of the registry
+    pub fn next_raffle_id(&mut self) -> Result<u64, ProgramError> {
+        self.raffle_id = self
+            .raffle_id
+            .checked_add(1)
+            .ok_or(RaffleError::AmountOverflow)?;
+        Ok(self.raffle_id)
+    }
+
+    // raffles created before the active count existed were never counted in it
+    pub fn record_close(&mut self) {
+        self.active_raffles = self.active_raffles.saturating_sub(1);
+    }
+
+    // count the creation fees and protocol fees paid to the treasury
+    pub fn record_fees(&mut self, fees_sol: u64) -> Result<(), ProgramError> {
+        self.total_fees_sol = self
+            .total_fees_sol
+            .checked_add(fees_sol)
+            .ok_or(RaffleError::AmountOverflow)?;
+        Ok(())
+    }
+
+    // count the tickets of a purchase and the SOL paid for them
+    pub fn record_sale(&mut self, tickets: u64, volume_sol: u64) -> Result<(), ProgramError> {
+        self.total_tickets = self
+            .total_tickets
+            .checked_add(tickets)
+            .ok_or(RaffleError::AmountOverflow)?;
+        self.total_volume_sol = self
+            .total_volume_sol
+            .checked_add(volume_sol)
+            .ok_or(RaffleError::AmountOverflow)?;
+        Ok(())
+    }
+
+    // count the tokens burnt from a ticket payment
+    pub fn record_burn(&mut self, amount: u64) -> Result<(), ProgramError> {
+        self.total_reap_burned = self
+            .total_reap_burned
+            .checked_add(amount)
+            .ok_or(RaffleError::AmountOverflow)?;
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(Default)]
+pub struct WinnerSnapshot {
+    pub raffle: Pubkey,       // 32
+    pub creator: Pubkey,      // 32
+    pub taken_at: i64,        // 8
+    pub winners: Vec<Pubkey>, // 4+32*winner_count
+}
+
+#[account]
+#[derive(Default)]
+pub struct WhitelistProof {
+    pub raffle: Pubkey,  // 32
+    pub winner: Pubkey,  // 32
+    pub claimed_at: i64, // 8
+}
+
+#[account]
+#[derive(Default)]
+pub struct FeeExemption {
+    pub wallet: Pubkey, // 32
+    pub added_at: i64,  // 8
+}
+
+#[account]
+#[derive(Default)]
+pub struct TicketReceipt {
+    pub raffle: Pubkey,  // 32
+    pub buyer: Pubkey,   // 32
+    pub paid_sol: u64,   // 8
+    pub paid_token: u64, // 8
+    pub tickets: u64,    // 8
+}
+
+#[account]
+#[derive(Default)]
+pub struct TicketRecord {
+    pub raffle: Pubkey,         // 32
+    pub buyer: Pubkey,          // 32
+    pub page: u64,              // 8
+    pub slot: u64,              // 8
+    pub tickets: u64,           // 8
+    pub paid_sol: u64,          // 8
+    pub paid_token: u64,        // 8
+    pub first_purchase_at: i64, // 8
+    pub last_purchase_at: i64,  // 8
+}
+
+#[account]
+#[derive(Default)]
+pub struct UserProfile {
+    pub wallet: Pubkey,                         // 32
+    pub raffles_entered: u64,                   // 8
+    pub tickets_bought: u64,                    // 8
+    pub wins: u64,                              // 8
+    pub raffles: [Pubkey; MAX_PROFILE_RAFFLES], // 32*32
+    pub season: u64,                            // 8
+    pub season_tickets: u64,                    // 8
+}
+
+impl UserProfile {
+    // count a raffle the wallet entered, keeping the most recent ones in a ring
+    pub fn add_raffle(&mut self, raffle: Pubkey) {
+        self.raffles[(self.raffles_entered % MAX_PROFILE_RAFFLES as u64) as usize] = raffle;
+        self.raffles_entered += 1;
+    }
+
+    // count tickets bought in the leaderboard's season, restarting the count in a new season
+    pub fn add_season_tickets(&mut self, season: u64, amount: u64) -> Result<u64, ProgramError> {
+        if self.season != season {
+            self.season = season;
+            self.season_tickets = 0;
+        }
+        self.season_tickets = self
+            .season_tickets
+            .checked_add(amount)
+            .ok_or(RaffleError::AmountOverflow)?;
+        Ok(self.season_tickets)
+    }
+}
+
+#[account(zero_copy)]
+pub struct Leaderboard {
+    pub season: u64,                         // 8
+    pub season_start: i64,                   // 8
+    pub count: u64,                          // 8
+    pub wallets: [Pubkey; LEADERBOARD_SIZE], // 32*50
+    pub tickets: [u64; LEADERBOARD_SIZE],    // 8*50
+}
+
+impl Default for Leaderboard {
+    #[inline]
+    fn default() -> Leaderboard {
+        Leaderboard {
+            season: 0,
+            season_start: 0,
+            count: 0,
+            wallets: [Pubkey::default(); LEADERBOARD_SIZE],
+            tickets: [0; LEADERBOARD_SIZE],
+        }
+    }
+}
+
+impl Leaderboard {
+    // clear the rankings for a new season
+    pub fn start_season(&mut self, timestamp: i64) {
+        *self = Leaderboard {
+            season: self.season + 1,
+            season_start: timestamp,
+            ..Leaderboard::default()
+        };
+    }
+
+    // move the wallet up to its rank by season tickets, a wallet new to a full board
+    // replaces the last one if it has more tickets, ties keep the earlier wallet ahead
+    pub fn record(&mut self, wallet: Pubkey, tickets: u64) {
+        let count = self.count as usize;
+        let mut i = match self.wallets[..count].iter().position(|w| *w == wallet) {
+            Some(i) => i,
+            None if count < LEADERBOARD_SIZE => {
+                self.count += 1;
+                count
+            }
+            None if tickets > self.tickets[count - 1] => count - 1,
+            None => return,
+        };
+        while i > 0 && self.tickets[i - 1] < tickets {
+            self.wallets[i] = self.wallets[i - 1];
+            self.tickets[i] = self.tickets[i - 1];
+            i -= 1;
+        }
+        self.wallets[i] = wallet;
+        self.tickets[i] = tickets;
+    }
+}
+
+#[account]
+#[derive(Default)]
+pub struct CreatorStats {
+    pub creator: Pubkey,       // 32
+    pub raffles_created: u64,  // 8
+    pub prizes_delivered: u64, // 8
+    pub volume_sol: u64,       // 8
+    pub cancellations: u64,    // 8
+}
+
+#[account]
+#[derive(Default)]
+pub struct Referrer {
+    pub wallet: Pubkey,     // 32
+    pub registered_at: i64, // 8
+}
+
+#[account]
+#[derive(Default)]
+pub struct EmergencyWithdrawal {
+    pub raffle: Pubkey,      // 32
+    pub source: Pubkey,      // 32
+    pub destination: Pubkey, // 32
+    pub amount: u64,         // 8
+    pub proposed_at: i64,    // 8
+    pub executable_at: i64,  // 8
+}
+
+#[account]
+#[derive(Default)]
+pub struct FreeEntry {
+    pub raffle: Pubkey,  // 32
+    pub entrant: Pubkey, // 32
+    pub entered_at: i64, // 8
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum RandomnessMode {
+    Pseudo,
+    SlotHash,
+    Switchboard,
+    Orao,
+    CommitReveal,
+}
+
+impl RandomnessMode {
+    pub fn from_u64(mode: u64) -> Result<RandomnessMode, ProgramError> {
+        match mode {
+            0 => Ok(RandomnessMode::Pseudo),
+            1 => Ok(RandomnessMode::SlotHash),
+            2 => Ok(RandomnessMode::Switchboard),
+            3 => Ok(RandomnessMode::Orao),
+            4 => Ok(RandomnessMode::CommitReveal),
+            _ => Err(RaffleError::InvalidRandomnessMode.into()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum RaffleStatus {
+    Created,
+    Active,
+    SoldOut,
+    Ended,
+    Revealed,
+    Claimed,
+    Cancelled,
+    Withdrawn,
+}
+
+impl RaffleStatus {
+    pub fn from_u64(status: u64) -> Result<RaffleStatus, ProgramError> {
+        match status {
+            0 => Ok(RaffleStatus::Created),
+            1 => Ok(RaffleStatus::Active),
+            2 => Ok(RaffleStatus::SoldOut),
+            3 => Ok(RaffleStatus::Ended),
+            4 => Ok(RaffleStatus::Revealed),
+            5 => Ok(RaffleStatus::Claimed),
+            6 => Ok(RaffleStatus::Cancelled),
+            7 => Ok(RaffleStatus::Withdrawn),
+            _ => Err(RaffleError::InvalidRaffleStatus.into()),
+        }
+    }
+
+    // Ended is a draw in progress, Claimed has every winner paid out
+    // and Withdrawn has the creator's prizes taken back
+    pub fn can_become(self, next: RaffleStatus) -> bool {
+        use RaffleStatus::*;
+        let drawable = self == Created || self == Active || self == SoldOut || self == Ended;
+        match next {
+            Created => false,
+            Active => self == Created,
+            SoldOut => self == Created || self == Active,
+            Ended | Revealed | Cancelled => drawable,
+            Claimed => self == Revealed,
+            Withdrawn => self == Revealed || self == Claimed || self == Cancelled,
+        }
+    }
 }
 
 #[account(zero_copy)]
 pub struct RafflePool {
-    // 72+64+32*2000+40*50 = 66136
+    // 512+4*3+2+1*2+32*13+352+24*5+40*5+16*4+24*4+56+40+64*50+40*2000 = 85072
     pub creator: Pubkey,                    //32
     pub nft_mint: Pubkey,                   //32
-    pub count: u64,                         //8
-    pub winner_count: u64,                  //8
-    pub no_repeat: u64,                     //8
-    pub max_entrants: u64,                  //8
+    pub count: u32,                         //4
+    pub max_entrants: u32,                  //4
+    pub no_repeat: u32,                     //4
+    pub winner_count: u16,                  //2
+    pub whitelisted: u8,                    //1
+    pub status: u8,                         //1
     pub end_timestamp: i64,                 //8
     pub ticket_price_reap: u64,             //8
     pub ticket_price_sol: u64,              //8
-    pub whitelisted: u64,                   //8
-    pub claimed_winner: [u64; MAX_WINNERS], //50*8
+    pub randomness_mode: u64,               //8
+    pub randomness: Pubkey,                 //32
+    pub commitment: [u8; 32],               //32
+    pub entropy: [u8; 32],                  //32
+    pub draw_seed: [u8; 32],                //32
+    pub revealed_count: u64,                //8
+    pub revealed: u64,                      //8
+    pub crank_tip: u64,                     //8
+    pub prize_type: u64,                    //8
+    pub programmable: u64,                  //8
+    pub prize_amount: u64,                  //8
+    pub prize_count: u64,                   //8
+    pub merkle_tree: Pubkey,                //32
+    pub cnft_data_hash: [u8; 32],           //32
+    pub cnft_creator_hash: [u8; 32],        //32
+    pub cnft_nonce: u64,                    //8
+    pub cnft_index: u64,                    //8
+    pub tier_count: u64,                    //8
+    pub tier_ends: [u64; MAX_TIERS],        //8*5
+    pub tier_types: [u64; MAX_TIERS],       //8*5
+    pub tier_amounts: [u64; MAX_TIERS],     //8*5
+    pub tier_mint: Pubkey,                  //32
+    pub tier_escrowed: u64,                 //8
+    pub edition_base: u64,                  //8
+    pub delegated_account: Pubkey,          //32
+    pub prize_hash: [u8; 32],               //32
+    pub bundle_claimed: u64,                //8
+    pub wl_mint: Pubkey,                    //32
+    pub wl_escrowed: u64,                   //8
+    pub payment_mint: Pubkey,               //32
+    pub payment_decimals: u64,              //8
+    pub burn_bps: u64,                      //8
+    pub treasury_bps: u64,                  //8
+    pub proceeds: u64,                      //8
+    pub split_count: u64,                   //8
+    pub split_payees: [Pubkey; MAX_SPLITS], //32*5
+    pub split_bps: [u64; MAX_SPLITS],       //8*5
+    pub deal_count: u64,                    //8
+    pub deal_sizes: [u64; MAX_DEALS],       //8*4
+    pub deal_paid: [u64; MAX_DEALS],        //8*4
+    pub window_count: u64,                  //8
+    pub window_ends: [i64; MAX_WINDOWS],    //8*4
+    pub window_reap: [u64; MAX_WINDOWS],    //8*4
+    pub window_sol: [u64; MAX_WINDOWS],     //8*4
+    pub auction_start: i64,                 //8
+    pub auction_step: u64,                  //8
+    pub floor_price_reap: u64,              //8
+    pub floor_price_sol: u64,               //8
+    pub buyout_price: u64,                  //8
+    pub bought_out: u64,                    //8
+    pub refund_pool: u64,                   //8
+    pub refunded: u64,                      //8
+    pub cancelled: u64,                     //8
+    pub refundable: u64,                    //8
+    pub held_fees: u64,                     //8
+    pub token_proceeds: u64,                //8
+    pub min_entrants: u64,                  //8
+    pub created_at: i64,                    //8
+    pub start_timestamp: i64,               //8
+    pub sold_out: u64,                      //8
+    pub claim_deadline: i64,                //8
+    pub rerolls: u64,                       //8
+    pub open_receipts: u64,                 //8
+    pub paused: u64,                        //8
+    pub presale_end: i64,                   //8
+    pub presale_mint: Pubkey,               //32
+    pub tickets_remaining: u64,             //8
+    pub sales_end_timestamp: i64,           //8
+    pub draw_after_timestamp: i64,          //8
+    pub snipe_window: i64,                  //8
+    pub max_extensions: u64,                //8
+    pub extensions: u64,                    //8
+    pub holder_collection: Pubkey,          //32
+    pub holder_discount_bps: u64,           //8
+    pub charity: Pubkey,                    //32
+    pub charity_bps: u64,                   //8
+    pub royalty_bps: u64,                   //8
+    pub payment_options: u64,               //8
+    pub usd_price_cents: u64,               //8
+    pub price_feed: Pubkey,                 //32
+    pub usd_peg_token: u64,                 //8
+    pub prize_mints: [Pubkey; MAX_WINNERS], //32*50
+    pub claimed_winners: u64,               //8
     pub winner: [Pubkey; MAX_WINNERS],      //32*50
     pub entrants: [Pubkey; MAX_ENTRANTS],   //32*2000
+    // fields added from version 1 on go last so migrate_account can grow older raffles
+    pub version: u64,                         //8
+    pub name: [u8; 32],                       //32
+    pub uri: [u8; 128],                       //128
+    pub prize_collection: Pubkey,             //32
+    pub entrant_count: u64,                   //8
+    pub entrant_tickets: [u64; MAX_ENTRANTS], //8*2000
+    pub page_count: u64,                      //8
+    pub page_tickets: u64,                    //8
+    pub max_tickets_per_user: u64,            //8
+    pub winner_order: [u8; WINNER_ORDER_LEN], //56
+    pub total_sol_collected: u64,             //8
+    pub total_tokens_burned: u64,             //8
+    pub unique_entrants: u64,                 //8
+    pub raffle_id: u64,                       //8
+    pub end_slot: u64,                        //8
 }
 
 impl Default for RafflePool {
@@ -39,19 +492,1030 @@ impl Default for RafflePool {
             winner_count: 0,
             no_repeat: 0,
             max_entrants: 0,
+            whitelisted: 0,
+            status: 0,
             end_timestamp: 0,
             ticket_price_reap: 0,
             ticket_price_sol: 0,
-            whitelisted: 0,
-            claimed_winner: [0; MAX_WINNERS],
+            randomness_mode: 0,
+            randomness: Pubkey::default(),
+            commitment: [0; 32],
+            entropy: [0; 32],
+            draw_seed: [0; 32],
+            revealed_count: 0,
+            revealed: 0,
+            crank_tip: 0,
+            prize_type: 0,
+            programmable: 0,
+            prize_amount: 0,
+            prize_count: 0,
+            merkle_tree: Pubkey::default(),
+            cnft_data_hash: [0; 32],
+            cnft_creator_hash: [0; 32],
+            cnft_nonce: 0,
+            cnft_index: 0,
+            tier_count: 0,
+            tier_ends: [0; MAX_TIERS],
+            tier_types: [0; MAX_TIERS],
+            tier_amounts: [0; MAX_TIERS],
+            tier_mint: Pubkey::default(),
+            tier_escrowed: 0,
+            edition_base: 0,
+            delegated_account: Pubkey::default(),
+            prize_hash: [0; 32],
+            bundle_claimed: 0,
+            wl_mint: Pubkey::default(),
+            wl_escrowed: 0,
+            payment_mint: Pubkey::default(),
+            payment_decimals: 0,
+            burn_bps: 0,
+            treasury_bps: 0,
+            proceeds: 0,
+            split_count: 0,
+            split_payees: [Pubkey::default(); MAX_SPLITS],
+            split_bps: [0; MAX_SPLITS],
+            deal_count: 0,
+            deal_sizes: [0; MAX_DEALS],
+            deal_paid: [0; MAX_DEALS],
+            window_count: 0,
+            window_ends: [0; MAX_WINDOWS],
+            window_reap: [0; MAX_WINDOWS],
+            window_sol: [0; MAX_WINDOWS],
+            auction_start: 0,
+            auction_step: 0,
+            floor_price_reap: 0,
+            floor_price_sol: 0,
+            buyout_price: 0,
+            bought_out: 0,
+            refund_pool: 0,
+            refunded: 0,
+            cancelled: 0,
+            refundable: 0,
+            held_fees: 0,
+            token_proceeds: 0,
+            min_entrants: 0,
+            created_at: 0,
+            start_timestamp: 0,
+            sold_out: 0,
+            claim_deadline: 0,
+            rerolls: 0,
+            open_receipts: 0,
+            paused: 0,
+            presale_end: 0,
+            presale_mint: Pubkey::default(),
+            tickets_remaining: 0,
+            sales_end_timestamp: 0,
+            draw_after_timestamp: 0,
+            snipe_window: 0,
+            max_extensions: 0,
+            extensions: 0,
+            holder_collection: Pubkey::default(),
+            holder_discount_bps: 0,
+            charity: Pubkey::default(),
+            charity_bps: 0,
+            royalty_bps: 0,
+            payment_options: 0,
+            usd_price_cents: 0,
+            price_feed: Pubkey::default(),
+            usd_peg_token: 0,
+            prize_mints: [Pubkey::default(); MAX_WINNERS],
+            claimed_winners: 0,
             winner: [Pubkey::default(); MAX_WINNERS],
             entrants: [Pubkey::default(); MAX_ENTRANTS],
+            version: 0,
+            name: [0; 32],
+            uri: [0; 128],
+            prize_collection: Pubkey::default(),
+            entrant_count: 0,
+            entrant_tickets: [0; MAX_ENTRANTS],
+            page_count: 0,
+            page_tickets: 0,
+            max_tickets_per_user: 0,
+            winner_order: [0; WINNER_ORDER_LEN],
+            total_sol_collected: 0,
+            total_tokens_burned: 0,
+            unique_entrants: 0,
+            raffle_id: 0,
+            end_slot: 0,
         }
     }
 }
 impl RafflePool {
-    pub fn append(&mut self, buyer: Pubkey) {
-        self.entrants[self.count as usize] = buyer;
-        self.count += 1;
+    // the entrant and winner counters index fixed arrays, a counter past its array's
+    // capacity can only come from corrupted state and stops every write through it
+    pub fn check_capacity(&self) -> Result<(), ProgramError> {
+        if self.entrant_count as usize > MAX_ENTRANTS {
+            return Err(RaffleError::EntrantIndexOutOfBounds.into());
+        }
+        if self.winner_count() as usize > MAX_WINNERS {
+            return Err(RaffleError::WinnerIndexOutOfBounds.into());
+        }
+        Ok(())
+    }
+
+    // give a new wallet the next entrant slot, its TicketRecord or FreeEntry keeps it to one slot
+    pub fn add_entrant(&mut self, wallet: Pubkey, amount: u64) -> Result<(), ProgramError> {
+        let i = self.entrant_count as usize;
+        if i >= MAX_ENTRANTS {
+            return Err(RaffleError::TooManyEntrants.into());
+        }
+        self.entrants[i] = wallet;
+        self.entrant_tickets[i] = amount;
+        self.entrant_count += 1;
+        self.add_tickets(amount)
+    }
+
+    // add tickets to the wallet's entrant slot
+    pub fn add_slot_tickets(
+        &mut self,
+        wallet: Pubkey,
+        index: usize,
+        amount: u64,
+    ) -> Result<(), ProgramError> {
+        self.check_capacity()?;
+        if index >= self.entrant_count as usize || self.entrants[index] != wallet {
+            return Err(RaffleError::InvalidTicketRecord.into());
+        }
+        self.entrant_tickets[index] = self.entrant_tickets[index]
+            .checked_add(amount)
+            .ok_or(RaffleError::InvalidCalculation)?;
+        self.add_tickets(amount)
+    }
+
+    // count tickets added to one of the raffle's entrant pages
+    pub fn add_page_tickets(&mut self, amount: u64) -> Result<(), ProgramError> {
+        self.page_tickets = self
+            .page_tickets
+            .checked_add(amount)
+            .ok_or(RaffleError::InvalidCalculation)?;
+        self.add_tickets(amount)
+    }
+
+    // count tickets entered into the raffle's entrant slots or one of its entrant pages
+    pub fn add_tickets(&mut self, amount: u64) -> Result<(), ProgramError> {
+        let count = self
+            .count()
+            .checked_add(amount)
+            .ok_or(RaffleError::InvalidCalculation)?;
+        self.set_count(count)?;
+        self.tickets_remaining = self
+            .max_entrants()
+            .checked_sub(count)
+            .ok_or(RaffleError::InvalidCalculation)?;
+        if self.count() >= self.max_entrants() {
+            self.sold_out = 1;
+            return self.set_status(RaffleStatus::SoldOut);
+        }
+        self.set_status(RaffleStatus::Active)
+    }
+
+    // the entrant slot of the wallet, if it has entered
+    pub fn entrant_index(&self, wallet: &Pubkey) -> Option<usize> {
+        (0..self.entrant_count as usize).find(|&i| self.entrants[i] == *wallet)
+    }
+
+    // the entrant slot holding the given ticket, tickets are numbered through the slots in order
+    pub fn ticket_owner(&self, ticket: u64) -> usize {
+        let mut tickets = 0;
+        for i in 0..self.entrant_count as usize {
+            tickets += self.entrant_tickets[i];
+            if ticket < tickets {
+                return i;
+            }
+        }
+        self.entrant_count as usize - 1
+    }
+
+    // take one ticket of the entrant slot out of the draw, freeing the slot with its last ticket
+    pub fn remove_ticket(&mut self, index: usize) -> Result<Pubkey, ProgramError> {
+        let wallet = self.entrants[index];
+        self.entrant_tickets[index] -= 1;
+        self.set_count(self.count() - 1)?;
+        if self.entrant_tickets[index] == 0 {
+            let last = self.entrant_count as usize - 1;
+            self.entrants[index] = self.entrants[last];
+            self.entrant_tickets[index] = self.entrant_tickets[last];
+            self.entrants[last] = Pubkey::default();
+            self.entrant_tickets[last] = 0;
+            self.entrant_count -= 1;
+        }
+        Ok(wallet)
+    }
+
+    // take the given ticket out of the draw, tickets past the raffle's own entrant slots
+    // are numbered on through its entrant pages in page order
+    pub fn take_ticket(
+        &mut self,
+        ticket: u64,
+        pages: &mut [RefMut<EntrantPage>],
+    ) -> Result<Pubkey, ProgramError> {
+        let slot_tickets = self.count() - self.page_tickets;
+        if ticket < slot_tickets {
+            let index = self.ticket_owner(ticket);
+            return self.remove_ticket(index);
+        }
+        let mut ticket = ticket - slot_tickets;
+        for page in pages.iter_mut() {
+            if ticket < page.tickets {
+                page.check_capacity()?;
+                let index = page.ticket_owner(ticket);
+                self.set_count(self.count() - 1)?;
+                self.page_tickets -= 1;
+                return Ok(page.remove_ticket(index));
+            }
+            ticket -= page.tickets;
+        }
+        Err(RaffleError::InvalidEntrantPage.into())
+    }
+
+    // give each entry of a raffle created before weighted entrants its own slot with one ticket
+    pub fn index_legacy_entrants(&mut self) {
+        for i in 0..std::cmp::min(self.count() as usize, MAX_ENTRANTS) {
+            if self.entrants[i] != Pubkey::default() {
+                self.entrant_tickets[i] = 1;
+            }
+        }
+        self.entrant_count = self.count();
+    }
+
+    // the counters are packed into small fields, these widen them for arithmetic
+    pub fn count(&self) -> u64 {
+        self.count as u64
+    }
+
+    pub fn set_count(&mut self, count: u64) -> Result<(), ProgramError> {
+        if count > u32::MAX as u64 {
+            return Err(RaffleError::TicketCountTooLarge.into());
+        }
+        self.count = count as u32;
+        Ok(())
+    }
+
+    pub fn max_entrants(&self) -> u64 {
+        self.max_entrants as u64
+    }
+
+    pub fn set_max_entrants(&mut self, max_entrants: u64) -> Result<(), ProgramError> {
+        if max_entrants > u32::MAX as u64 {
+            return Err(RaffleError::MaxEntrantsTooLarge.into());
+        }
+        self.max_entrants = max_entrants as u32;
+        Ok(())
+    }
+
+    pub fn winner_count(&self) -> u64 {
+        self.winner_count as u64
+    }
+
+    pub fn set_winner_count(&mut self, winner_count: u64) -> Result<(), ProgramError> {
+        if winner_count > u16::MAX as u64 {
+            return Err(RaffleError::TooManyWinners.into());
+        }
+        self.winner_count = winner_count as u16;
+        Ok(())
+    }
+
+    pub fn status(&self) -> Result<RaffleStatus, ProgramError> {
+        RaffleStatus::from_u64(self.status as u64)
+    }
+
+    // move to the given status, failing unless the current status can become it
+    pub fn set_status(&mut self, next: RaffleStatus) -> Result<(), ProgramError> {
+        let status = self.status()?;
+        if status != next && !status.can_become(next) {
+            return Err(RaffleError::InvalidStatusTransition.into());
+        }
+        self.status = next as u8;
+        Ok(())
+    }
+
+    // whether the escrowed prizes can be shared between the given number of winners
+    // editions, bundles and single prize raffles keep their winner count
+    pub fn winner_count_allowed(&self, winner_count: u64) -> bool {
+        if winner_count == self.winner_count() {
+            return true;
+        }
+        if winner_count == 0 || self.tier_count > 0 {
+            return false;
+        }
+        match self.prize_type {
+            PRIZE_TOKEN | PRIZE_SOL => self.prize_amount >= winner_count,
+            PRIZE_NFT if self.whitelisted == 1 => winner_count <= self.prize_count,
+            PRIZE_NFT => self.wl_escrowed == 0,
+            _ => false,
+        }
+    }
+
+    // reveal the drawn winners, unclaimed prizes can be reclaimed after the claim window
+    pub fn finish_draw(&mut self, timestamp: i64) -> Result<(), ProgramError> {
+        self.set_status(RaffleStatus::Revealed)?;
+        self.sort_winners()?;
+        self.claim_deadline = timestamp + CLAIM_WINDOW;
+        Ok(())
+    }
+
+    // tickets can only be bought before the first sellout, draw or cancellation
+    pub fn selling(&self) -> Result<bool, ProgramError> {
+        let status = self.status()?;
+        Ok(status == RaffleStatus::Created || status == RaffleStatus::Active)
+    }
+
+    // a revealed raffle becomes claimed once every winner claimed their prize
+    pub fn mark_claimed(&mut self) {
+        if self.status == RaffleStatus::Revealed as u8 && self.all_claimed() {
+            self.status = RaffleStatus::Claimed as u8;
+        }
+    }
+
+    // unclaimed prizes can be reclaimed or rerolled once the claim window has passed
+    pub fn claim_window_closed(&self, timestamp: i64) -> bool {
+        self.claim_deadline != 0 && timestamp >= self.claim_deadline
+    }
+
+    // a sold out raffle can be drawn right away without waiting for its end timestamp
+    // unless a draw timestamp holds the draw back for a verification window
+    pub fn ended(&self, timestamp: i64) -> bool {
+        if self.draw_after_timestamp > 0 {
+            return timestamp >= self.draw_after_timestamp;
+        }
+        timestamp >= self.end_timestamp || self.sold_out == 1
+    }
+
+    // push the end back by the snipe window when a ticket is bought within its final snipe window
+    pub fn extend_for_snipe(&mut self, timestamp: i64) -> bool {
+        if self.snipe_window == 0
+            || self.extensions >= self.max_extensions
+            || timestamp < self.end_timestamp - self.snipe_window
+            || self.end_timestamp + self.snipe_window - self.created_at > MAX_RAFFLE_DURATION
+        {
+            return false;
+        }
+        self.end_timestamp += self.snipe_window;
+        self.extensions += 1;
+        true
+    }
+
+    // ticket sales close at the sales end timestamp, or the end timestamp when it is not set
+    pub fn sales_end(&self) -> i64 {
+        if self.sales_end_timestamp > 0 {
+            self.sales_end_timestamp
+        } else {
+            self.end_timestamp
+        }
+    }
+
+    // fold a buyer's nonce and purchase slot into the accumulated entropy
+    pub fn add_entropy(&mut self, buyer: Pubkey, nonce: &[u8], slot: u64) {
+        self.entropy =
+            keccak::hashv(&[&self.entropy, &buyer.to_bytes(), nonce, &slot.to_le_bytes()])
+                .to_bytes();
+    }
+
+    pub fn prize_index(&self, prize_mint: Pubkey) -> Option<usize> {
+        (0..self.prize_count as usize).find(|&i| self.prize_mints[i] == prize_mint)
+    }
+
+    // whether the global authority escrows the mint as one of this raffle's prizes
+    pub fn escrows_mint(&self, mint: &Pubkey) -> bool {
+        *mint != Pubkey::default()
+            && (self.nft_mint == *mint
+                || self.tier_mint == *mint
+                || self.wl_mint == *mint
+                || self.prize_index(*mint).is_some())
+    }
+
+    // claims are one bit per winner slot
+    pub fn claimed(&self, winner_index: usize) -> bool {
+        winner_index < MAX_WINNERS && self.claimed_winners & (1 << winner_index) != 0
+    }
+
+    pub fn set_claimed(&mut self, winner_index: usize) -> Result<(), ProgramError> {
+        if winner_index >= MAX_WINNERS {
+            return Err(RaffleError::WinnerIndexOutOfBounds.into());
+        }
+        self.claimed_winners |= 1 << winner_index;
+        Ok(())
+    }
+
+    pub fn clear_claimed(&mut self, winner_index: usize) -> Result<(), ProgramError> {
+        if winner_index >= MAX_WINNERS {
+            return Err(RaffleError::WinnerIndexOutOfBounds.into());
+        }
+        self.claimed_winners &= !(1 << winner_index);
+        Ok(())
+    }
+
+    // order the winner slots by winner, slots of the same winner stay in slot order
+    pub fn sort_winners(&mut self) -> Result<(), ProgramError> {
+        self.check_capacity()?;
+        let winner_count = self.winner_count() as usize;
+        let mut order: Vec<u8> = (0..winner_count as u8).collect();
+        order.sort_by_key(|&i| self.winner[i as usize]);
+        self.winner_order[..winner_count].copy_from_slice(&order);
+        Ok(())
+    }
+
+    // the claimer's winner slots in slot order, found by binary search over the sorted winners
+    pub fn winner_slots(&self, claimer: Pubkey) -> impl Iterator<Item = usize> + '_ {
+        let order = &self.winner_order[..self.winner_count() as usize];
+        let start = order.partition_point(|&i| self.winner[i as usize] < claimer);
+        order[start..]
+            .iter()
+            .map(|&i| i as usize)
+            .take_while(move |&i| self.winner[i] == claimer)
+    }
+
+    pub fn unclaimed_winner_index(&self, claimer: Pubkey) -> Option<usize> {
+        self.winner_slots(claimer).find(|&i| !self.claimed(i))
+    }
+
+    pub fn unclaimed_winners(&self) -> u64 {
+        (0..self.winner_count() as usize)
+            .filter(|&i| !self.claimed(i))
+            .count() as u64
+    }
+
+    pub fn all_claimed(&self) -> bool {
+        (0..self.winner_count() as usize).all(|i| self.claimed(i))
+    }
+
+    // raffles without payment options charge both prices together
+    pub fn payment_allowed(&self, pay_with: u64) -> bool {
+        // wSOL pays the SOL price from a token account
+        let pay_with = if pay_with == PAY_WSOL {
+            PAY_SOL
+        } else {
+            pay_with
+        };
+        let options = if self.payment_options == 0 {
+            PAY_BOTH
+        } else {
+            self.payment_options
+        };
+        (pay_with == PAY_SOL || pay_with == PAY_TOKEN || pay_with == PAY_BOTH)
+            && options & pay_with != 0
+    }
+
+    // whether buyers can pay the token price alone or together with the SOL price
+    pub fn accepts_token_payment(&self) -> bool {
+        self.payment_allowed(PAY_TOKEN) || self.payment_allowed(PAY_BOTH)
+    }
+
+    // zero-price raffles are entered once per wallet through a FreeEntry PDA
+    pub fn free_entry(&self) -> bool {
+        self.ticket_price_reap == 0
+            && self.ticket_price_sol == 0
+            && self.usd_price_cents == 0
+            && self.window_count == 0
+    }
+
+    // a mystery prize stays hidden until the creator reveals and escrows it
+    pub fn prize_hidden(&self) -> bool {
+        self.prize_hash != [0; 32] && self.prize_count == 0
+    }
+
+    // bundle prizes are claimed one mint at a time, one bit per prize index
+    pub fn bundle_claimed(&self, prize_index: usize) -> bool {
+        self.bundle_claimed & (1 << prize_index) != 0
+    }
+
+    // the prize tier of a winner slot, tiers cover consecutive winner ranges
+    pub fn tier_index(&self, winner_index: usize) -> Option<usize> {
+        (0..self.tier_count as usize).find(|&i| (winner_index as u64) < self.tier_ends[i])
+    }
+
+    // tier tokens still owed to the drawn winners
+    pub fn tier_tokens_owed(&self) -> u64 {
+        (0..self.winner_count() as usize)
+            .filter(|&i| !self.claimed(i))
+            .filter_map(|i| self.tier_index(i))
+            .filter(|&tier| self.tier_types[tier] == PRIZE_TOKEN)
+            .map(|tier| self.tier_amounts[tier])
+            .sum()
+    }
+
+    // the winner slot a claimer names, it must be theirs and still unclaimed
+    pub fn claimable_slot(
+        &self,
+        claimer: Pubkey,
+        winner_index: u64,
+    ) -> Result<usize, ProgramError> {
+        if winner_index >= self.winner_count() || self.winner[winner_index as usize] != claimer {
+            return Err(RaffleError::NotWinner.into());
+        }
+        if self.claimed(winner_index as usize) {
+            return Err(RaffleError::NoPrize.into());
+        }
+        Ok(winner_index as usize)
+    }
+
+    // fix the draw seed and clamp winner_count to the entrants
+    pub fn start_draw(&mut self, seed: [u8; 32]) -> Result<(), ProgramError> {
+        if self.count() < self.winner_count() {
+            self.set_winner_count(self.count())?;
+        }
+        self.draw_seed = seed;
+        Ok(())
+    }
+
+    // draw up to amount winners from the draw seed and the accumulated entropy,
+    // removing each winner from the entrants
+    pub fn draw_next_winners(
+        &mut self,
+        amount: u64,
+        pages: &mut [RefMut<EntrantPage>],
+    ) -> Result<(), ProgramError> {
+        self.check_capacity()?;
+        let end = std::cmp::min(self.revealed_count + amount, self.winner_count());
+        for j in self.revealed_count..end {
+            let hash =
+                keccak::hashv(&[&self.draw_seed, &self.entropy, &j.to_le_bytes()]).to_bytes();
+            let mut index_bytes = [0u8; 8];
+            index_bytes.copy_from_slice(&hash[0..8]);
+            let ticket = u64::from_le_bytes(index_bytes) % self.count();
+            self.winner[j as usize] = self.take_ticket(ticket, pages)?;
+        }
+        self.revealed_count = end;
+        if self.revealed_count == self.winner_count() {
+            self.revealed = 1;
+        }
+        Ok(())
+    }
+
+    pub fn draw_winners(
+        &mut self,
+        seed: [u8; 32],
+        pages: &mut [RefMut<EntrantPage>],
+    ) -> Result<(), ProgramError> {
+        self.start_draw(seed)?;
+        self.draw_next_winners(self.winner_count(), pages)
+    }
+
+    // whether every prize, proceed and refund has been paid out so the raffle can be closed
+    pub fn settled(&self) -> Result<bool, ProgramError> {
+        let status = self.status()?;
+        if status != RaffleStatus::Claimed
+            && status != RaffleStatus::Withdrawn
+            && status != RaffleStatus::Cancelled
+        {
+            return Ok(false);
+        }
+        if self.proceeds != 0 || self.held_fees != 0 || self.token_proceeds != 0 {
+            return Ok(false);
+        }
+        if self.cancelled == 1 && self.open_receipts != 0 {
+            return Ok(false);
+        }
+        // ticket holders refunded after a buyout have their entries cleared
+        if self.bought_out == 1
+            && (0..self.entrant_count as usize).any(|i| self.entrant_tickets[i] != 0)
+        {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    // redraw an unclaimed winner slot from the remaining entrants, removing the new winner
+    // each reroll mixes its count and slot into the draw seed
+    pub fn reroll(
+        &mut self,
+        winner_index: usize,
+        slot: u64,
+        pages: &mut [RefMut<EntrantPage>],
+    ) -> Result<(), ProgramError> {
+        self.check_capacity()?;
+        if winner_index as u64 >= self.winner_count() {
+            return Err(RaffleError::WinnerIndexOutOfBounds.into());
+        }
+        if self.count() == 0 {
+            return Err(RaffleError::NoEntrantsLeft.into());
+        }
+        let hash = keccak::hashv(&[
+            &self.draw_seed,
+            &self.entropy,
+            &(winner_index as u64).to_le_bytes(),
+            &self.rerolls.to_le_bytes(),
+            &slot.to_le_bytes(),
+        ])
+        .to_bytes();
+        let mut index_bytes = [0u8; 8];
+        index_bytes.copy_from_slice(&hash[0..8]);
+        let ticket = u64::from_le_bytes(index_bytes) % self.count();
+        self.winner[winner_index] = self.take_ticket(ticket, pages)?;
+        self.sort_winners()?;
+        self.clear_claimed(winner_index)?;
+        self.rerolls += 1;
+        Ok(())
+    }
+
+    // raffle PDAs set up by allocate_raffle are reserved for the creator that allocated them
+    pub fn check_allocation(&self, creator: &Pubkey) -> Result<(), ProgramError> {
+        if self.creator != Pubkey::default() && self.creator != *creator {
+            return Err(RaffleError::NotCreator.into());
+        }
+        Ok(())
+    }
+
+    // copy the sale parameters of an existing raffle, moving its schedule by shift seconds
+    pub fn copy_params(&mut self, source: &RafflePool, shift: i64) {
+        self.ticket_price_reap = source.ticket_price_reap;
+        self.ticket_price_sol = source.ticket_price_sol;
+        self.end_timestamp = source.end_timestamp + shift;
+        self.max_entrants = source.max_entrants;
+        self.tickets_remaining = source.max_entrants();
+        self.winner_count = source.winner_count;
+        self.no_repeat = source.no_repeat;
+        self.whitelisted = source.whitelisted;
+        self.randomness_mode = source.randomness_mode;
+        self.crank_tip = source.crank_tip;
+        self.programmable = source.programmable;
+        self.payment_mint = source.payment_mint;
+        self.payment_decimals = source.payment_decimals;
+        self.burn_bps = source.burn_bps;
+        self.treasury_bps = source.treasury_bps;
+        self.split_count = source.split_count;
+        self.split_payees = source.split_payees;
+        self.split_bps = source.split_bps;
+        self.deal_count = source.deal_count;
+        self.deal_sizes = source.deal_sizes;
+        self.deal_paid = source.deal_paid;
+        self.window_count = source.window_count;
+        for i in 0..source.window_count as usize {
+            self.window_ends[i] = source.window_ends[i] + shift;
+            self.window_reap[i] = source.window_reap[i];
+            self.window_sol[i] = source.window_sol[i];
+        }
+        if source.auction_start > 0 {
+            self.auction_start = source.auction_start + shift;
+        }
+        self.auction_step = source.auction_step;
+        self.floor_price_reap = source.floor_price_reap;
+        self.floor_price_sol = source.floor_price_sol;
+        self.buyout_price = source.buyout_price;
+        self.refundable = source.refundable;
+        self.min_entrants = source.min_entrants;
+        if source.start_timestamp > 0 {
+            self.start_timestamp = source.start_timestamp + shift;
+        }
+        if source.presale_end > 0 {
+            self.presale_end = source.presale_end + shift;
+        }
+        if source.sales_end_timestamp > 0 {
+            self.sales_end_timestamp = source.sales_end_timestamp + shift;
+        }
+        if source.draw_after_timestamp > 0 {
+            self.draw_after_timestamp = source.draw_after_timestamp + shift;
+        }
+        self.presale_mint = source.presale_mint;
+        self.snipe_window = source.snipe_window;
+        self.max_extensions = source.max_extensions;
+        self.max_tickets_per_user = source.max_tickets_per_user;
+        self.holder_collection = source.holder_collection;
+        self.holder_discount_bps = source.holder_discount_bps;
+        self.charity = source.charity;
+        self.charity_bps = source.charity_bps;
+        self.royalty_bps = source.royalty_bps;
+        self.payment_options = source.payment_options;
+        self.usd_price_cents = source.usd_price_cents;
+        self.price_feed = source.price_feed;
+        self.usd_peg_token = source.usd_peg_token;
+        self.name = source.name;
+        self.uri = source.uri;
+    }
+
+    // register the revenue split payees and their shares of the SOL proceeds
+    pub fn set_splits(&mut self, payees: &[Pubkey], bps: &[u64]) -> Result<(), ProgramError> {
+        if payees.len() != bps.len() || payees.len() > MAX_SPLITS {
+            return Err(RaffleError::InvalidSplits.into());
+        }
+        let total_bps = bps
+            .iter()
+            .try_fold(0u64, |total, &bps| total.checked_add(bps));
+        if total_bps.map_or(true, |total| total > MAX_FEE_BPS) {
+            return Err(RaffleError::InvalidSplits.into());
+        }
+        self.split_payees[..payees.len()].copy_from_slice(payees);
+        self.split_bps[..bps.len()].copy_from_slice(bps);
+        self.split_count = payees.len() as u64;
+        Ok(())
+    }
+
+    // the split, charity and royalty shares of the SOL proceeds in basis points, None on overflow
+    pub fn payout_bps(&self, charity_bps: u64, royalty_bps: u64) -> Option<u64> {
+        self.split_bps[..self.split_count as usize]
+            .iter()
+            .try_fold(charity_bps, |total, &bps| total.checked_add(bps))?
+            .checked_add(royalty_bps)
+    }
+
+    // the ticket prices (reap, sol) of the price window active at the given time
+    // the base prices, or the dutch auction prices, apply once every window has ended
+    pub fn ticket_prices(&self, timestamp: i64) -> Result<(u64, u64), ProgramError> {
+        match (0..self.window_count as usize).find(|&i| timestamp < self.window_ends[i]) {
+            Some(i) => Ok((self.window_reap[i], self.window_sol[i])),
+            None if self.auction_start > 0 => Ok((
+                self.auction_price(self.ticket_price_reap, self.floor_price_reap, timestamp)?,
+                self.auction_price(self.ticket_price_sol, self.floor_price_sol, timestamp)?,
+            )),
+            None => Ok((self.ticket_price_reap, self.ticket_price_sol)),
+        }
+    }
+
+    // decline the price from start to floor over the auction, in steps if auction_step is set
+    // a floor above the start price fails instead of wrapping
+    pub fn auction_price(
+        &self,
+        start: u64,
+        floor: u64,
+        timestamp: i64,
+    ) -> Result<u64, ProgramError> {
+        let duration = (self.end_timestamp - self.auction_start) as u128;
+        let mut elapsed = (timestamp - self.auction_start).max(0) as u128;
+        if duration == 0 || elapsed >= duration {
+            return Ok(floor);
+        }
+        if self.auction_step > 0 {
+            elapsed -= elapsed % self.auction_step as u128;
+        }
+        let range = start
+            .checked_sub(floor)
+            .ok_or(RaffleError::InvalidCalculation)?;
+        Ok(start - (range as u128 * elapsed / duration) as u64)
+    }
+
+    // end the raffle at the given time without winners, the escrowed payments go back to the buyers
+    pub fn cancel(&mut self, timestamp: i64) -> Result<(), ProgramError> {
+        self.set_status(RaffleStatus::Cancelled)?;
+        self.cancelled = 1;
+        self.end_timestamp = timestamp;
+        self.set_winner_count(0)?;
+        self.revealed = 1;
+        self.proceeds = 0;
+        self.held_fees = 0;
+        self.token_proceeds = 0;
+        Ok(())
+    }
+
+    // the number of tickets paid for when buying the given amount
+    // the largest bundle deals are applied first and the rest are paid at full price
+    // a zero sized deal fails instead of dividing by zero
+    pub fn paid_tickets(&self, amount: u64) -> Result<u64, ProgramError> {
+        let mut left = amount;
+        let mut paid: u64 = 0;
+        for i in (0..self.deal_count as usize).rev() {
+            let bundles = left
+                .checked_div(self.deal_sizes[i])
+                .ok_or(RaffleError::InvalidCalculation)?;
+            paid = bundles
+                .checked_mul(self.deal_paid[i])
+                .and_then(|bundle_paid| paid.checked_add(bundle_paid))
+                .ok_or(RaffleError::InvalidCalculation)?;
+            left %= self.deal_sizes[i];
+        }
+        let paid = paid
+            .checked_add(left)
+            .ok_or(RaffleError::InvalidCalculation)?;
+        Ok(paid)
+    }
+
+    // set the given entrants as winners, removing each winner from the entrants
+    pub fn force_winners(&mut self, winners: &[Pubkey]) -> Result<(), ProgramError> {
+        if winners.len() > MAX_WINNERS {
+            return Err(RaffleError::TooManyWinners.into());
+        }
+        self.check_capacity()?;
+        for (j, winner) in winners.iter().enumerate() {
+            let index = self
+                .entrant_index(winner)
+                .ok_or(RaffleError::WinnerNotEntrant)?;
+            self.winner[j] = self.remove_ticket(index)?;
+        }
+        self.set_winner_count(winners.len() as u64)?;
+        self.revealed_count = self.winner_count();
+        self.revealed = 1;
+        Ok(())
+    }
+}
+
+// overflow entrant slots of a raffle whose own entrant slots are full
+#[account(zero_copy)]
+pub struct EntrantPage {
+    // 56+40*250 = 10056
+    pub raffle: Pubkey,                            //32
+    pub page_index: u64,                           //8
+    pub entrant_count: u64,                        //8
+    pub tickets: u64,                              //8
+    pub entrants: [Pubkey; MAX_PAGE_ENTRANTS],     //32*250
+    pub entrant_tickets: [u64; MAX_PAGE_ENTRANTS], //8*250
+}
+
+impl EntrantPage {
+    // a page's entrant counter past its capacity can only come from corrupted state
+    pub fn check_capacity(&self) -> Result<(), ProgramError> {
+        if self.entrant_count as usize > MAX_PAGE_ENTRANTS {
+            return Err(RaffleError::EntrantIndexOutOfBounds.into());
+        }
+        Ok(())
+    }
+
+    // give a new wallet the next slot of this page
+    pub fn add_entrant(&mut self, wallet: Pubkey, amount: u64) -> Result<(), ProgramError> {
+        let i = self.entrant_count as usize;
+        if i >= MAX_PAGE_ENTRANTS {
+            return Err(RaffleError::TooManyEntrants.into());
+        }
+        self.entrants[i] = wallet;
+        self.entrant_tickets[i] = amount;
+        self.entrant_count += 1;
+        self.tickets += amount;
+        Ok(())
+    }
+
+    // add tickets to the wallet's slot of this page
+    pub fn add_slot_tickets(
+        &mut self,
+        wallet: Pubkey,
+        index: usize,
+        amount: u64,
+    ) -> Result<(), ProgramError> {
+        self.check_capacity()?;
+        if index >= self.entrant_count as usize || self.entrants[index] != wallet {
+            return Err(RaffleError::InvalidTicketRecord.into());
+        }
+        self.entrant_tickets[index] += amount;
+        self.tickets += amount;
+        Ok(())
+    }
+
+    // the slot of this page holding the given ticket of the page
+    pub fn ticket_owner(&self, ticket: u64) -> usize {
+        let mut tickets = 0;
+        for i in 0..self.entrant_count as usize {
+            tickets += self.entrant_tickets[i];
+            if ticket < tickets {
+                return i;
+            }
+        }
+        self.entrant_count as usize - 1
+    }
+
+    // take one ticket of the slot out of the draw, freeing the slot with its last ticket
+    pub fn remove_ticket(&mut self, index: usize) -> Pubkey {
+        let wallet = self.entrants[index];
+        self.entrant_tickets[index] -= 1;
+        self.tickets -= 1;
+        if self.entrant_tickets[index] == 0 {
+            let last = self.entrant_count as usize - 1;
+            self.entrants[index] = self.entrants[last];
+            self.entrant_tickets[index] = self.entrant_tickets[last];
+            self.entrants[last] = Pubkey::default();
+            self.entrant_tickets[last] = 0;
+            self.entrant_count -= 1;
+        }
+        wallet
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auction(step: u64) -> RafflePool {
+        RafflePool {
+            auction_start: 100,
+            end_timestamp: 200,
+            auction_step: step,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn auction_price_declines_from_start_to_floor() {
+        let raffle = auction(0);
+        assert_eq!(raffle.auction_price(1_000, 500, 50).unwrap(), 1_000);
+        assert_eq!(raffle.auction_price(1_000, 500, 100).unwrap(), 1_000);
+        assert_eq!(raffle.auction_price(1_000, 500, 150).unwrap(), 750);
+        assert_eq!(raffle.auction_price(1_000, 500, 200).unwrap(), 500);
+        assert_eq!(raffle.auction_price(1_000, 500, 300).unwrap(), 500);
+    }
+
+    #[test]
+    fn auction_price_declines_in_steps() {
+        let raffle = auction(30);
+        assert_eq!(raffle.auction_price(1_000, 500, 129).unwrap(), 1_000);
+        assert_eq!(raffle.auction_price(1_000, 500, 150).unwrap(), 850);
+        assert_eq!(raffle.auction_price(1_000, 500, 170).unwrap(), 700);
+    }
+
+    #[test]
+    fn auction_price_rejects_a_floor_above_the_start() {
+        assert!(auction(0).auction_price(500, 1_000, 150).is_err());
+    }
+
+    #[test]
+    fn auction_price_is_the_floor_without_a_duration() {
+        let raffle = RafflePool {
+            auction_start: 100,
+            end_timestamp: 100,
+            ..Default::default()
+        };
+        assert_eq!(raffle.auction_price(1_000, 500, 100).unwrap(), 500);
+    }
+
+    #[test]
+    fn claim_window_closes_at_the_claim_deadline() {
+        let mut raffle = RafflePool::default();
+        assert!(!raffle.claim_window_closed(1_000));
+        raffle.claim_deadline = 1_000;
+        assert!(!raffle.claim_window_closed(999));
+        assert!(raffle.claim_window_closed(1_000));
+    }
+
+    #[test]
+    fn payout_bps_sums_splits_charity_and_royalty() {
+        let mut raffle = RafflePool::default();
+        let payees = [Pubkey::new_unique(), Pubkey::new_unique()];
+        raffle.set_splits(&payees, &[1_000, 500]).unwrap();
+        assert_eq!(raffle.payout_bps(250, 100), Some(1_850));
+        assert_eq!(raffle.payout_bps(u64::MAX, 0), None);
+        assert_eq!(raffle.payout_bps(0, u64::MAX), None);
+    }
+
+    #[test]
+    fn packed_counters_reject_values_that_do_not_fit() {
+        let mut raffle = RafflePool::default();
+        raffle.set_count(u32::MAX as u64).unwrap();
+        assert_eq!(raffle.count(), u32::MAX as u64);
+        assert!(raffle.set_count(u32::MAX as u64 + 1).is_err());
+        raffle.set_winner_count(u16::MAX as u64).unwrap();
+        assert_eq!(raffle.winner_count(), u16::MAX as u64);
+        assert!(raffle.set_winner_count(u16::MAX as u64 + 1).is_err());
+    }
+
+    #[test]
+    fn add_tickets_rejects_tickets_past_max_entrants() {
+        let mut raffle = RafflePool::default();
+        raffle.set_max_entrants(10).unwrap();
+        raffle.add_tickets(4).unwrap();
+        assert_eq!(raffle.tickets_remaining, 6);
+        assert!(raffle.add_tickets(7).is_err());
+        assert!(raffle.add_page_tickets(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn only_sol_only_raffles_refuse_token_payment() {
+        let mut raffle = RafflePool::default();
+        assert!(raffle.accepts_token_payment());
+        raffle.payment_options = PAY_SOL;
+        assert!(!raffle.accepts_token_payment());
+        raffle.payment_options = PAY_SOL | PAY_TOKEN;
+        assert!(raffle.accepts_token_payment());
+    }
+
+    #[test]
+    fn admin_role_moves_once_the_proposed_admin_accepts() {
+        let admin = Pubkey::new_unique();
+        let new_admin = Pubkey::new_unique();
+        let mut global = GlobalPool {
+            super_admin: admin,
+            ..Default::default()
+        };
+        assert!(global.propose_admin(new_admin, new_admin).is_err());
+        assert!(global.accept_admin(new_admin).is_err());
+
+        global.propose_admin(admin, new_admin).unwrap();
+        assert_eq!(global.super_admin, admin);
+        assert!(global.accept_admin(Pubkey::new_unique()).is_err());
+        assert_eq!(global.accept_admin(new_admin).unwrap(), admin);
+        assert_eq!(global.super_admin, new_admin);
+        assert_eq!(global.pending_admin, Pubkey::default());
+        assert!(global.accept_admin(new_admin).is_err());
+    }
+
+    #[test]
+    fn proposing_the_default_key_withdraws_the_proposal() {
+        let admin = Pubkey::new_unique();
+        let mut global = GlobalPool {
+            super_admin: admin,
+            ..Default::default()
+        };
+        global.propose_admin(admin, Pubkey::new_unique()).unwrap();
+        global.propose_admin(admin, Pubkey::default()).unwrap();
+        assert!(global.accept_admin(Pubkey::default()).is_err());
+        assert_eq!(global.super_admin, admin);
+    }
+
+    #[test]
+    fn escrows_mint_matches_the_raffle_prize_mints_only() {
+        let mut raffle = RafflePool::default();
+        let nft_mint = Pubkey::new_unique();
+        let bundle_mint = Pubkey::new_unique();
+        raffle.nft_mint = nft_mint;
+        raffle.prize_mints[0] = bundle_mint;
+        raffle.prize_count = 1;
+        assert!(raffle.escrows_mint(&nft_mint));
+        assert!(raffle.escrows_mint(&bundle_mint));
+        assert!(!raffle.escrows_mint(&Pubkey::new_unique()));
+        assert!(!raffle.escrows_mint(&Pubkey::default()));
     }
 }
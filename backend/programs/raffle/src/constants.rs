@@ -1,6 +1,89 @@
 pub const GLOBAL_AUTHORITY_SEED: &str = "global-authority";
-pub const RANDOM_SEED: &str = "random-seed";
+pub const VAULT_SEED: &str = "raffle-vault";
+pub const RAFFLE_SEED: &str = "raffle";
+pub const PROCEEDS_SEED: &str = "raffle-proceeds";
+pub const ENTRY_SEED: &str = "raffle-entry";
+pub const VOUCHER_SEED: &str = "raffle-voucher";
+pub const REFERRER_SEED: &str = "raffle-referrer";
+pub const FEE_EXEMPTION_SEED: &str = "fee-exemption";
+pub const RECEIPT_SEED: &str = "raffle-receipt";
+pub const EMERGENCY_SEED: &str = "emergency-withdraw";
+pub const ENTRANT_PAGE_SEED: &str = "raffle-entrants";
+pub const TICKET_RECORD_SEED: &str = "raffle-ticket-record";
+pub const USER_PROFILE_SEED: &str = "user-profile";
+pub const CREATOR_STATS_SEED: &str = "creator-stats";
+pub const LEADERBOARD_SEED: &str = "leaderboard";
+pub const WINNER_SNAPSHOT_SEED: &str = "winner-snapshot";
+// the staking program's stake account PDA: seeds [STAKE_SEED, owner]
+// layout: discriminator(8) + owner(32) + staked amount(8)
+pub const STAKE_SEED: &str = "stake-account";
 pub const REAP_TOKEN_MINT: &str = "2Dm1zu8ERJGBs3NLXt8s8Vor3YHwJye5E2pYhLiMHU4L";
 
+pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+pub const TOKEN_METADATA_PROGRAM_ID: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+pub const PNFT_ACCOUNTS_LEN: usize = 9;
+pub const BUBBLEGUM_PROGRAM_ID: &str = "BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY";
+pub const MPL_CORE_PROGRAM_ID: &str = "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d";
+
+pub const ORAO_VRF_PROGRAM_ID: &str = "VRFzZoJdhFWL8rkvu87LpKM3RbcVezpMEc6X5GVDr7y";
+pub const ORAO_NETWORK_STATE_SEED: &str = "orao-vrf-network-configuration";
+pub const ORAO_RANDOMNESS_SEED: &str = "orao-vrf-randomness-request";
+
+pub const PRIZE_NFT: u64 = 0;
+pub const PRIZE_TOKEN: u64 = 1;
+pub const PRIZE_SOL: u64 = 2;
+pub const PRIZE_CNFT: u64 = 3;
+pub const PRIZE_CORE: u64 = 4;
+pub const PRIZE_WL: u64 = 5;
+pub const PRIZE_EDITION: u64 = 6;
+pub const PRIZE_DELEGATED: u64 = 7;
+pub const PRIZE_BUNDLE: u64 = 8;
+
+pub const PYTH_PROGRAM_ID: &str = "FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH";
+pub const ED25519_PROGRAM_ID: &str = "Ed25519SigVerify111111111111111111111111111";
+pub const MAX_PRICE_AGE: i64 = 60;
+pub const MAX_PRICE_CONF_BPS: u64 = 100;
+
+pub const MAX_FEE_BPS: u64 = 10000;
+
+pub const GLOBAL_VERSION: u64 = 5;
+pub const RAFFLE_VERSION: u64 = 12;
+// the layout version of the RaffleState summary returned by get_raffle_state
+pub const RAFFLE_STATE_VERSION: u8 = 1;
+// win probabilities returned by get_winning_odds are fractions of ODDS_SCALE
+pub const ODDS_SCALE: u64 = 1_000_000_000;
+// raffles before version 7 kept one u64 claim flag per winner slot at this offset of the layout
+pub const LEGACY_CLAIMED_OFFSET: usize = 3168;
+pub const LEGACY_CLAIMED_LEN: usize = 8 * MAX_WINNERS;
+// raffles before version 8 kept count, winner_count, no_repeat and max_entrants as u64 at
+// LEGACY_COUNTERS_OFFSET and whitelisted and status as u64 at their own offsets
+pub const LEGACY_COUNTERS_OFFSET: usize = 64;
+pub const LEGACY_WHITELISTED_OFFSET: usize = 120;
+pub const LEGACY_STATUS_OFFSET: usize = 1296;
+pub const LEGACY_COUNTERS_LEN: usize = 32;
+// account sizes of version 7 raffles and of the packed layouts from version 8 on,
+// migrate_account tells these layouts apart by size
+pub const RAFFLE_V7_LEN: usize = 85016;
+pub const PACKED_RAFFLE_LENS: [usize; 4] = [84984, 85040, 85064, 85072];
+
+pub const PAY_SOL: u64 = 1;
+pub const PAY_TOKEN: u64 = 2;
+pub const PAY_BOTH: u64 = 4;
+pub const PAY_WSOL: u64 = 8;
+
 pub const MAX_ENTRANTS: usize = 2000;
-pub const MAX_WINNERS: usize = 50;
+pub const MAX_PAGE_ENTRANTS: usize = 250;
+pub const MAX_PROFILE_RAFFLES: usize = 32;
+pub const LEADERBOARD_SIZE: usize = 50;
+pub const MAX_WINNERS: usize = 50; // at most 64, claims are bits of a u64
+pub const WINNER_ORDER_LEN: usize = (MAX_WINNERS + 7) / 8 * 8; // a byte per winner, 8 byte aligned
+pub const MAX_STAKE_TIERS: usize = 4;
+pub const MAX_TIERS: usize = 5;
+pub const MAX_SPLITS: usize = 5;
+pub const MAX_DEALS: usize = 4;
+pub const MAX_WINDOWS: usize = 4;
+pub const MAX_REVEAL_CHUNK: u64 = 10;
+pub const FORCE_DRAW_DELAY: i64 = 7 * 24 * 60 * 60;
+pub const MAX_RAFFLE_DURATION: i64 = 90 * 24 * 60 * 60;
+pub const CLAIM_WINDOW: i64 = 30 * 24 * 60 * 60;
+pub const EMERGENCY_WITHDRAW_DELAY: i64 = 3 * 24 * 60 * 60;
@@ -46,4 +46,194 @@ pub enum RaffleError {
     UnclaimedPrizes,
     #[msg("Invalid recent blockhashes")]
     InvalidRecentBlockhashes,
+    #[msg("Invalid slot hashes")]
+    InvalidSlotHashes,
+    #[msg("Invalid VRF program")]
+    InvalidVrfProgram,
+    #[msg("Invalid randomness account")]
+    InvalidRandomnessAccount,
+    #[msg("Randomness already requested")]
+    RandomnessAlreadyRequested,
+    #[msg("Randomness not fulfilled yet")]
+    RandomnessNotFulfilled,
+    #[msg("Invalid randomness mode")]
+    InvalidRandomnessMode,
+    #[msg("Randomness mode is not supported")]
+    RandomnessModeUnsupported,
+    #[msg("Invalid commitment")]
+    InvalidCommitment,
+    #[msg("Invalid reveal chunk")]
+    InvalidRevealChunk,
+    #[msg("You are not the Super Admin")]
+    NotSuperAdmin,
+    #[msg("Force draw is still timelocked")]
+    ForceDrawLocked,
+    #[msg("Winner is not an entrant")]
+    WinnerNotEntrant,
+    #[msg("Too many winners")]
+    TooManyWinners,
+    #[msg("Invalid prize accounts")]
+    InvalidPrizeAccounts,
+    #[msg("Not enough prizes for the winners")]
+    NotEnoughPrizes,
+    #[msg("Invalid Bubblegum program")]
+    InvalidBubblegumProgram,
+    #[msg("Invalid merkle tree")]
+    InvalidMerkleTree,
+    #[msg("Invalid MPL Core program")]
+    InvalidCoreProgram,
+    #[msg("Invalid MPL Core asset")]
+    InvalidCoreAsset,
+    #[msg("Invalid token program")]
+    InvalidTokenProgram,
+    #[msg("Invalid token account")]
+    InvalidTokenAccount,
+    #[msg("Invalid prize tiers")]
+    InvalidPrizeTiers,
+    #[msg("Invalid master edition")]
+    InvalidMasterEdition,
+    #[msg("Mystery prize is not revealed")]
+    PrizeNotRevealed,
+    #[msg("Prize does not match the mystery prize hash")]
+    InvalidPrizeHash,
+    #[msg("Invalid payment mint")]
+    InvalidPaymentMint,
+    #[msg("Payment option not allowed")]
+    PaymentNotAllowed,
+    #[msg("Invalid price feed")]
+    InvalidPriceFeed,
+    #[msg("Price feed is stale")]
+    StalePrice,
+    #[msg("Price feed confidence is too wide")]
+    PriceUncertain,
+    #[msg("Invalid protocol fee")]
+    InvalidFee,
+    #[msg("Invalid treasury")]
+    InvalidTreasury,
+    #[msg("Invalid revenue splits")]
+    InvalidSplits,
+    #[msg("Split payee does not match")]
+    InvalidSplitPayee,
+    #[msg("Invalid bundle discounts")]
+    InvalidDiscounts,
+    #[msg("Invalid price windows")]
+    InvalidPriceWindows,
+    #[msg("Invalid dutch auction")]
+    InvalidAuction,
+    #[msg("Raffle has no buyout")]
+    NoBuyout,
+    #[msg("No refund to claim")]
+    NoRefund,
+    #[msg("Free raffles are entered once per wallet")]
+    FreeEntryOnly,
+    #[msg("Raffle is not free to enter")]
+    NotFreeRaffle,
+    #[msg("Invalid voucher")]
+    InvalidVoucher,
+    #[msg("Invalid collection holder NFT")]
+    InvalidHolderNft,
+    #[msg("Invalid stake account")]
+    InvalidStakeAccount,
+    #[msg("Invalid charity")]
+    InvalidCharity,
+    #[msg("Invalid royalty")]
+    InvalidRoyalty,
+    #[msg("Nothing to withdraw")]
+    NothingToWithdraw,
+    #[msg("Invalid fee exemption account")]
+    InvalidFeeExemption,
+    #[msg("Ticket price overflow")]
+    PriceOverflow,
+    #[msg("Amount overflow")]
+    AmountOverflow,
+    #[msg("Tickets have already been sold")]
+    TicketsSold,
+    #[msg("Raffle is cancelled")]
+    RaffleCancelled,
+    #[msg("Raffle is not refundable")]
+    NotRefundable,
+    #[msg("Raffle is not cancelled")]
+    RaffleNotCancelled,
+    #[msg("Invalid ticket receipt")]
+    InvalidReceipt,
+    #[msg("Min entrants must be below max entrants")]
+    InvalidMinEntrants,
+    #[msg("Ticket sales have not started")]
+    SalesNotStarted,
+    #[msg("Invalid raffle status")]
+    InvalidRaffleStatus,
+    #[msg("Invalid raffle status transition")]
+    InvalidStatusTransition,
+    #[msg("Winner count cannot be changed for this prize")]
+    InvalidWinnerCount,
+    #[msg("Claim window is still open")]
+    ClaimWindowOpen,
+    #[msg("No entrants left to draw")]
+    NoEntrantsLeft,
+    #[msg("Raffle prizes, proceeds or refunds are not settled")]
+    RaffleNotSettled,
+    #[msg("Only NFT prize raffles created with a timestamp can be cloned")]
+    NotCloneable,
+    #[msg("Raffle ticket sales are paused")]
+    RafflePaused,
+    #[msg("Raffle creation and ticket sales are paused")]
+    ProtocolPaused,
+    #[msg("Emergency withdrawal is still timelocked")]
+    EmergencyTimelocked,
+    #[msg("Invalid presale")]
+    InvalidPresale,
+    #[msg("Only collection or whitelist token holders can buy during the presale")]
+    PresaleHoldersOnly,
+    #[msg("Ticket amount must be greater than zero")]
+    ZeroTicketAmount,
+    #[msg("Raffle winners are already revealed")]
+    RaffleAlreadyRevealed,
+    #[msg("Invalid sales end or draw timestamp")]
+    InvalidSaleWindows,
+    #[msg("Invalid anti-snipe window")]
+    InvalidAntiSnipe,
+    #[msg("Raffle account is already fully allocated")]
+    RaffleAlreadyAllocated,
+    #[msg("Account cannot be migrated")]
+    InvalidMigration,
+    #[msg("Invalid Metaplex metadata account")]
+    InvalidMetadata,
+    #[msg("Prize is not a verified member of the collection")]
+    PrizeNotInCollection,
+    #[msg("Raffle has no entrant slot left for a new wallet")]
+    TooManyEntrants,
+    #[msg("Invalid entrant page")]
+    InvalidEntrantPage,
+    #[msg("Invalid ticket record")]
+    InvalidTicketRecord,
+    #[msg("Invalid user profile")]
+    InvalidUserProfile,
+    #[msg("Wallet ticket limit reached")]
+    WalletTicketLimit,
+    #[msg("Invalid creator stats")]
+    InvalidCreatorStats,
+    #[msg("Raffle id is not the next one in the registry")]
+    InvalidRaffleId,
+    #[msg("Winner snapshot already taken")]
+    WinnerSnapshotTaken,
+    #[msg("Invalid winner snapshot")]
+    InvalidWinnerSnapshot,
+    #[msg("Entrant slot out of bounds")]
+    EntrantIndexOutOfBounds,
+    #[msg("Winner slot out of bounds")]
+    WinnerIndexOutOfBounds,
+    #[msg("Not the proposed super admin")]
+    NotPendingAdmin,
+    #[msg("Ticket sales have not been closed")]
+    SalesNotClosed,
+    #[msg("End slot hash is not in the slot hashes sysvar")]
+    SlotHashNotFound,
+    #[msg("Ticket sales are already closed")]
+    SalesAlreadyClosed,
+    #[msg("Token account is not empty or holds native SOL")]
+    TokenAccountNotEmpty,
+    #[msg("Ticket count is too large")]
+    TicketCountTooLarge,
+    #[msg("Only raffles paid in SOL only can be bought out")]
+    BuyoutSolOnly,
 }
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct ForceDrawEvent {
+    pub raffle: Pubkey,
+    pub admin: Pubkey,
+    pub winners: Vec<Pubkey>,
+    pub reason: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RafflePausedEvent {
+    pub raffle: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RaffleResumedEvent {
+    pub raffle: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolPausedEvent {
+    pub admin: Pubkey,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminProposedEvent {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminTransferredEvent {
+    pub previous_admin: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyWithdrawProposedEvent {
+    pub raffle: Pubkey,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub executable_at: i64,
+}
+
+#[event]
+pub struct EmergencyWithdrawExecutedEvent {
+    pub raffle: Pubkey,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyWithdrawCancelledEvent {
+    pub raffle: Pubkey,
+    pub source: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TicketRefundedEvent {
+    pub raffle: Pubkey,
+    pub buyer: Pubkey,
+    pub tickets: u64,
+    pub paid_sol: u64,
+    pub paid_token: u64,
+}
+
+#[event]
+pub struct RaffleExtendedEvent {
+    pub raffle: Pubkey,
+    pub buyer: Pubkey,
+    pub end_timestamp: i64,
+    pub extensions: u64,
+}
+
+#[event]
+pub struct TicketsPurchasedEvent {
+    pub raffle: Pubkey,
+    pub buyer: Pubkey,
+    pub tickets: u64,
+    pub unit_price_sol: u64,
+    pub unit_price_token: u64,
+    pub paid_sol: u64,
+    pub paid_token: u64,
+    pub slot: u64,
+    pub timestamp: i64,
+}
@@ -1,21 +1,27 @@
-use anchor_lang::{accounts::cpi_account::CpiAccount, prelude::*, AccountSerialize, System};
-use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{self, Burn, Token, TokenAccount, Transfer},
-};
-use solana_program::program::{invoke, invoke_signed};
+// instruction handlers take every argument of their instruction
+#![allow(clippy::too_many_arguments)]
+
+use anchor_lang::{prelude::*, AccountSerialize, Discriminator, System};
+use anchor_spl::token::Token;
+use solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use solana_program::keccak;
+use solana_program::program::{invoke, invoke_signed, set_return_data};
 use solana_program::pubkey::Pubkey;
 use spl_token::instruction::*;
 
 pub mod account;
 pub mod constants;
 pub mod error;
+pub mod event;
 pub mod utils;
+pub mod view;
 
 use account::*;
 use constants::*;
 use error::*;
+use event::*;
 use utils::*;
+use view::*;
 
 declare_id!("EsBdqM8dL2yH3g3t2BKKLttYnertN7sx4RsVp2Je9szi");
 
@@ -26,15 +32,407 @@ pub mod raffle {
      * @dev Initialize the project
      */
     pub fn initialize(ctx: Context<Initialize>, _global_bump: u8) -> ProgramResult {
-        let global_authority = &mut ctx.accounts.global_authority;
+        let mut global_authority = ctx.accounts.global_authority.load_init()?;
         global_authority.super_admin = ctx.accounts.admin.key();
+        global_authority.version = GLOBAL_VERSION;
+        Ok(())
+    }
+
+    /**
+     * @dev Upgrade a GlobalPool or RafflePool created with an older layout to the current version
+     * the account grows by up to MAX_PERMITTED_DATA_INCREASE per call, the payer funds the extra rent
+     * and the version is stamped once the account has the current size,
+     * raffles from before version 8 shrink to it as their counters are packed into smaller fields
+     * and, before version 7, their claim flags into a bitmap
+     * @Context has payer and the account to migrate
+     */
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> ProgramResult {
+        let account = &ctx.accounts.account;
+        if *account.owner != crate::ID {
+            return Err(RaffleError::InvalidMigration.into());
+        }
+        let mut discriminator = [0u8; 8];
+        discriminator.copy_from_slice(
+            account
+                .try_borrow_data()?
+                .get(0..8)
+                .ok_or(RaffleError::InvalidMigration)?,
+        );
+        let is_global = discriminator == GlobalPool::discriminator();
+        let space = if is_global {
+            8 + std::mem::size_of::<GlobalPool>()
+        } else if discriminator == RafflePool::discriminator() {
+            8 + std::mem::size_of::<RafflePool>()
+        } else {
+            return Err(RaffleError::InvalidMigration.into());
+        };
+
+        // raffles before version 8 have their counters unpacked and before version 7 their
+        // claim flags too, they grow to the full legacy layout first and then shrink as these
+        // are packed
+        let len = account.data_len();
+        let legacy_counters = !is_global && len != space && !PACKED_RAFFLE_LENS.contains(&len);
+        let legacy_claims = legacy_counters && len != RAFFLE_V7_LEN;
+        let full_len = if legacy_claims {
+            space + LEGACY_COUNTERS_LEN - 8 + LEGACY_CLAIMED_LEN
+        } else if legacy_counters {
+            space + LEGACY_COUNTERS_LEN
+        } else {
+            space
+        };
+        if len < full_len {
+            let new_len = std::cmp::min(full_len, len + MAX_PERMITTED_DATA_INCREASE);
+            let rent = Rent::get()?.minimum_balance(new_len);
+            if rent > account.lamports() {
+                sol_transfer_user(
+                    ctx.accounts.payer.to_account_info(),
+                    account.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                    rent - account.lamports(),
+                )?;
+            }
+            account.realloc(new_len, true)?;
+            if new_len < full_len {
+                return Ok(());
+            }
+        }
+        if legacy_claims {
+            pack_legacy_claims(account)?;
+        }
+        if legacy_counters {
+            pack_legacy_counters(account)?;
+            account.realloc(space, false)?;
+        }
+
+        if is_global {
+            let global_authority = AccountLoader::<GlobalPool>::try_from(account)?;
+            global_authority.load_mut()?.version = GLOBAL_VERSION;
+        } else {
+            let raffle = AccountLoader::<RafflePool>::try_from(account)?;
+            let mut raffle = raffle.load_mut()?;
+            if raffle.version < 4 {
+                raffle.index_legacy_entrants();
+            }
+            if raffle.version < 9 {
+                raffle.sort_winners()?;
+            }
+            raffle.version = RAFFLE_VERSION;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * @dev Set the protocol fee taken from SOL ticket payments
+     * @Context has super admin and global_authority account
+     * @param global_bump: global_authority's bump
+     * @param fee_bps: fee in basis points of the SOL payment
+     * @param treasury: the address receiving the fee
+     */
+    pub fn set_protocol_fee(
+        ctx: Context<SetProtocolFee>,
+        _global_bump: u8,
+        fee_bps: u64,
+        treasury: Pubkey,
+    ) -> ProgramResult {
+        let mut global_authority = ctx.accounts.global_authority.load_mut()?;
+        if global_authority.super_admin != ctx.accounts.admin.key() {
+            return Err(RaffleError::NotSuperAdmin.into());
+        }
+        let total_bps = fee_bps.checked_add(global_authority.referral_bps);
+        if total_bps.map_or(true, |bps| bps > MAX_FEE_BPS) {
+            return Err(RaffleError::InvalidFee.into());
+        }
+        global_authority.fee_bps = fee_bps;
+        global_authority.treasury = treasury;
+        Ok(())
+    }
+    /**
+     * @dev Set the lamports charged to the treasury for every raffle created
+     * @Context has admin and global_authority account
+     * @param global_bump: global authority's bump
+     * @param creation_fee: lamports charged per raffle, 0 turns the fee off
+     */
+    pub fn set_creation_fee(
+        ctx: Context<SetProtocolFee>,
+        _global_bump: u8,
+        creation_fee: u64,
+    ) -> ProgramResult {
+        let mut global_authority = ctx.accounts.global_authority.load_mut()?;
+        if global_authority.super_admin != ctx.accounts.admin.key() {
+            return Err(RaffleError::NotSuperAdmin.into());
+        }
+        global_authority.creation_fee = creation_fee;
+        Ok(())
+    }
+    /**
+     * @dev Set the staking program and the ticket discount of each staked amount tier
+     * @Context has admin and global_authority account
+     * @param global_bump: global authority's bump
+     * @param staking_program: the staking program owning the stake accounts, default turns discounts off
+     * @param stake_tier_mins: minimum staked amount of each tier in ascending order
+     * @param stake_tier_bps: ticket discount of each tier in basis points
+     */
+    pub fn set_stake_discounts(
+        ctx: Context<SetProtocolFee>,
+        _global_bump: u8,
+        staking_program: Pubkey,
+        stake_tier_mins: Vec<u64>,
+        stake_tier_bps: Vec<u64>,
+    ) -> ProgramResult {
+        let mut global_authority = ctx.accounts.global_authority.load_mut()?;
+        if global_authority.super_admin != ctx.accounts.admin.key() {
+            return Err(RaffleError::NotSuperAdmin.into());
+        }
+        if stake_tier_mins.len() != stake_tier_bps.len() || stake_tier_mins.len() > MAX_STAKE_TIERS
+        {
+            return Err(RaffleError::InvalidDiscounts.into());
+        }
+        for i in 0..stake_tier_mins.len() {
+            if (i > 0 && stake_tier_mins[i] <= stake_tier_mins[i - 1])
+                || stake_tier_bps[i] > MAX_FEE_BPS
+            {
+                return Err(RaffleError::InvalidDiscounts.into());
+            }
+            global_authority.stake_tier_mins[i] = stake_tier_mins[i];
+            global_authority.stake_tier_bps[i] = stake_tier_bps[i];
+        }
+        global_authority.staking_program = staking_program;
+        global_authority.stake_tier_count = stake_tier_mins.len() as u64;
+        Ok(())
+    }
+    /**
+     * @dev Set the share of SOL ticket payments paid to the buyer's referrer
+     * @Context has admin and global_authority account
+     * @param global_bump: global authority's bump
+     * @param referral_bps: the referrer's share in basis points, 0 turns referrals off
+     */
+    pub fn set_referral_bps(
+        ctx: Context<SetProtocolFee>,
+        _global_bump: u8,
+        referral_bps: u64,
+    ) -> ProgramResult {
+        let mut global_authority = ctx.accounts.global_authority.load_mut()?;
+        if global_authority.super_admin != ctx.accounts.admin.key() {
+            return Err(RaffleError::NotSuperAdmin.into());
+        }
+        let total_bps = global_authority.fee_bps.checked_add(referral_bps);
+        if total_bps.map_or(true, |bps| bps > MAX_FEE_BPS) {
+            return Err(RaffleError::InvalidFee.into());
+        }
+        global_authority.referral_bps = referral_bps;
+        Ok(())
+    }
+    /**
+     * @dev Pause or resume raffle creation and ticket sales across the protocol
+     * draws, claims, refunds and withdrawals keep working while paused
+     * @Context has admin and global_authority account
+     * @param global_bump: global authority's bump
+     * @param paused: true stops new raffles and ticket sales, false resumes them
+     */
+    pub fn set_paused(
+        ctx: Context<SetProtocolFee>,
+        _global_bump: u8,
+        paused: bool,
+    ) -> ProgramResult {
+        let mut global_authority = ctx.accounts.global_authority.load_mut()?;
+        if global_authority.super_admin != ctx.accounts.admin.key() {
+            return Err(RaffleError::NotSuperAdmin.into());
+        }
+        global_authority.paused = paused as u64;
+
+        emit!(ProtocolPausedEvent {
+            admin: ctx.accounts.admin.key(),
+            paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+    /**
+     * @dev Propose a new super admin, the current one stays in charge until the new one accepts
+     * proposing the default key withdraws a pending proposal
+     * @Context has admin and global_authority account
+     * @param global_bump: global authority's bump
+     * @param new_admin: the wallet to hand the super admin role to
+     */
+    pub fn propose_admin(
+        ctx: Context<SetProtocolFee>,
+        _global_bump: u8,
+        new_admin: Pubkey,
+    ) -> ProgramResult {
+        let mut global_authority = ctx.accounts.global_authority.load_mut()?;
+        global_authority.propose_admin(ctx.accounts.admin.key(), new_admin)?;
+
+        emit!(AdminProposedEvent {
+            admin: ctx.accounts.admin.key(),
+            pending_admin: new_admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+    /**
+     * @dev Take over the super admin role as the wallet proposed by propose_admin
+     * @Context has the proposed admin and global_authority account
+     * @param global_bump: global authority's bump
+     */
+    pub fn accept_admin(ctx: Context<SetProtocolFee>, _global_bump: u8) -> ProgramResult {
+        let mut global_authority = ctx.accounts.global_authority.load_mut()?;
+        let admin = ctx.accounts.admin.key();
+        let previous_admin = global_authority.accept_admin(admin)?;
+
+        emit!(AdminTransferredEvent {
+            previous_admin,
+            admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+    /**
+     * @dev Exempt a partner wallet from the protocol and creation fees
+     * @Context has admin, global_authority and the wallet's FeeExemption PDA to create
+     * @param global_bump: global authority's bump
+     * @param exemption_bump: FeeExemption's bump
+     * @param wallet: the wallet to exempt
+     */
+    pub fn add_fee_exemption(
+        ctx: Context<AddFeeExemption>,
+        _global_bump: u8,
+        _exemption_bump: u8,
+        wallet: Pubkey,
+    ) -> ProgramResult {
+        if ctx.accounts.global_authority.load()?.super_admin != ctx.accounts.admin.key() {
+            return Err(RaffleError::NotSuperAdmin.into());
+        }
+        let fee_exemption = &mut ctx.accounts.fee_exemption;
+        fee_exemption.wallet = wallet;
+        fee_exemption.added_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+    /**
+     * @dev Remove a wallet's fee exemption, refunding its rent to the admin
+     * @Context has admin, global_authority and the wallet's FeeExemption PDA
+     * @param global_bump: global authority's bump
+     */
+    pub fn remove_fee_exemption(
+        ctx: Context<RemoveFeeExemption>,
+        _global_bump: u8,
+    ) -> ProgramResult {
+        if ctx.accounts.global_authority.load()?.super_admin != ctx.accounts.admin.key() {
+            return Err(RaffleError::NotSuperAdmin.into());
+        }
+        Ok(())
+    }
+    /**
+     * @dev Create the Leaderboard PDA ranking wallets by the tickets they bought in a season
+     * and start its first season
+     * @Context has admin, global_authority and the Leaderboard PDA to create
+     * @param global_bump: global authority's bump
+     * @param leaderboard_bump: Leaderboard's bump
+     */
+    pub fn initialize_leaderboard(
+        ctx: Context<InitializeLeaderboard>,
+        _global_bump: u8,
+        _leaderboard_bump: u8,
+    ) -> ProgramResult {
+        if ctx.accounts.global_authority.load()?.super_admin != ctx.accounts.admin.key() {
+            return Err(RaffleError::NotSuperAdmin.into());
+        }
+        let mut leaderboard = ctx.accounts.leaderboard.load_init()?;
+        leaderboard.start_season(Clock::get()?.unix_timestamp);
+        Ok(())
+    }
+    /**
+     * @dev End the leaderboard's season and start a new one with empty rankings,
+     * buyers' season tickets restart from their next purchase
+     * @Context has admin, global_authority and the Leaderboard PDA
+     * @param global_bump: global authority's bump
+     * @param leaderboard_bump: Leaderboard's bump
+     */
+    pub fn start_leaderboard_season(
+        ctx: Context<StartLeaderboardSeason>,
+        _global_bump: u8,
+        _leaderboard_bump: u8,
+    ) -> ProgramResult {
+        if ctx.accounts.global_authority.load()?.super_admin != ctx.accounts.admin.key() {
+            return Err(RaffleError::NotSuperAdmin.into());
+        }
+        let mut leaderboard = ctx.accounts.leaderboard.load_mut()?;
+        leaderboard.start_season(Clock::get()?.unix_timestamp);
+        Ok(())
+    }
+    /**
+     * @dev Allocate a raffle account at the PDA of [RAFFLE_SEED, raffle_id] from the raffle registry
+     * the raffle account is too large to create in one instruction, so the first call creates it
+     * with the next id of the registry and each further call grows it by MAX_PERMITTED_DATA_INCREASE
+     * until it has the full RafflePool size and its id is written,
+     * then it is passed as the zero-account Raffle to any create_raffle instruction by the same creator
+     * @Context has creator, global_authority and the raffle PDA
+     * @param global_bump: global authority's bump
+     * @param raffle_bump: the raffle PDA's bump
+     * @param raffle_id: the raffle's registry id, the next one after global_authority's raffle_id
+     * on the first call
+     */
+    pub fn allocate_raffle(
+        ctx: Context<AllocateRaffle>,
+        _global_bump: u8,
+        raffle_bump: u8,
+        raffle_id: u64,
+    ) -> ProgramResult {
+        let raffle = &ctx.accounts.raffle;
+        let creator = ctx.accounts.creator.key();
+        let space = 8 + std::mem::size_of::<RafflePool>();
+
+        if raffle.data_is_empty() {
+            if ctx.accounts.global_authority.load_mut()?.next_raffle_id()? != raffle_id {
+                return Err(RaffleError::InvalidRaffleId.into());
+            }
+            let raffle_id = raffle_id.to_le_bytes();
+            let seeds = &[RAFFLE_SEED.as_bytes(), raffle_id.as_ref(), &[raffle_bump]];
+            create_program_account(
+                ctx.accounts.creator.to_account_info(),
+                raffle.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                std::cmp::min(space, MAX_PERMITTED_DATA_INCREASE),
+                &[&seeds[..]],
+            )?;
+            // Rent for the full size is paid upfront so growing only reallocates
+            let rent = Rent::get()?.minimum_balance(space);
+            sol_transfer_user(
+                ctx.accounts.creator.to_account_info(),
+                raffle.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                rent - raffle.lamports(),
+            )?;
+            // Reserve the raffle for its creator, creator leads the RafflePool layout
+            let mut data = raffle.try_borrow_mut_data()?;
+            data[8..40].copy_from_slice(creator.as_ref());
+            return Ok(());
+        }
+
+        if *raffle.owner != crate::ID {
+            return Err(ProgramError::IllegalOwner);
+        }
+        let len = raffle.data_len();
+        if len >= space {
+            return Err(RaffleError::RaffleAlreadyAllocated.into());
+        }
+        let new_len = std::cmp::min(space, len + MAX_PERMITTED_DATA_INCREASE);
+        raffle.realloc(new_len, true)?;
+        // raffle_id ends the RafflePool layout, it is written once the account holds it
+        if new_len == space {
+            let mut data = raffle.try_borrow_mut_data()?;
+            data[space - 8..].copy_from_slice(&raffle_id.to_le_bytes());
+        }
         Ok(())
     }
+
     /**
      * @dev Create new raffle with new arguements
-     * @Context has admin, global_authority accounts.
+     * @Context has admin, global_authority, treasury, the admin's FeeExemption and CreatorStats PDAs.
      * and zero-account Raffle, owner's nft ATA and global_authority's nft ATA
-     * and nft mint address
+     * and nft mint address, extra prizes are passed as remaining accounts
+     * (owner's nft ATA, global_authority's nft ATA, nft mint) in winner order
+     * or the pNFT transfer accounts for programmable raffles
      * @param global_bump: global authority's bump
      * @param ticket_price_reap: ticket price by reap
      * @param ticket_price_sol: ticket price by sol
@@ -42,311 +440,6230 @@ pub mod raffle {
      * @param winner_count: how many winners will be get prize
      * @param whitelisted: if 1: winner will get the nft, if 0: winners get whitelist spot
      * @param max_entrants: entrants amount to take part in this raffle
+     * @param commitment: keccak hash of the creator's secret for commit-reveal raffles
+     * @param randomness_mode: 0: pseudo, 1: slot hashes, 2: switchboard, 3: orao, 4: commit-reveal
+     * @param crank_tip: lamports escrowed in the raffle and paid to whoever completes the draw
+     * @param programmable: if 1: the nft is a pNFT moved with the Token Metadata program
+     * @param payment_mint: the token mint tickets are paid with
+     * @param payment_decimals: decimals of the payment mint the ticket price is given in
+     * @param burn_bps: share of token payments burnt in basis points
+     * @param treasury_bps: share of token payments sent to the treasury in basis points
+     * @param split_payees: wallets sharing the SOL proceeds with the creator
+     * @param split_bps: each split payee's share of the SOL proceeds in basis points
+     * @param name: the raffle title shown by frontends, zero padded UTF-8
+     * @param uri: link to the raffle's off-chain details, zero padded UTF-8
      */
-    pub fn create_raffle(
-        ctx: Context<CreateRaffle>,
-        global_bump: u8,
+    pub fn create_raffle<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateRaffle<'info>>,
+        _global_bump: u8,
         ticket_price_reap: u64,
         ticket_price_sol: u64,
         end_timestamp: i64,
         winner_count: u64,
         whitelisted: u64,
         max_entrants: u64,
+        commitment: [u8; 32],
+        randomness_mode: u64,
+        crank_tip: u64,
+        programmable: u64,
+        payment_mint: Pubkey,
+        payment_decimals: u64,
+        burn_bps: u64,
+        treasury_bps: u64,
+        split_payees: Vec<Pubkey>,
+        split_bps: Vec<u64>,
+        name: [u8; 32],
+        uri: [u8; 128],
     ) -> ProgramResult {
         let mut raffle = ctx.accounts.raffle.load_init()?;
         let timestamp = Clock::get()?.unix_timestamp;
+        raffle.check_allocation(&ctx.accounts.admin.key())?;
+        raffle.version = RAFFLE_VERSION;
 
-        if max_entrants > 2000 {
-            return Err(RaffleError::MaxEntrantsTooLarge.into());
-        }
-        if timestamp > end_timestamp {
-            return Err(RaffleError::EndTimeError.into());
-        }
+        validate_raffle_args(
+            timestamp,
+            end_timestamp,
+            winner_count,
+            &commitment,
+            randomness_mode,
+        )?;
+        validate_token_split(burn_bps, treasury_bps)?;
+        start_raffle_creation(
+            &ctx.accounts.global_authority,
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            &ctx.accounts.fee_exemption.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+        record_raffle_created(
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.creator_stats,
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        ctx.accounts.check_token_accounts()?;
 
         // Transfer NFT to the PDA
         let src_token_account_info = &mut &ctx.accounts.owner_temp_nft_account;
         let dest_token_account_info = &mut &ctx.accounts.dest_nft_token_account;
         let token_program = &mut &ctx.accounts.token_program;
 
-        let cpi_accounts = Transfer {
-            from: src_token_account_info.to_account_info().clone(),
-            to: dest_token_account_info.to_account_info().clone(),
-            authority: ctx.accounts.admin.to_account_info().clone(),
+        if programmable == 1 {
+            PnftTransfer {
+                token: src_token_account_info.to_account_info(),
+                token_owner: ctx.accounts.admin.to_account_info(),
+                destination: dest_token_account_info.to_account_info(),
+                destination_owner: ctx.accounts.global_authority.to_account_info(),
+                mint: ctx.accounts.nft_mint_address.to_account_info(),
+                payer: ctx.accounts.admin.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: token_program.to_account_info(),
+            }
+            .invoke_signed(ctx.remaining_accounts, &[])?;
+        } else {
+            TokenTransfer {
+                token_program: token_program.to_account_info(),
+                source: src_token_account_info.to_account_info(),
+                mint: ctx.accounts.nft_mint_address.to_account_info(),
+                destination: dest_token_account_info.to_account_info(),
+                authority: ctx.accounts.admin.to_account_info(),
+            }
+            .invoke_signed(1, &[], &[])?;
+        }
+        raffle.prize_mints[0] = ctx.accounts.nft_mint_address.key();
+        raffle.prize_count = 1;
+
+        // Transfer the extra prize NFTs to the PDA
+        // pNFT raffles use the remaining accounts for the Token Metadata transfer instead
+        let extra_prizes: &[AccountInfo] = if programmable == 1 {
+            &[]
+        } else {
+            ctx.remaining_accounts
         };
-        token::transfer(
-            CpiContext::new(token_program.clone().to_account_info(), cpi_accounts),
-            1,
+        ctx.accounts
+            .escrow_extra_prizes(&mut raffle, extra_prizes)?;
+        if whitelisted == 1 && winner_count > raffle.prize_count {
+            return Err(RaffleError::NotEnoughPrizes.into());
+        }
+
+        raffle.creator = ctx.accounts.admin.key();
+        raffle.nft_mint = ctx.accounts.nft_mint_address.key();
+        raffle.ticket_price_reap = ticket_price_reap;
+        raffle.ticket_price_sol = ticket_price_sol;
+        raffle.end_timestamp = end_timestamp;
+        raffle.created_at = timestamp;
+        raffle.set_max_entrants(max_entrants)?;
+        raffle.tickets_remaining = max_entrants;
+        raffle.set_winner_count(winner_count)?;
+        raffle.whitelisted = whitelisted as u8;
+        raffle.commitment = commitment;
+        raffle.randomness_mode = randomness_mode;
+        raffle.crank_tip = crank_tip;
+        raffle.payment_mint = payment_mint;
+        raffle.payment_decimals = payment_decimals;
+        raffle.burn_bps = burn_bps;
+        raffle.treasury_bps = treasury_bps;
+        raffle.set_splits(&split_payees, &split_bps)?;
+        raffle.name = name;
+        raffle.uri = uri;
+        raffle.programmable = programmable;
+        drop(raffle);
+
+        ctx.accounts.escrow_crank_tip(crank_tip)?;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Create new raffle whose prize is a fungible token amount split across the winners
+     * @Context has admin, global_authority, treasury, the admin's FeeExemption and CreatorStats PDAs.
+     * and zero-account Raffle, owner's token ATA and global_authority's token ATA
+     * and prize token mint address, Token-2022 transfer hook accounts as remaining accounts
+     * @param global_bump: global authority's bump
+     * @param ticket_price_reap: ticket price by reap
+     * @param ticket_price_sol: ticket price by sol
+     * @param end_timestamp: the end time of raffle
+     * @param winner_count: how many winners will share the prize
+     * @param max_entrants: entrants amount to take part in this raffle
+     * @param commitment: keccak hash of the creator's secret for commit-reveal raffles
+     * @param randomness_mode: 0: pseudo, 1: slot hashes, 2: switchboard, 3: orao, 4: commit-reveal
+     * @param crank_tip: lamports escrowed in the raffle and paid to whoever completes the draw
+     * @param prize_amount: token amount escrowed and split evenly across the winners
+     * @param payment_mint: the token mint tickets are paid with
+     * @param payment_decimals: decimals of the payment mint the ticket price is given in
+     * @param burn_bps: share of token payments burnt in basis points
+     * @param treasury_bps: share of token payments sent to the treasury in basis points
+     * @param split_payees: wallets sharing the SOL proceeds with the creator
+     * @param split_bps: each split payee's share of the SOL proceeds in basis points
+     */
+    pub fn create_raffle_token_prize<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateRaffle<'info>>,
+        _global_bump: u8,
+        ticket_price_reap: u64,
+        ticket_price_sol: u64,
+        end_timestamp: i64,
+        winner_count: u64,
+        max_entrants: u64,
+        commitment: [u8; 32],
+        randomness_mode: u64,
+        crank_tip: u64,
+        prize_amount: u64,
+        payment_mint: Pubkey,
+        payment_decimals: u64,
+        burn_bps: u64,
+        treasury_bps: u64,
+        split_payees: Vec<Pubkey>,
+        split_bps: Vec<u64>,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_init()?;
+        let timestamp = Clock::get()?.unix_timestamp;
+        raffle.check_allocation(&ctx.accounts.admin.key())?;
+        raffle.version = RAFFLE_VERSION;
+
+        validate_raffle_args(
+            timestamp,
+            end_timestamp,
+            winner_count,
+            &commitment,
+            randomness_mode,
+        )?;
+        validate_token_split(burn_bps, treasury_bps)?;
+        start_raffle_creation(
+            &ctx.accounts.global_authority,
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            &ctx.accounts.fee_exemption.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+        record_raffle_created(
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.creator_stats,
+            &ctx.accounts.system_program.to_account_info(),
         )?;
+        if winner_count == 0 || prize_amount < winner_count {
+            return Err(RaffleError::NoPrize.into());
+        }
+        ctx.accounts.check_token_accounts()?;
+
+        // Transfer the prize tokens to the PDA
+        TokenTransfer {
+            token_program: ctx.accounts.token_program.to_account_info(),
+            source: ctx.accounts.owner_temp_nft_account.to_account_info(),
+            mint: ctx.accounts.nft_mint_address.to_account_info(),
+            destination: ctx.accounts.dest_nft_token_account.to_account_info(),
+            authority: ctx.accounts.admin.to_account_info(),
+        }
+        .invoke_signed(prize_amount, ctx.remaining_accounts, &[])?;
 
         raffle.creator = ctx.accounts.admin.key();
         raffle.nft_mint = ctx.accounts.nft_mint_address.key();
         raffle.ticket_price_reap = ticket_price_reap;
         raffle.ticket_price_sol = ticket_price_sol;
         raffle.end_timestamp = end_timestamp;
-        raffle.max_entrants = max_entrants;
-        raffle.winner_count = winner_count;
-        raffle.whitelisted = whitelisted;
+        raffle.created_at = timestamp;
+        raffle.set_max_entrants(max_entrants)?;
+        raffle.tickets_remaining = max_entrants;
+        raffle.set_winner_count(winner_count)?;
+        raffle.whitelisted = 1;
+        raffle.commitment = commitment;
+        raffle.randomness_mode = randomness_mode;
+        raffle.crank_tip = crank_tip;
+        raffle.payment_mint = payment_mint;
+        raffle.payment_decimals = payment_decimals;
+        raffle.burn_bps = burn_bps;
+        raffle.treasury_bps = treasury_bps;
+        raffle.set_splits(&split_payees, &split_bps)?;
+        raffle.prize_type = PRIZE_TOKEN;
+        raffle.prize_amount = prize_amount;
+        raffle.prize_mints[0] = ctx.accounts.nft_mint_address.key();
+        raffle.prize_count = 1;
+        drop(raffle);
+
+        ctx.accounts.escrow_crank_tip(crank_tip)?;
 
         Ok(())
     }
 
     /**
-     * @dev Buy tickets functions
-     * @Context has buyer and raffle's account.
-     * global_authority and creator address and their reap token ATAs
-     * @param global_bump: global_authority's bump
-     * @param amount: the amount of the tickets
+     * @dev Create new raffle whose single winner takes the whole bundle of prize NFTs
+     * @Context has admin, global_authority, treasury, the admin's FeeExemption and CreatorStats PDAs.
+     * and zero-account Raffle, owner's nft ATA and global_authority's nft ATA
+     * and nft mint address, the other bundled NFTs are passed as remaining accounts
+     * (owner's nft ATA, global_authority's nft ATA, nft mint)
+     * @param global_bump: global authority's bump
+     * @param ticket_price_reap: ticket price by reap
+     * @param ticket_price_sol: ticket price by sol
+     * @param end_timestamp: the end time of raffle
+     * @param max_entrants: entrants amount to take part in this raffle
+     * @param commitment: keccak hash of the creator's secret for commit-reveal raffles
+     * @param randomness_mode: 0: pseudo, 1: slot hashes, 2: switchboard, 3: orao, 4: commit-reveal
+     * @param crank_tip: lamports escrowed in the raffle and paid to whoever completes the draw
+     * @param payment_mint: the token mint tickets are paid with
+     * @param payment_decimals: decimals of the payment mint the ticket price is given in
+     * @param burn_bps: share of token payments burnt in basis points
+     * @param treasury_bps: share of token payments sent to the treasury in basis points
+     * @param split_payees: wallets sharing the SOL proceeds with the creator
+     * @param split_bps: each split payee's share of the SOL proceeds in basis points
+     */
+    pub fn create_raffle_bundle<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateRaffle<'info>>,
+        _global_bump: u8,
+        ticket_price_reap: u64,
+        ticket_price_sol: u64,
+        end_timestamp: i64,
+        max_entrants: u64,
+        commitment: [u8; 32],
+        randomness_mode: u64,
+        crank_tip: u64,
+        payment_mint: Pubkey,
+        payment_decimals: u64,
+        burn_bps: u64,
+        treasury_bps: u64,
+        split_payees: Vec<Pubkey>,
+        split_bps: Vec<u64>,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_init()?;
+        let timestamp = Clock::get()?.unix_timestamp;
+        raffle.check_allocation(&ctx.accounts.admin.key())?;
+        raffle.version = RAFFLE_VERSION;
+
+        validate_raffle_args(timestamp, end_timestamp, 1, &commitment, randomness_mode)?;
+        validate_token_split(burn_bps, treasury_bps)?;
+        start_raffle_creation(
+            &ctx.accounts.global_authority,
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            &ctx.accounts.fee_exemption.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+        record_raffle_created(
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.creator_stats,
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        ctx.accounts.check_token_accounts()?;
+
+        // Transfer the bundled NFTs to the PDA
+        TokenTransfer {
+            token_program: ctx.accounts.token_program.to_account_info(),
+            source: ctx.accounts.owner_temp_nft_account.to_account_info(),
+            mint: ctx.accounts.nft_mint_address.to_account_info(),
+            destination: ctx.accounts.dest_nft_token_account.to_account_info(),
+            authority: ctx.accounts.admin.to_account_info(),
+        }
+        .invoke_signed(1, &[], &[])?;
+        raffle.prize_mints[0] = ctx.accounts.nft_mint_address.key();
+        raffle.prize_count = 1;
+        ctx.accounts
+            .escrow_extra_prizes(&mut raffle, ctx.remaining_accounts)?;
+
+        raffle.creator = ctx.accounts.admin.key();
+        raffle.nft_mint = ctx.accounts.nft_mint_address.key();
+        raffle.ticket_price_reap = ticket_price_reap;
+        raffle.ticket_price_sol = ticket_price_sol;
+        raffle.end_timestamp = end_timestamp;
+        raffle.created_at = timestamp;
+        raffle.set_max_entrants(max_entrants)?;
+        raffle.tickets_remaining = max_entrants;
+        raffle.set_winner_count(1)?;
+        raffle.whitelisted = 1;
+        raffle.commitment = commitment;
+        raffle.randomness_mode = randomness_mode;
+        raffle.crank_tip = crank_tip;
+        raffle.payment_mint = payment_mint;
+        raffle.payment_decimals = payment_decimals;
+        raffle.burn_bps = burn_bps;
+        raffle.treasury_bps = treasury_bps;
+        raffle.set_splits(&split_payees, &split_bps)?;
+        raffle.prize_type = PRIZE_BUNDLE;
+        drop(raffle);
+
+        ctx.accounts.escrow_crank_tip(crank_tip)?;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Create new raffle which prints a numbered edition of the escrowed master edition for every winner
+     * @Context has admin, global_authority, treasury, the admin's FeeExemption and CreatorStats PDAs.
+     * and zero-account Raffle, owner's nft ATA and global_authority's nft ATA
+     * and nft mint address, the master edition account as the first remaining account
+     * @param global_bump: global authority's bump
+     * @param ticket_price_reap: ticket price by reap
+     * @param ticket_price_sol: ticket price by sol
+     * @param end_timestamp: the end time of raffle
+     * @param winner_count: how many winners will get an edition
+     * @param max_entrants: entrants amount to take part in this raffle
+     * @param commitment: keccak hash of the creator's secret for commit-reveal raffles
+     * @param randomness_mode: 0: pseudo, 1: slot hashes, 2: switchboard, 3: orao, 4: commit-reveal
+     * @param crank_tip: lamports escrowed in the raffle and paid to whoever completes the draw
+     * @param payment_mint: the token mint tickets are paid with
+     * @param payment_decimals: decimals of the payment mint the ticket price is given in
+     * @param burn_bps: share of token payments burnt in basis points
+     * @param treasury_bps: share of token payments sent to the treasury in basis points
+     * @param split_payees: wallets sharing the SOL proceeds with the creator
+     * @param split_bps: each split payee's share of the SOL proceeds in basis points
      */
-    pub fn buy_tickets(ctx: Context<BuyTickets>, global_bump: u8, amount: u64) -> ProgramResult {
+    pub fn create_raffle_editions(
+        ctx: Context<CreateRaffle>,
+        _global_bump: u8,
+        ticket_price_reap: u64,
+        ticket_price_sol: u64,
+        end_timestamp: i64,
+        winner_count: u64,
+        max_entrants: u64,
+        commitment: [u8; 32],
+        randomness_mode: u64,
+        crank_tip: u64,
+        payment_mint: Pubkey,
+        payment_decimals: u64,
+        burn_bps: u64,
+        treasury_bps: u64,
+        split_payees: Vec<Pubkey>,
+        split_bps: Vec<u64>,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_init()?;
         let timestamp = Clock::get()?.unix_timestamp;
-        let mut raffle = ctx.accounts.raffle.load_mut()?;
-        if *ctx.accounts.token_mint.key != REAP_TOKEN_MINT.parse::<Pubkey>().unwrap() {
-            return Err(RaffleError::NotREAPToken.into());
+        raffle.check_allocation(&ctx.accounts.admin.key())?;
+        raffle.version = RAFFLE_VERSION;
+
+        validate_raffle_args(
+            timestamp,
+            end_timestamp,
+            winner_count,
+            &commitment,
+            randomness_mode,
+        )?;
+        validate_token_split(burn_bps, treasury_bps)?;
+        start_raffle_creation(
+            &ctx.accounts.global_authority,
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            &ctx.accounts.fee_exemption.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+        record_raffle_created(
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.creator_stats,
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        ctx.accounts.check_token_accounts()?;
+
+        // The master edition must be able to print an edition for every winner
+        let master_edition = ctx
+            .remaining_accounts
+            .get(0)
+            .ok_or(RaffleError::InvalidMasterEdition)?;
+        let (supply, max_supply) =
+            master_edition_supply(master_edition, &ctx.accounts.nft_mint_address.key())?;
+        if let Some(max_supply) = max_supply {
+            if supply + winner_count > max_supply {
+                return Err(RaffleError::NotEnoughPrizes.into());
+            }
         }
 
-        if timestamp > raffle.end_timestamp {
-            return Err(RaffleError::RaffleEnded.into());
-        }
-        if raffle.count + amount >= raffle.max_entrants {
-            return Err(RaffleError::NotEnoughTicketsLeft.into());
-        }
+        // Transfer the master edition to the PDA
+        TokenTransfer {
+            token_program: ctx.accounts.token_program.to_account_info(),
+            source: ctx.accounts.owner_temp_nft_account.to_account_info(),
+            mint: ctx.accounts.nft_mint_address.to_account_info(),
+            destination: ctx.accounts.dest_nft_token_account.to_account_info(),
+            authority: ctx.accounts.admin.to_account_info(),
+        }
+        .invoke_signed(1, &[], &[])?;
+
+        raffle.creator = ctx.accounts.admin.key();
+        raffle.nft_mint = ctx.accounts.nft_mint_address.key();
+        raffle.ticket_price_reap = ticket_price_reap;
+        raffle.ticket_price_sol = ticket_price_sol;
+        raffle.end_timestamp = end_timestamp;
+        raffle.created_at = timestamp;
+        raffle.set_max_entrants(max_entrants)?;
+        raffle.tickets_remaining = max_entrants;
+        raffle.set_winner_count(winner_count)?;
+        raffle.commitment = commitment;
+        raffle.randomness_mode = randomness_mode;
+        raffle.crank_tip = crank_tip;
+        raffle.payment_mint = payment_mint;
+        raffle.payment_decimals = payment_decimals;
+        raffle.burn_bps = burn_bps;
+        raffle.treasury_bps = treasury_bps;
+        raffle.set_splits(&split_payees, &split_bps)?;
+        raffle.prize_type = PRIZE_EDITION;
+        raffle.edition_base = supply;
+        raffle.prize_mints[0] = ctx.accounts.nft_mint_address.key();
+        raffle.prize_count = 1;
+        drop(raffle);
+
+        ctx.accounts.escrow_crank_tip(crank_tip)?;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Create new raffle whose prize NFT stays frozen in the creator's wallet
+     * with global_authority as its delegate instead of being escrowed
+     * @Context has admin, global_authority, treasury, FeeExemption and CreatorStats accounts, zero-account Raffle,
+     * owner's nft ATA, nft mint address, its master edition and the Token Metadata program
+     * @param global_bump: global authority's bump
+     * @param ticket_price_reap: ticket price by reap
+     * @param ticket_price_sol: ticket price by sol
+     * @param end_timestamp: the end time of raffle
+     * @param max_entrants: entrants amount to take part in this raffle
+     * @param commitment: keccak hash of the creator's secret for commit-reveal raffles
+     * @param randomness_mode: 0: pseudo, 1: slot hashes, 2: switchboard, 3: orao, 4: commit-reveal
+     * @param crank_tip: lamports escrowed in the raffle and paid to whoever completes the draw
+     * @param payment_mint: the token mint tickets are paid with
+     * @param payment_decimals: decimals of the payment mint the ticket price is given in
+     * @param burn_bps: share of token payments burnt in basis points
+     * @param treasury_bps: share of token payments sent to the treasury in basis points
+     * @param split_payees: wallets sharing the SOL proceeds with the creator
+     * @param split_bps: each split payee's share of the SOL proceeds in basis points
+     */
+    pub fn create_raffle_delegated(
+        ctx: Context<CreateRaffleDelegated>,
+        global_bump: u8,
+        ticket_price_reap: u64,
+        ticket_price_sol: u64,
+        end_timestamp: i64,
+        max_entrants: u64,
+        commitment: [u8; 32],
+        randomness_mode: u64,
+        crank_tip: u64,
+        payment_mint: Pubkey,
+        payment_decimals: u64,
+        burn_bps: u64,
+        treasury_bps: u64,
+        split_payees: Vec<Pubkey>,
+        split_bps: Vec<u64>,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_init()?;
+        let timestamp = Clock::get()?.unix_timestamp;
+        raffle.check_allocation(&ctx.accounts.admin.key())?;
+        raffle.version = RAFFLE_VERSION;
+
+        validate_raffle_args(timestamp, end_timestamp, 1, &commitment, randomness_mode)?;
+        validate_token_split(burn_bps, treasury_bps)?;
+        start_raffle_creation(
+            &ctx.accounts.global_authority,
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            &ctx.accounts.fee_exemption.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+        record_raffle_created(
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.creator_stats,
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        check_token_account(
+            &ctx.accounts.owner_nft_token_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.nft_mint_address.key(),
+            &ctx.accounts.admin.key(),
+        )?;
+
+        // Approve global_authority as delegate of the NFT and freeze it in the creator's wallet
+        let ix = approve(
+            ctx.accounts.token_program.key,
+            ctx.accounts.owner_nft_token_account.key,
+            &ctx.accounts.global_authority.key(),
+            ctx.accounts.admin.key,
+            &[],
+            1,
+        )?;
+        invoke(
+            &ix,
+            &[
+                ctx.accounts.owner_nft_token_account.to_account_info(),
+                ctx.accounts.global_authority.to_account_info(),
+                ctx.accounts.admin.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+        )?;
+        let seeds = &[GLOBAL_AUTHORITY_SEED.as_bytes(), &[global_bump]];
+        DelegatedFreeze {
+            delegate: ctx.accounts.global_authority.to_account_info(),
+            token_account: ctx.accounts.owner_nft_token_account.to_account_info(),
+            edition: ctx.accounts.edition.to_account_info(),
+            mint: ctx.accounts.nft_mint_address.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        }
+        .invoke_signed(&ctx.accounts.token_metadata_program, false, &[&seeds[..]])?;
+
+        raffle.creator = ctx.accounts.admin.key();
+        raffle.nft_mint = ctx.accounts.nft_mint_address.key();
+        raffle.ticket_price_reap = ticket_price_reap;
+        raffle.ticket_price_sol = ticket_price_sol;
+        raffle.end_timestamp = end_timestamp;
+        raffle.created_at = timestamp;
+        raffle.set_max_entrants(max_entrants)?;
+        raffle.tickets_remaining = max_entrants;
+        raffle.set_winner_count(1)?;
+        raffle.whitelisted = 1;
+        raffle.commitment = commitment;
+        raffle.randomness_mode = randomness_mode;
+        raffle.crank_tip = crank_tip;
+        raffle.payment_mint = payment_mint;
+        raffle.payment_decimals = payment_decimals;
+        raffle.burn_bps = burn_bps;
+        raffle.treasury_bps = treasury_bps;
+        raffle.set_splits(&split_payees, &split_bps)?;
+        raffle.prize_type = PRIZE_DELEGATED;
+        raffle.delegated_account = ctx.accounts.owner_nft_token_account.key();
+        raffle.prize_mints[0] = ctx.accounts.nft_mint_address.key();
+        raffle.prize_count = 1;
+        drop(raffle);
+
+        if crank_tip > 0 {
+            sol_transfer_user(
+                ctx.accounts.admin.to_account_info(),
+                ctx.accounts.raffle.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                crank_tip,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * @dev Create new raffle whose prize NFT stays hidden until the creator reveals it
+     * @Context has admin, global_authority, treasury, FeeExemption and CreatorStats accounts and zero-account Raffle
+     * @param global_bump: global authority's bump
+     * @param ticket_price_reap: ticket price by reap
+     * @param ticket_price_sol: ticket price by sol
+     * @param end_timestamp: the end time of raffle
+     * @param max_entrants: entrants amount to take part in this raffle
+     * @param commitment: keccak hash of the creator's secret for commit-reveal raffles
+     * @param randomness_mode: 0: pseudo, 1: slot hashes, 2: switchboard, 3: orao, 4: commit-reveal
+     * @param crank_tip: lamports escrowed in the raffle and paid to whoever completes the draw
+     * @param prize_hash: keccak hash of the prize mint and a salt
+     * @param payment_mint: the token mint tickets are paid with
+     * @param payment_decimals: decimals of the payment mint the ticket price is given in
+     * @param burn_bps: share of token payments burnt in basis points
+     * @param treasury_bps: share of token payments sent to the treasury in basis points
+     * @param split_payees: wallets sharing the SOL proceeds with the creator
+     * @param split_bps: each split payee's share of the SOL proceeds in basis points
+     */
+    pub fn create_raffle_mystery(
+        ctx: Context<CreateRaffleMystery>,
+        _global_bump: u8,
+        ticket_price_reap: u64,
+        ticket_price_sol: u64,
+        end_timestamp: i64,
+        max_entrants: u64,
+        commitment: [u8; 32],
+        randomness_mode: u64,
+        crank_tip: u64,
+        prize_hash: [u8; 32],
+        payment_mint: Pubkey,
+        payment_decimals: u64,
+        burn_bps: u64,
+        treasury_bps: u64,
+        split_payees: Vec<Pubkey>,
+        split_bps: Vec<u64>,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_init()?;
+        let timestamp = Clock::get()?.unix_timestamp;
+        raffle.check_allocation(&ctx.accounts.admin.key())?;
+        raffle.version = RAFFLE_VERSION;
+
+        validate_raffle_args(timestamp, end_timestamp, 1, &commitment, randomness_mode)?;
+        validate_token_split(burn_bps, treasury_bps)?;
+        start_raffle_creation(
+            &ctx.accounts.global_authority,
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            &ctx.accounts.fee_exemption.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+        record_raffle_created(
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.creator_stats,
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        if prize_hash == [0; 32] {
+            return Err(RaffleError::InvalidPrizeHash.into());
+        }
+
+        raffle.creator = ctx.accounts.admin.key();
+        raffle.ticket_price_reap = ticket_price_reap;
+        raffle.ticket_price_sol = ticket_price_sol;
+        raffle.end_timestamp = end_timestamp;
+        raffle.created_at = timestamp;
+        raffle.set_max_entrants(max_entrants)?;
+        raffle.tickets_remaining = max_entrants;
+        raffle.set_winner_count(1)?;
+        raffle.whitelisted = 1;
+        raffle.commitment = commitment;
+        raffle.randomness_mode = randomness_mode;
+        raffle.crank_tip = crank_tip;
+        raffle.payment_mint = payment_mint;
+        raffle.payment_decimals = payment_decimals;
+        raffle.burn_bps = burn_bps;
+        raffle.treasury_bps = treasury_bps;
+        raffle.set_splits(&split_payees, &split_bps)?;
+        raffle.prize_type = PRIZE_NFT;
+        raffle.prize_hash = prize_hash;
+        drop(raffle);
+
+        if crank_tip > 0 {
+            sol_transfer_user(
+                ctx.accounts.admin.to_account_info(),
+                ctx.accounts.raffle.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                crank_tip,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * @dev Create new raffle whose prize is a SOL pot held in the raffle vault PDA
+     * @Context has admin, global_authority, treasury, FeeExemption and CreatorStats accounts,
+     * zero-account Raffle and the raffle's vault PDA
+     * @param vault_bump: raffle vault's bump
+     * @param ticket_price_reap: ticket price by reap
+     * @param ticket_price_sol: ticket price by sol
+     * @param end_timestamp: the end time of raffle
+     * @param winner_count: how many winners will share the prize
+     * @param max_entrants: entrants amount to take part in this raffle
+     * @param commitment: keccak hash of the creator's secret for commit-reveal raffles
+     * @param randomness_mode: 0: pseudo, 1: slot hashes, 2: switchboard, 3: orao, 4: commit-reveal
+     * @param crank_tip: lamports escrowed in the raffle and paid to whoever completes the draw
+     * @param prize_amount: lamports escrowed in the vault and split evenly across the winners
+     * @param payment_mint: the token mint tickets are paid with
+     * @param payment_decimals: decimals of the payment mint the ticket price is given in
+     * @param burn_bps: share of token payments burnt in basis points
+     * @param treasury_bps: share of token payments sent to the treasury in basis points
+     * @param split_payees: wallets sharing the SOL proceeds with the creator
+     * @param split_bps: each split payee's share of the SOL proceeds in basis points
+     */
+    pub fn create_raffle_sol_prize(
+        ctx: Context<CreateRaffleSolPrize>,
+        _vault_bump: u8,
+        ticket_price_reap: u64,
+        ticket_price_sol: u64,
+        end_timestamp: i64,
+        winner_count: u64,
+        max_entrants: u64,
+        commitment: [u8; 32],
+        randomness_mode: u64,
+        crank_tip: u64,
+        prize_amount: u64,
+        payment_mint: Pubkey,
+        payment_decimals: u64,
+        burn_bps: u64,
+        treasury_bps: u64,
+        split_payees: Vec<Pubkey>,
+        split_bps: Vec<u64>,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_init()?;
+        let timestamp = Clock::get()?.unix_timestamp;
+        raffle.check_allocation(&ctx.accounts.admin.key())?;
+        raffle.version = RAFFLE_VERSION;
+
+        validate_raffle_args(
+            timestamp,
+            end_timestamp,
+            winner_count,
+            &commitment,
+            randomness_mode,
+        )?;
+        validate_token_split(burn_bps, treasury_bps)?;
+        start_raffle_creation(
+            &ctx.accounts.global_authority,
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            &ctx.accounts.fee_exemption.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+        record_raffle_created(
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.creator_stats,
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        if winner_count == 0 || prize_amount < winner_count {
+            return Err(RaffleError::NoPrize.into());
+        }
+
+        raffle.creator = ctx.accounts.admin.key();
+        raffle.ticket_price_reap = ticket_price_reap;
+        raffle.ticket_price_sol = ticket_price_sol;
+        raffle.end_timestamp = end_timestamp;
+        raffle.created_at = timestamp;
+        raffle.set_max_entrants(max_entrants)?;
+        raffle.tickets_remaining = max_entrants;
+        raffle.set_winner_count(winner_count)?;
+        raffle.whitelisted = 1;
+        raffle.commitment = commitment;
+        raffle.randomness_mode = randomness_mode;
+        raffle.crank_tip = crank_tip;
+        raffle.payment_mint = payment_mint;
+        raffle.payment_decimals = payment_decimals;
+        raffle.burn_bps = burn_bps;
+        raffle.treasury_bps = treasury_bps;
+        raffle.set_splits(&split_payees, &split_bps)?;
+        raffle.prize_type = PRIZE_SOL;
+        raffle.prize_amount = prize_amount;
+        drop(raffle);
+
+        // Fund the vault with the prize pot and keep it rent exempt until the pot is emptied
+        let rent_reserve = Rent::get()?.minimum_balance(0);
+        sol_transfer_user(
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            prize_amount + rent_reserve,
+        )?;
+        if crank_tip > 0 {
+            sol_transfer_user(
+                ctx.accounts.admin.to_account_info(),
+                ctx.accounts.raffle.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                crank_tip,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * @dev Create new raffle whose prize is a compressed NFT escrowed to the global authority
+     * @Context has admin, global_authority, treasury, FeeExemption and CreatorStats accounts and zero-account Raffle
+     * and the Bubblegum tree authority, merkle tree, log wrapper, compression and Bubblegum programs
+     * with the cNFT's proof path as remaining accounts
+     * @param global_bump: global authority's bump
+     * @param ticket_price_reap: ticket price by reap
+     * @param ticket_price_sol: ticket price by sol
+     * @param end_timestamp: the end time of raffle
+     * @param max_entrants: entrants amount to take part in this raffle
+     * @param commitment: keccak hash of the creator's secret for commit-reveal raffles
+     * @param randomness_mode: 0: pseudo, 1: slot hashes, 2: switchboard, 3: orao, 4: commit-reveal
+     * @param crank_tip: lamports escrowed in the raffle and paid to whoever completes the draw
+     * @param root: the current root of the merkle tree
+     * @param data_hash: the cNFT's metadata hash
+     * @param creator_hash: the cNFT's creators hash
+     * @param nonce: the cNFT's leaf nonce
+     * @param index: the cNFT's leaf index
+     * @param payment_mint: the token mint tickets are paid with
+     * @param payment_decimals: decimals of the payment mint the ticket price is given in
+     * @param burn_bps: share of token payments burnt in basis points
+     * @param treasury_bps: share of token payments sent to the treasury in basis points
+     * @param split_payees: wallets sharing the SOL proceeds with the creator
+     * @param split_bps: each split payee's share of the SOL proceeds in basis points
+     */
+    pub fn create_raffle_cnft<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateRaffleCnft<'info>>,
+        _global_bump: u8,
+        ticket_price_reap: u64,
+        ticket_price_sol: u64,
+        end_timestamp: i64,
+        max_entrants: u64,
+        commitment: [u8; 32],
+        randomness_mode: u64,
+        crank_tip: u64,
+        root: [u8; 32],
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+        nonce: u64,
+        index: u32,
+        payment_mint: Pubkey,
+        payment_decimals: u64,
+        burn_bps: u64,
+        treasury_bps: u64,
+        split_payees: Vec<Pubkey>,
+        split_bps: Vec<u64>,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_init()?;
+        let timestamp = Clock::get()?.unix_timestamp;
+        raffle.check_allocation(&ctx.accounts.admin.key())?;
+        raffle.version = RAFFLE_VERSION;
+
+        validate_raffle_args(timestamp, end_timestamp, 1, &commitment, randomness_mode)?;
+        validate_token_split(burn_bps, treasury_bps)?;
+        start_raffle_creation(
+            &ctx.accounts.global_authority,
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            &ctx.accounts.fee_exemption.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+        record_raffle_created(
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.creator_stats,
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        if ctx.accounts.bubblegum_program.key() != BUBBLEGUM_PROGRAM_ID.parse::<Pubkey>().unwrap() {
+            return Err(RaffleError::InvalidBubblegumProgram.into());
+        }
+
+        // Transfer the cNFT to the PDA
+        let leaf = CnftLeaf {
+            root,
+            data_hash,
+            creator_hash,
+            nonce,
+            index,
+        };
+        let mut accounts = vec![
+            ctx.accounts.tree_authority.to_account_info(),
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.global_authority.to_account_info(),
+            ctx.accounts.merkle_tree.to_account_info(),
+            ctx.accounts.log_wrapper.to_account_info(),
+            ctx.accounts.compression_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ];
+        accounts.extend_from_slice(ctx.remaining_accounts);
+        bubblegum_transfer(&ctx.accounts.bubblegum_program, &accounts, &leaf, &[])?;
+
+        raffle.creator = ctx.accounts.admin.key();
+        raffle.ticket_price_reap = ticket_price_reap;
+        raffle.ticket_price_sol = ticket_price_sol;
+        raffle.end_timestamp = end_timestamp;
+        raffle.created_at = timestamp;
+        raffle.set_max_entrants(max_entrants)?;
+        raffle.tickets_remaining = max_entrants;
+        raffle.set_winner_count(1)?;
+        raffle.whitelisted = 1;
+        raffle.commitment = commitment;
+        raffle.randomness_mode = randomness_mode;
+        raffle.crank_tip = crank_tip;
+        raffle.payment_mint = payment_mint;
+        raffle.payment_decimals = payment_decimals;
+        raffle.burn_bps = burn_bps;
+        raffle.treasury_bps = treasury_bps;
+        raffle.set_splits(&split_payees, &split_bps)?;
+        raffle.prize_type = PRIZE_CNFT;
+        raffle.merkle_tree = ctx.accounts.merkle_tree.key();
+        raffle.cnft_data_hash = data_hash;
+        raffle.cnft_creator_hash = creator_hash;
+        raffle.cnft_nonce = nonce;
+        raffle.cnft_index = index as u64;
+        drop(raffle);
+
+        if crank_tip > 0 {
+            sol_transfer_user(
+                ctx.accounts.admin.to_account_info(),
+                ctx.accounts.raffle.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                crank_tip,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * @dev Create new raffle whose prize is an MPL Core asset
+     * @Context has admin, global_authority, treasury, FeeExemption and CreatorStats accounts, zero-account Raffle,
+     * the asset, its collection (or the Core program if it has none) and the Core program
+     * @param global_bump: global_authority's bump
+     * @param ticket_price_reap: ticket price with REAP token
+     * @param ticket_price_sol: ticket price with SOL
+     * @param end_timestamp: the end time of raffle
+     * @param max_entrants: entrants amount to take part in this raffle
+     * @param commitment: hash of the creator's secret for commit-reveal draws
+     * @param randomness_mode: where the draw randomness comes from
+     * @param crank_tip: lamports escrowed in the raffle and paid to whoever completes the draw
+     * @param payment_mint: the token mint tickets are paid with
+     * @param payment_decimals: decimals of the payment mint the ticket price is given in
+     * @param burn_bps: share of token payments burnt in basis points
+     * @param treasury_bps: share of token payments sent to the treasury in basis points
+     * @param split_payees: wallets sharing the SOL proceeds with the creator
+     * @param split_bps: each split payee's share of the SOL proceeds in basis points
+     */
+    pub fn create_raffle_core(
+        ctx: Context<CreateRaffleCore>,
+        _global_bump: u8,
+        ticket_price_reap: u64,
+        ticket_price_sol: u64,
+        end_timestamp: i64,
+        max_entrants: u64,
+        commitment: [u8; 32],
+        randomness_mode: u64,
+        crank_tip: u64,
+        payment_mint: Pubkey,
+        payment_decimals: u64,
+        burn_bps: u64,
+        treasury_bps: u64,
+        split_payees: Vec<Pubkey>,
+        split_bps: Vec<u64>,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_init()?;
+        let timestamp = Clock::get()?.unix_timestamp;
+        raffle.check_allocation(&ctx.accounts.admin.key())?;
+        raffle.version = RAFFLE_VERSION;
+
+        validate_raffle_args(timestamp, end_timestamp, 1, &commitment, randomness_mode)?;
+        validate_token_split(burn_bps, treasury_bps)?;
+        start_raffle_creation(
+            &ctx.accounts.global_authority,
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            &ctx.accounts.fee_exemption.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+        record_raffle_created(
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.creator_stats,
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        if *ctx.accounts.asset.owner != MPL_CORE_PROGRAM_ID.parse::<Pubkey>().unwrap() {
+            return Err(RaffleError::InvalidCoreAsset.into());
+        }
+
+        // Transfer the Core asset to the PDA
+        CoreTransfer {
+            asset: ctx.accounts.asset.to_account_info(),
+            collection: ctx.accounts.collection.to_account_info(),
+            payer: ctx.accounts.admin.to_account_info(),
+            authority: ctx.accounts.admin.to_account_info(),
+            new_owner: ctx.accounts.global_authority.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        }
+        .invoke_signed(&ctx.accounts.core_program, &[])?;
+
+        raffle.creator = ctx.accounts.admin.key();
+        raffle.nft_mint = ctx.accounts.asset.key();
+        raffle.prize_mints[0] = ctx.accounts.asset.key();
+        raffle.prize_count = 1;
+        raffle.ticket_price_reap = ticket_price_reap;
+        raffle.ticket_price_sol = ticket_price_sol;
+        raffle.end_timestamp = end_timestamp;
+        raffle.created_at = timestamp;
+        raffle.set_max_entrants(max_entrants)?;
+        raffle.tickets_remaining = max_entrants;
+        raffle.set_winner_count(1)?;
+        raffle.whitelisted = 1;
+        raffle.commitment = commitment;
+        raffle.randomness_mode = randomness_mode;
+        raffle.crank_tip = crank_tip;
+        raffle.payment_mint = payment_mint;
+        raffle.payment_decimals = payment_decimals;
+        raffle.burn_bps = burn_bps;
+        raffle.treasury_bps = treasury_bps;
+        raffle.set_splits(&split_payees, &split_bps)?;
+        raffle.prize_type = PRIZE_CORE;
+        drop(raffle);
+
+        if crank_tip > 0 {
+            sol_transfer_user(
+                ctx.accounts.admin.to_account_info(),
+                ctx.accounts.raffle.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                crank_tip,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * @dev Create new raffle with the parameters of one of the creator's earlier NFT raffles
+     * the prize is escrowed fresh and every timestamp is moved forward by the time since the source was created
+     * @Context has admin, global_authority, treasury, the admin's FeeExemption and CreatorStats PDAs.
+     * and zero-account Raffle, owner's nft ATA and global_authority's nft ATA
+     * and nft mint address, the source raffle is the first remaining account
+     * followed by the extra prizes or the pNFT transfer accounts as for create_raffle
+     * @param global_bump: global authority's bump
+     * @param commitment: keccak hash of a new secret for commit-reveal raffles
+     */
+    pub fn clone_raffle<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateRaffle<'info>>,
+        _global_bump: u8,
+        commitment: [u8; 32],
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_init()?;
+        let timestamp = Clock::get()?.unix_timestamp;
+        raffle.check_allocation(&ctx.accounts.admin.key())?;
+        raffle.version = RAFFLE_VERSION;
+
+        let (source_info, remaining_accounts) = ctx
+            .remaining_accounts
+            .split_first()
+            .ok_or(RaffleError::InvalidPrizeAccounts)?;
+        let source_loader = AccountLoader::<RafflePool>::try_from(source_info)?;
+        let source = source_loader.load()?;
+        if source.creator != ctx.accounts.admin.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if source.prize_type != PRIZE_NFT
+            || source.prize_hash != [0u8; 32]
+            || source.created_at == 0
+        {
+            return Err(RaffleError::NotCloneable.into());
+        }
+        raffle.copy_params(&source, timestamp - source.created_at);
+        drop(source);
+
+        validate_raffle_args(
+            timestamp,
+            raffle.end_timestamp,
+            raffle.winner_count(),
+            &commitment,
+            raffle.randomness_mode,
+        )?;
+        start_raffle_creation(
+            &ctx.accounts.global_authority,
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            &ctx.accounts.fee_exemption.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+        record_raffle_created(
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.creator_stats,
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        ctx.accounts.check_token_accounts()?;
+
+        // Transfer NFT to the PDA
+        let src_token_account_info = &mut &ctx.accounts.owner_temp_nft_account;
+        let dest_token_account_info = &mut &ctx.accounts.dest_nft_token_account;
+        let token_program = &mut &ctx.accounts.token_program;
+
+        if raffle.programmable == 1 {
+            PnftTransfer {
+                token: src_token_account_info.to_account_info(),
+                token_owner: ctx.accounts.admin.to_account_info(),
+                destination: dest_token_account_info.to_account_info(),
+                destination_owner: ctx.accounts.global_authority.to_account_info(),
+                mint: ctx.accounts.nft_mint_address.to_account_info(),
+                payer: ctx.accounts.admin.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: token_program.to_account_info(),
+            }
+            .invoke_signed(remaining_accounts, &[])?;
+        } else {
+            TokenTransfer {
+                token_program: token_program.to_account_info(),
+                source: src_token_account_info.to_account_info(),
+                mint: ctx.accounts.nft_mint_address.to_account_info(),
+                destination: dest_token_account_info.to_account_info(),
+                authority: ctx.accounts.admin.to_account_info(),
+            }
+            .invoke_signed(1, &[], &[])?;
+        }
+        raffle.prize_mints[0] = ctx.accounts.nft_mint_address.key();
+        raffle.prize_count = 1;
+
+        // Transfer the extra prize NFTs to the PDA
+        let extra_prizes: &[AccountInfo] = if raffle.programmable == 1 {
+            &[]
+        } else {
+            remaining_accounts
+        };
+        ctx.accounts
+            .escrow_extra_prizes(&mut raffle, extra_prizes)?;
+        if raffle.whitelisted == 1 && raffle.winner_count() > raffle.prize_count {
+            return Err(RaffleError::NotEnoughPrizes.into());
+        }
+
+        raffle.creator = ctx.accounts.admin.key();
+        raffle.nft_mint = ctx.accounts.nft_mint_address.key();
+        raffle.created_at = timestamp;
+        raffle.commitment = commitment;
+        let crank_tip = raffle.crank_tip;
+        drop(raffle);
+
+        ctx.accounts.escrow_crank_tip(crank_tip)?;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Declare the collection of the escrowed prize NFTs before any ticket is sold
+     * every prize's Metaplex metadata must have the collection verified, so buyers can trust
+     * a raffle whose prize_collection is set, adding a prize afterwards clears it
+     * @Context has creator and raffle account
+     * and the metadata account of each prize mint as remaining accounts in prize order
+     * @param collection: the Metaplex collection mint, default clears the declaration
+     */
+    pub fn set_prize_collection(ctx: Context<UpdateRaffle>, collection: Pubkey) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.count() != 0 {
+            return Err(RaffleError::TicketsSold.into());
+        }
+        if collection != Pubkey::default() {
+            if raffle.prize_type != PRIZE_NFT || raffle.prize_hidden() {
+                return Err(RaffleError::InvalidPrizeAccounts.into());
+            }
+            if ctx.remaining_accounts.len() != raffle.prize_count as usize {
+                return Err(RaffleError::InvalidPrizeAccounts.into());
+            }
+            for (i, metadata) in ctx.remaining_accounts.iter().enumerate() {
+                let prize_mint = raffle.prize_mints[i];
+                if verified_collection(&prize_mint, metadata)? != Some(collection) {
+                    return Err(RaffleError::PrizeNotInCollection.into());
+                }
+            }
+        }
+        raffle.prize_collection = collection;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Escrow one more prize NFT in a live raffle and add a winner slot for it
+     * @Context has creator and global_authority account, raffle account,
+     * creator's nft ATA, global_authority's nft ATA and the nft mint address,
+     * pNFT raffles pass the pNFT transfer accounts as remaining accounts
+     * @param global_bump: global_authority's bump
+     */
+    pub fn add_prize<'info>(
+        ctx: Context<'_, '_, '_, 'info, AddPrize<'info>>,
+        _global_bump: u8,
+    ) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if timestamp >= raffle.end_timestamp {
+            return Err(RaffleError::RaffleEnded.into());
+        }
+        if raffle.prize_type != PRIZE_NFT || raffle.whitelisted != 1 || raffle.tier_count != 0 {
+            return Err(RaffleError::InvalidPrizeAccounts.into());
+        }
+        if raffle.revealed == 1 || raffle.revealed_count != 0 {
+            return Err(RaffleError::WinnersAlreadyDrawn.into());
+        }
+        if raffle.prize_hidden() {
+            return Err(RaffleError::PrizeNotRevealed.into());
+        }
+        if raffle.prize_count as usize >= MAX_WINNERS
+            || raffle.winner_count() as usize >= MAX_WINNERS
+        {
+            return Err(RaffleError::TooManyWinners.into());
+        }
+        ctx.accounts.check_token_accounts()?;
+        // The new prize has not been verified against the declared collection
+        raffle.prize_collection = Pubkey::default();
+
+        // Transfer the prize NFT to the PDA
+        if raffle.programmable == 1 {
+            PnftTransfer {
+                token: ctx.accounts.owner_temp_nft_account.to_account_info(),
+                token_owner: ctx.accounts.creator.to_account_info(),
+                destination: ctx.accounts.dest_nft_token_account.to_account_info(),
+                destination_owner: ctx.accounts.global_authority.to_account_info(),
+                mint: ctx.accounts.nft_mint_address.to_account_info(),
+                payer: ctx.accounts.creator.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            }
+            .invoke_signed(ctx.remaining_accounts, &[])?;
+        } else {
+            TokenTransfer {
+                token_program: ctx.accounts.token_program.to_account_info(),
+                source: ctx.accounts.owner_temp_nft_account.to_account_info(),
+                mint: ctx.accounts.nft_mint_address.to_account_info(),
+                destination: ctx.accounts.dest_nft_token_account.to_account_info(),
+                authority: ctx.accounts.creator.to_account_info(),
+            }
+            .invoke_signed(1, ctx.remaining_accounts, &[])?;
+        }
+
+        let prize_index = raffle.prize_count as usize;
+        raffle.prize_mints[prize_index] = ctx.accounts.nft_mint_address.key();
+        raffle.prize_count += 1;
+        let winner_count = raffle.winner_count();
+        raffle.set_winner_count(winner_count + 1)?;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Reveal and escrow the hidden prize of a mystery raffle, must happen before the draw
+     * @Context has creator and global_authority account, raffle account,
+     * creator's nft ATA, global_authority's nft ATA and the nft mint address
+     * @param global_bump: global_authority's bump
+     * @param salt: the salt hashed with the prize mint at creation
+     */
+    pub fn reveal_prize<'info>(
+        ctx: Context<'_, '_, '_, 'info, AddPrize<'info>>,
+        _global_bump: u8,
+        salt: [u8; 32],
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if !raffle.prize_hidden() {
+            return Err(RaffleError::NoPrize.into());
+        }
+        if raffle.revealed == 1 || raffle.revealed_count != 0 {
+            return Err(RaffleError::WinnersAlreadyDrawn.into());
+        }
+        let prize_mint = ctx.accounts.nft_mint_address.key();
+        if keccak::hashv(&[&prize_mint.to_bytes(), &salt]).to_bytes() != raffle.prize_hash {
+            return Err(RaffleError::InvalidPrizeHash.into());
+        }
+        ctx.accounts.check_token_accounts()?;
+
+        // Transfer the revealed prize NFT to the PDA
+        TokenTransfer {
+            token_program: ctx.accounts.token_program.to_account_info(),
+            source: ctx.accounts.owner_temp_nft_account.to_account_info(),
+            mint: ctx.accounts.nft_mint_address.to_account_info(),
+            destination: ctx.accounts.dest_nft_token_account.to_account_info(),
+            authority: ctx.accounts.creator.to_account_info(),
+        }
+        .invoke_signed(1, ctx.remaining_accounts, &[])?;
+
+        raffle.nft_mint = prize_mint;
+        raffle.prize_mints[0] = prize_mint;
+        raffle.prize_count = 1;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Escrow one whitelist token per winner of a whitelist raffle, claimed with claim_reward
+     * @Context has creator and global_authority account, raffle account,
+     * creator's whitelist token ATA, global_authority's whitelist token ATA,
+     * the whitelist token mint and token program
+     * @param global_bump: global_authority's bump
+     */
+    pub fn escrow_wl_tokens<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetPrizeTiers<'info>>,
+        _global_bump: u8,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.prize_type != PRIZE_NFT
+            || raffle.whitelisted != 0
+            || raffle.wl_mint != Pubkey::default()
+            || raffle.winner_count() == 0
+        {
+            return Err(RaffleError::InvalidPrizeAccounts.into());
+        }
+        if raffle.revealed == 1 || raffle.revealed_count != 0 {
+            return Err(RaffleError::WinnersAlreadyDrawn.into());
+        }
+        ctx.accounts.check_token_accounts()?;
+
+        // Transfer a whitelist token for every winner to the PDA
+        TokenTransfer {
+            token_program: ctx.accounts.token_program.to_account_info(),
+            source: ctx.accounts.creator_token_account.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
+            destination: ctx.accounts.dest_token_account.to_account_info(),
+            authority: ctx.accounts.creator.to_account_info(),
+        }
+        .invoke_signed(raffle.winner_count(), ctx.remaining_accounts, &[])?;
+        raffle.wl_mint = ctx.accounts.token_mint.key();
+        raffle.wl_escrowed = raffle.winner_count();
+
+        Ok(())
+    }
+
+    /**
+     * @dev Split the winners of an NFT raffle into prize tiers before any ticket is sold
+     * @Context has creator and global_authority account, raffle account,
+     * creator's token ATA, global_authority's token ATA, the tier token mint and token program
+     * which are only used when a tier pays tokens
+     * @param global_bump: global_authority's bump
+     * @param tier_ends: the exclusive end winner index of each tier, the last one is the winner count
+     * @param tier_types: 0: the winner slot's escrowed NFT, 1: tokens, 5: whitelist spot
+     * @param tier_amounts: token amount per winner for token tiers
+     */
+    pub fn set_prize_tiers<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetPrizeTiers<'info>>,
+        _global_bump: u8,
+        tier_ends: Vec<u64>,
+        tier_types: Vec<u64>,
+        tier_amounts: Vec<u64>,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.prize_type != PRIZE_NFT || raffle.tier_count != 0 || raffle.count() != 0 {
+            return Err(RaffleError::InvalidPrizeTiers.into());
+        }
+        let tier_count = tier_ends.len();
+        if tier_count == 0
+            || tier_count > MAX_TIERS
+            || tier_types.len() != tier_count
+            || tier_amounts.len() != tier_count
+            || tier_ends[tier_count - 1] != raffle.winner_count()
+        {
+            return Err(RaffleError::InvalidPrizeTiers.into());
+        }
+
+        let mut start = 0;
+        let mut token_total: u64 = 0;
+        for (i, &end) in tier_ends.iter().enumerate() {
+            if end <= start {
+                return Err(RaffleError::InvalidPrizeTiers.into());
+            }
+            if tier_types[i] == PRIZE_NFT {
+                if end > raffle.prize_count {
+                    return Err(RaffleError::NotEnoughPrizes.into());
+                }
+            } else if tier_types[i] == PRIZE_TOKEN {
+                if tier_amounts[i] == 0 {
+                    return Err(RaffleError::InvalidPrizeTiers.into());
+                }
+                token_total = (end - start)
+                    .checked_mul(tier_amounts[i])
+                    .and_then(|amount| token_total.checked_add(amount))
+                    .ok_or(RaffleError::InvalidCalculation)?;
+            } else if tier_types[i] != PRIZE_WL {
+                return Err(RaffleError::InvalidPrizeTiers.into());
+            }
+            raffle.tier_ends[i] = end;
+            raffle.tier_types[i] = tier_types[i];
+            raffle.tier_amounts[i] = tier_amounts[i];
+            start = end;
+        }
+
+        // Transfer the tokens for all token tiers to the PDA
+        if token_total > 0 {
+            ctx.accounts.check_token_accounts()?;
+            TokenTransfer {
+                token_program: ctx.accounts.token_program.to_account_info(),
+                source: ctx.accounts.creator_token_account.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                destination: ctx.accounts.dest_token_account.to_account_info(),
+                authority: ctx.accounts.creator.to_account_info(),
+            }
+            .invoke_signed(token_total, ctx.remaining_accounts, &[])?;
+            raffle.tier_mint = ctx.accounts.token_mint.key();
+            raffle.tier_escrowed = token_total;
+        }
+        raffle.tier_count = tier_count as u64;
+        // won NFT slots now belong to their winners
+        raffle.whitelisted = 1;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Edit the ticket prices, entrants, winners and end of a raffle before any ticket is sold
+     * @Context has creator and raffle account
+     * @param ticket_price_reap: ticket price in payment tokens
+     * @param ticket_price_sol: ticket price in lamports
+     * @param max_entrants: entrant amount to take part in this raffle
+     * @param winner_count: how many winners will get prize
+     * @param end_timestamp: the end time of the raffle
+     */
+    pub fn update_raffle(
+        ctx: Context<UpdateRaffle>,
+        ticket_price_reap: u64,
+        ticket_price_sol: u64,
+        max_entrants: u64,
+        winner_count: u64,
+        end_timestamp: i64,
+    ) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.count() != 0 {
+            return Err(RaffleError::TicketsSold.into());
+        }
+        if raffle.status()? != RaffleStatus::Created {
+            return Err(RaffleError::RaffleEnded.into());
+        }
+        validate_raffle_args(
+            timestamp,
+            end_timestamp,
+            winner_count,
+            &raffle.commitment,
+            raffle.randomness_mode,
+        )?;
+        if end_timestamp <= raffle.start_timestamp
+            || end_timestamp - raffle.created_at > MAX_RAFFLE_DURATION
+        {
+            return Err(RaffleError::EndTimeError.into());
+        }
+        if max_entrants < raffle.min_entrants {
+            return Err(RaffleError::InvalidMinEntrants.into());
+        }
+        if !raffle.winner_count_allowed(winner_count) {
+            return Err(RaffleError::InvalidWinnerCount.into());
+        }
+        raffle.ticket_price_reap = ticket_price_reap;
+        raffle.ticket_price_sol = ticket_price_sol;
+        raffle.set_max_entrants(max_entrants)?;
+        raffle.tickets_remaining = max_entrants;
+        raffle.set_winner_count(winner_count)?;
+        raffle.end_timestamp = end_timestamp;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Set which payment combinations buyers may choose from before any ticket is sold
+     * @Context has creator and raffle account
+     * @param payment_options: bitmask of 1: SOL only, 2: token only, 4: both prices together
+     */
+    pub fn set_payment_options(ctx: Context<UpdateRaffle>, payment_options: u64) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.count() != 0 {
+            return Err(RaffleError::TicketsSold.into());
+        }
+        if payment_options == 0 || payment_options & !(PAY_SOL | PAY_TOKEN | PAY_BOTH) != 0 {
+            return Err(RaffleError::PaymentNotAllowed.into());
+        }
+        raffle.payment_options = payment_options;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Set the ticket bundle discounts before any ticket is sold
+     * e.g. sizes [10, 50] and paid [9, 42]: 10 tickets for the price of 9, 50 for the price of 42
+     * @Context has creator and raffle account
+     * @param deal_sizes: bundle sizes in ascending order, empty turns discounts off
+     * @param deal_paid: tickets paid for each bundle size
+     */
+    pub fn set_bundle_discounts(
+        ctx: Context<UpdateRaffle>,
+        deal_sizes: Vec<u64>,
+        deal_paid: Vec<u64>,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.count() != 0 {
+            return Err(RaffleError::InvalidDiscounts.into());
+        }
+        if deal_sizes.len() != deal_paid.len() || deal_sizes.len() > MAX_DEALS {
+            return Err(RaffleError::InvalidDiscounts.into());
+        }
+        let mut last_size = 0;
+        for i in 0..deal_sizes.len() {
+            if deal_sizes[i] <= last_size || deal_paid[i] == 0 || deal_paid[i] > deal_sizes[i] {
+                return Err(RaffleError::InvalidDiscounts.into());
+            }
+            last_size = deal_sizes[i];
+            raffle.deal_sizes[i] = deal_sizes[i];
+            raffle.deal_paid[i] = deal_paid[i];
+        }
+        raffle.deal_count = deal_sizes.len() as u64;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Set early-bird price windows before any ticket is sold
+     * e.g. ends [t+24h] with sol [0.5 SOL]: 0.5 SOL per ticket for the first day, then the base price
+     * @Context has creator and raffle account
+     * @param window_ends: end time of each price window in ascending order, empty turns windows off
+     * @param window_reap: ticket price by reap during each window
+     * @param window_sol: ticket price by sol during each window
+     */
+    pub fn set_price_windows(
+        ctx: Context<UpdateRaffle>,
+        window_ends: Vec<i64>,
+        window_reap: Vec<u64>,
+        window_sol: Vec<u64>,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.count() != 0 {
+            return Err(RaffleError::InvalidPriceWindows.into());
+        }
+        if window_ends.len() != window_reap.len()
+            || window_ends.len() != window_sol.len()
+            || window_ends.len() > MAX_WINDOWS
+        {
+            return Err(RaffleError::InvalidPriceWindows.into());
+        }
+        let mut last_end = 0;
+        for i in 0..window_ends.len() {
+            if window_ends[i] <= last_end || window_ends[i] > raffle.end_timestamp {
+                return Err(RaffleError::InvalidPriceWindows.into());
+            }
+            last_end = window_ends[i];
+            raffle.window_ends[i] = window_ends[i];
+            raffle.window_reap[i] = window_reap[i];
+            raffle.window_sol[i] = window_sol[i];
+        }
+        raffle.window_count = window_ends.len() as u64;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Turn on a dutch auction before any ticket is sold
+     * the ticket prices decline from the base prices now to the floor prices at the raffle end
+     * @Context has creator and raffle account
+     * @param floor_price_reap: ticket price by reap at the end of the raffle
+     * @param floor_price_sol: ticket price by sol at the end of the raffle
+     * @param auction_step: seconds between price drops, 0 declines linearly
+     */
+    pub fn set_dutch_auction(
+        ctx: Context<UpdateRaffle>,
+        floor_price_reap: u64,
+        floor_price_sol: u64,
+        auction_step: u64,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.count() != 0 || timestamp >= raffle.end_timestamp {
+            return Err(RaffleError::InvalidAuction.into());
+        }
+        if floor_price_reap > raffle.ticket_price_reap || floor_price_sol > raffle.ticket_price_sol
+        {
+            return Err(RaffleError::InvalidAuction.into());
+        }
+        raffle.auction_start = timestamp;
+        raffle.auction_step = auction_step;
+        raffle.floor_price_reap = floor_price_reap;
+        raffle.floor_price_sol = floor_price_sol;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Set the buy-it-now price, 0 turns the buyout off
+     * only raffles paid in SOL only can be bought out, the refunds cover SOL proceeds
+     * @Context has creator and raffle account
+     * @param buyout_price: lamports paid by buy_now to win the raffle instantly
+     */
+    pub fn set_buyout_price(ctx: Context<UpdateRaffle>, buyout_price: u64) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.revealed != 0 {
+            return Err(RaffleError::RaffleEnded.into());
+        }
+        if buyout_price > 0 && raffle.accepts_token_payment() {
+            return Err(RaffleError::BuyoutSolOnly.into());
+        }
+        raffle.buyout_price = buyout_price;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Escrow every ticket payment, fees and tokens included, so buyers can be refunded
+     * if the raffle is cancelled, before any ticket is sold
+     * burned tokens cannot be refunded so the raffle must not burn token payments
+     * @Context has creator and raffle account
+     */
+    pub fn set_refundable(ctx: Context<UpdateRaffle>) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.count() != 0 {
+            return Err(RaffleError::TicketsSold.into());
+        }
+        if raffle.burn_bps != 0 {
+            return Err(RaffleError::InvalidFee.into());
+        }
+        raffle.refundable = 1;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Close ticket sales and hold back the draw separately before any ticket is sold
+     * so a verification window can pass between the sales closing and the draw
+     * @Context has creator and raffle account
+     * @param sales_end_timestamp: when ticket sales close, 0 closes them at the end timestamp
+     * @param draw_after_timestamp: when the winners can be drawn, 0 keeps the end timestamp
+     */
+    pub fn set_sale_windows(
+        ctx: Context<UpdateRaffle>,
+        sales_end_timestamp: i64,
+        draw_after_timestamp: i64,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.count() != 0 {
+            return Err(RaffleError::TicketsSold.into());
+        }
+        if (sales_end_timestamp != 0 || draw_after_timestamp != 0) && raffle.snipe_window != 0 {
+            return Err(RaffleError::InvalidSaleWindows.into());
+        }
+        if sales_end_timestamp != 0
+            && (sales_end_timestamp <= raffle.start_timestamp
+                || sales_end_timestamp > raffle.end_timestamp)
+        {
+            return Err(RaffleError::InvalidSaleWindows.into());
+        }
+        raffle.sales_end_timestamp = sales_end_timestamp;
+        if draw_after_timestamp != 0
+            && (draw_after_timestamp < raffle.end_timestamp
+                || draw_after_timestamp - raffle.created_at > MAX_RAFFLE_DURATION)
+        {
+            return Err(RaffleError::InvalidSaleWindows.into());
+        }
+        raffle.draw_after_timestamp = draw_after_timestamp;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Extend the raffle whenever a ticket is bought in its final minutes, before any ticket is sold
+     * e.g. a 10 minute window and 6 extensions: a purchase in the last 10 minutes adds 10 minutes, up to 6 times
+     * @Context has creator and raffle account
+     * @param snipe_window: seconds before the end that trigger an extension and the seconds added, 0 turns it off
+     * @param max_extensions: the most times the end can be extended
+     */
+    pub fn set_anti_snipe(
+        ctx: Context<UpdateRaffle>,
+        snipe_window: i64,
+        max_extensions: u64,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.count() != 0 {
+            return Err(RaffleError::TicketsSold.into());
+        }
+        // extensions only move the end timestamp, not a separate sales end or draw timestamp
+        if snipe_window < 0
+            || (snipe_window > 0
+                && (raffle.sales_end_timestamp != 0 || raffle.draw_after_timestamp != 0))
+        {
+            return Err(RaffleError::InvalidAntiSnipe.into());
+        }
+        raffle.snipe_window = snipe_window;
+        raffle.max_extensions = max_extensions;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Cap the tickets a single wallet can buy, before any ticket is sold
+     * @Context has creator and raffle account
+     * @param max_tickets_per_user: the most tickets one wallet can hold, 0 for no limit
+     */
+    pub fn set_max_tickets_per_user(
+        ctx: Context<UpdateRaffle>,
+        max_tickets_per_user: u64,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.count() != 0 {
+            return Err(RaffleError::TicketsSold.into());
+        }
+        raffle.max_tickets_per_user = max_tickets_per_user;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Set the raffle's title and details link before any ticket is sold
+     * @Context has creator and raffle account
+     * @param name: the raffle title shown by frontends, zero padded UTF-8
+     * @param uri: link to the raffle's off-chain details, zero padded UTF-8
+     */
+    pub fn set_metadata(
+        ctx: Context<UpdateRaffle>,
+        name: [u8; 32],
+        uri: [u8; 128],
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.count() != 0 {
+            return Err(RaffleError::TicketsSold.into());
+        }
+        raffle.name = name;
+        raffle.uri = uri;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Schedule when ticket sales open so the raffle can be announced ahead of time
+     * @Context has creator and raffle account
+     * @param start_timestamp: when ticket sales open, before the end timestamp
+     */
+    pub fn set_start_timestamp(ctx: Context<UpdateRaffle>, start_timestamp: i64) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.count() != 0 {
+            return Err(RaffleError::TicketsSold.into());
+        }
+        if start_timestamp >= raffle.end_timestamp {
+            return Err(RaffleError::EndTimeError.into());
+        }
+        raffle.start_timestamp = start_timestamp;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Set the fewest tickets a refundable raffle must sell to be drawn, before any ticket is sold
+     * raffles ending short of it are cancelled by reveal_winner so buyers can claim refunds
+     * @Context has creator and raffle account
+     * @param min_entrants: the minimum tickets sold, 0 turns the threshold off
+     */
+    pub fn set_min_entrants(ctx: Context<UpdateRaffle>, min_entrants: u64) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.count() != 0 {
+            return Err(RaffleError::TicketsSold.into());
+        }
+        if raffle.refundable != 1 {
+            return Err(RaffleError::NotRefundable.into());
+        }
+        if min_entrants > raffle.max_entrants() {
+            return Err(RaffleError::InvalidMinEntrants.into());
+        }
+        raffle.min_entrants = min_entrants;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Discount tickets for holders of a verified collection before any ticket is sold
+     * @Context has creator and raffle account
+     * @param holder_collection: the Metaplex collection mint, default turns the discount off
+     * @param holder_discount_bps: the holders' discount on the ticket price in basis points
+     */
+    pub fn set_holder_discount(
+        ctx: Context<UpdateRaffle>,
+        holder_collection: Pubkey,
+        holder_discount_bps: u64,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.count() != 0 || holder_discount_bps > MAX_FEE_BPS {
+            return Err(RaffleError::InvalidDiscounts.into());
+        }
+        raffle.holder_collection = holder_collection;
+        raffle.holder_discount_bps = holder_discount_bps;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Open the sale with a presale for holders before any ticket is sold
+     * until presale_end only holders of the holder discount collection or of the whitelist token can buy
+     * @Context has creator and raffle account
+     * @param presale_end: the time the public sale starts, 0 turns the presale off
+     * @param presale_mint: the whitelist token mint, default only admits collection holders
+     */
+    pub fn set_presale(
+        ctx: Context<UpdateRaffle>,
+        presale_end: i64,
+        presale_mint: Pubkey,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.count() != 0 {
+            return Err(RaffleError::TicketsSold.into());
+        }
+        if presale_end != 0
+            && (presale_end <= raffle.start_timestamp
+                || presale_end >= raffle.end_timestamp
+                || (presale_mint == Pubkey::default()
+                    && raffle.holder_collection == Pubkey::default()))
+        {
+            return Err(RaffleError::InvalidPresale.into());
+        }
+        raffle.presale_end = presale_end;
+        raffle.presale_mint = presale_mint;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Donate a share of the SOL proceeds to a charity before any ticket is sold
+     * @Context has creator and raffle account
+     * @param charity: the charity's wallet
+     * @param charity_bps: the charity's share of the SOL proceeds in basis points, 0 turns it off
+     */
+    pub fn set_charity(
+        ctx: Context<UpdateRaffle>,
+        charity: Pubkey,
+        charity_bps: u64,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        let payout_bps = raffle.payout_bps(charity_bps, raffle.royalty_bps);
+        if raffle.count() != 0 || payout_bps.map_or(true, |bps| bps > MAX_FEE_BPS) {
+            return Err(RaffleError::InvalidCharity.into());
+        }
+        raffle.charity = charity;
+        raffle.charity_bps = charity_bps;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Route a share of the SOL proceeds to the prize NFT's Metaplex creators before any ticket is sold
+     * @Context has creator and raffle account
+     * @param royalty_bps: the creators' share of the SOL proceeds in basis points, 0 turns it off
+     */
+    pub fn set_royalty_share(ctx: Context<UpdateRaffle>, royalty_bps: u64) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.prize_type != PRIZE_NFT {
+            return Err(RaffleError::InvalidRoyalty.into());
+        }
+        let payout_bps = raffle.payout_bps(raffle.charity_bps, royalty_bps);
+        if raffle.count() != 0 || payout_bps.map_or(true, |bps| bps > MAX_FEE_BPS) {
+            return Err(RaffleError::InvalidRoyalty.into());
+        }
+        raffle.royalty_bps = royalty_bps;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Price the tickets in USD, resolved from a Pyth feed at purchase, before any ticket is sold
+     * @Context has creator and raffle account
+     * @param usd_price_cents: ticket price in USD cents, 0 turns USD pricing off
+     * @param price_feed: the Pyth price account of the pegged asset in USD
+     * @param usd_peg_token: if 1: the payment token price is pegged, if 0: the SOL price
+     */
+    pub fn set_usd_price(
+        ctx: Context<UpdateRaffle>,
+        usd_price_cents: u64,
+        price_feed: Pubkey,
+        usd_peg_token: u64,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.count() != 0 {
+            return Err(RaffleError::InvalidPriceFeed.into());
+        }
+        raffle.usd_price_cents = usd_price_cents;
+        raffle.price_feed = price_feed;
+        raffle.usd_peg_token = usd_peg_token;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Buy tickets functions
+     * @Context has buyer and raffle's account.
+     * global_authority, treasury address, the buyer's TicketRecord PDA, the creator's CreatorStats PDA,
+     * the Leaderboard PDA and the raffle's proceeds vault
+     * and the payment token ATAs of buyer, creator and treasury
+     * and the proceeds vault's wSOL account for wSOL payments
+     * and the Pyth price feed as the first remaining account for USD priced raffles
+     * and the buyer's TicketReceipt PDA for refundable raffles after it
+     * and optionally the buyer's collection NFT token account and metadata for the holder discount
+     * or the buyer's whitelist token account during the presale,
+     * the buyer's stake account for the stake discount and the buyer's Referrer account
+     * as the last remaining accounts, followed by the entrant page the buyer's TicketRecord
+     * points to, or for new buyers the raffle's last page once its own entrant slots are full
+     * @param global_bump: global_authority's bump
+     * @param vault_bump: the raffle's proceeds vault bump
+     * @param amount: the amount of the tickets
+     * @param nonce: buyer supplied entropy mixed into the draw seed
+     * @param pay_with: 1: SOL only, 2: token only, 4: both prices together, 8: SOL price in wSOL
+     */
+    pub fn buy_tickets<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyTickets<'info>>,
+        _global_bump: u8,
+        _vault_bump: u8,
+        amount: u64,
+        nonce: [u8; 32],
+        pay_with: u64,
+    ) -> ProgramResult {
+        let (
+            holder_collection,
+            holder_discount_bps,
+            presale_end,
+            presale_mint,
+            usd_priced,
+            refundable,
+        ) = {
+            let raffle = ctx.accounts.raffle.load()?;
+            (
+                raffle.holder_collection,
+                raffle.holder_discount_bps,
+                raffle.presale_end,
+                raffle.presale_mint,
+                raffle.usd_price_cents > 0,
+                raffle.refundable == 1,
+            )
+        };
+        let (accounts, entrant_page) = split_entrant_page(ctx.remaining_accounts)?;
+        let (feeds, receipt, mut accounts) = split_receipt(accounts, usd_priced, refundable)?;
+        let mut discount_bps = 0;
+
+        // Referred buyers pass their referrer's account as the last remaining account
+        let mut referrer = None;
+        if let Some(referrer_account) = accounts.last() {
+            if *referrer_account.owner == crate::ID {
+                let referral = Account::<Referrer>::try_from(referrer_account)?;
+                if referral.wallet != ctx.accounts.buyer.key() {
+                    referrer = Some(referrer_account);
+                }
+                accounts = &accounts[..accounts.len() - 1];
+            }
+        }
+
+        // Stakers pass their stake account as the last remaining account
+        let staking_program = ctx.accounts.global_authority.load()?.staking_program;
+        if staking_program != Pubkey::default() {
+            if let Some(stake_account) = accounts.last() {
+                if *stake_account.owner == staking_program {
+                    let staked =
+                        staked_amount(stake_account, &staking_program, &ctx.accounts.buyer.key())?;
+                    discount_bps = ctx.accounts.global_authority.load()?.stake_discount(staked);
+                    accounts = &accounts[..accounts.len() - 1];
+                }
+            }
+        }
+
+        // Whitelist token holders pass their token account as the last remaining account
+        let mut presale_holder = false;
+        if presale_mint != Pubkey::default() {
+            if let Some(token_account) = accounts.last() {
+                if check_token_holder(token_account, &ctx.accounts.buyer.key(), &presale_mint)? {
+                    presale_holder = true;
+                    accounts = &accounts[..accounts.len() - 1];
+                }
+            }
+        }
+
+        // Holders of the raffle's collection pass their NFT as the last two remaining accounts
+        // the larger of the holder and stake discounts applies
+        let accounts_len = accounts.len();
+        if !presale_holder && holder_collection != Pubkey::default() && accounts_len >= 2 {
+            check_collection_holder(
+                &accounts[accounts_len - 2],
+                &accounts[accounts_len - 1],
+                &ctx.accounts.buyer.key(),
+                &holder_collection,
+            )?;
+            discount_bps = std::cmp::max(discount_bps, holder_discount_bps);
+            presale_holder = true;
+        }
+
+        if !presale_holder && Clock::get()?.unix_timestamp < presale_end {
+            return Err(RaffleError::PresaleHoldersOnly.into());
+        }
+
+        ctx.accounts.buy(
+            feeds,
+            amount,
+            &nonce,
+            pay_with,
+            discount_bps,
+            referrer,
+            receipt,
+            entrant_page,
+        )
+    }
+
+    /**
+     * @dev Buy tickets with a discount voucher signed off-chain by the raffle creator
+     * the voucher signs raffle, wallet, max_tickets and discount_bps and is redeemed once per wallet
+     * @Context has the buy_tickets accounts
+     * and the instructions sysvar and the voucher record PDA as the last two remaining accounts,
+     * the buy_tickets entrant page goes just before them,
+     * the transaction verifies the voucher with an ed25519 instruction just before this one
+     * @param global_bump: global_authority's bump
+     * @param vault_bump: the raffle's proceeds vault bump
+     * @param voucher_bump: the voucher record's bump
+     * @param amount: the amount of the tickets
+     * @param nonce: buyer supplied entropy mixed into the draw seed
+     * @param pay_with: 1: SOL only, 2: token only, 4: both prices together, 8: SOL price in wSOL
+     * @param max_tickets: the most tickets the voucher can buy
+     * @param discount_bps: the voucher's discount on the ticket price in basis points
+     */
+    pub fn buy_tickets_with_voucher<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyTickets<'info>>,
+        _global_bump: u8,
+        _vault_bump: u8,
+        voucher_bump: u8,
+        amount: u64,
+        nonce: [u8; 32],
+        pay_with: u64,
+        max_tickets: u64,
+        discount_bps: u64,
+    ) -> ProgramResult {
+        if amount > max_tickets || discount_bps > MAX_FEE_BPS {
+            return Err(RaffleError::InvalidVoucher.into());
+        }
+        let accounts_len = ctx.remaining_accounts.len();
+        if accounts_len < 2 {
+            return Err(RaffleError::InvalidVoucher.into());
+        }
+        let instructions = &ctx.remaining_accounts[accounts_len - 2];
+        let voucher_record = &ctx.remaining_accounts[accounts_len - 1];
+
+        let raffle_key = ctx.accounts.raffle.key();
+        let buyer_key = ctx.accounts.buyer.key();
+        let creator = ctx.accounts.raffle.load()?.creator;
+        let mut message = Vec::with_capacity(80);
+        message.extend_from_slice(raffle_key.as_ref());
+        message.extend_from_slice(buyer_key.as_ref());
+        message.extend_from_slice(&max_tickets.to_le_bytes());
+        message.extend_from_slice(&discount_bps.to_le_bytes());
+        verify_ed25519_signature(instructions, &creator, &message)?;
+
+        // Creating the voucher record fails once it exists, so each voucher is redeemed once
+        let seeds = &[
+            VOUCHER_SEED.as_bytes(),
+            raffle_key.as_ref(),
+            buyer_key.as_ref(),
+            &[voucher_bump],
+        ];
+        create_marker_account(
+            ctx.accounts.buyer.to_account_info(),
+            voucher_record.clone(),
+            ctx.accounts.system_program.to_account_info(),
+            &[&seeds[..]],
+        )?;
+
+        let (accounts, entrant_page) =
+            split_entrant_page(&ctx.remaining_accounts[..accounts_len - 2])?;
+        let (feeds, receipt, _) = {
+            let raffle = ctx.accounts.raffle.load()?;
+            split_receipt(accounts, raffle.usd_price_cents > 0, raffle.refundable == 1)?
+        };
+        ctx.accounts.buy(
+            feeds,
+            amount,
+            &nonce,
+            pay_with,
+            discount_bps,
+            None,
+            receipt,
+            entrant_page,
+        )
+    }
+
+    /**
+     * @dev Register the signer as a referrer with a Referrer PDA seeded by their wallet
+     * @Context has wallet and the Referrer PDA to create
+     * @param referrer_bump: Referrer's bump
+     */
+    pub fn register_referrer(ctx: Context<RegisterReferrer>, _referrer_bump: u8) -> ProgramResult {
+        let referrer = &mut ctx.accounts.referrer;
+        referrer.wallet = ctx.accounts.wallet.key();
+        referrer.registered_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /**
+     * @dev Withdraw the referral shares accrued in the referrer's account
+     * @Context has wallet and its Referrer PDA
+     * @param referrer_bump: Referrer's bump
+     */
+    pub fn withdraw_referral(ctx: Context<WithdrawReferral>, _referrer_bump: u8) -> ProgramResult {
+        let referrer = ctx.accounts.referrer.to_account_info();
+        let rent = Rent::get()?.minimum_balance(referrer.data_len());
+        let amount = referrer.lamports().saturating_sub(rent);
+        if amount == 0 {
+            return Err(RaffleError::NothingToWithdraw.into());
+        }
+        sol_transfer_from_program_account(&referrer, &ctx.accounts.wallet.to_account_info(), amount)
+    }
+
+    /**
+     * @dev Enter a zero-price raffle with a single ticket
+     * the FreeEntry PDA seeded by the raffle and entrant keeps each wallet to one entry
+     * @Context has entrant, global_authority, raffle account and the FreeEntry PDA to create
+     * @param global_bump: global_authority's bump
+     * @param entry_bump: FreeEntry's bump
+     * @param nonce: entrant supplied entropy mixed into the draw seed
+     */
+    pub fn enter_free_raffle(
+        ctx: Context<EnterFreeRaffle>,
+        _global_bump: u8,
+        _entry_bump: u8,
+        nonce: [u8; 32],
+    ) -> ProgramResult {
+        let clock = Clock::get()?;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if !raffle.free_entry() {
+            return Err(RaffleError::NotFreeRaffle.into());
+        }
+        if clock.unix_timestamp < raffle.start_timestamp {
+            return Err(RaffleError::SalesNotStarted.into());
+        }
+        if raffle.paused == 1 {
+            return Err(RaffleError::RafflePaused.into());
+        }
+        ctx.accounts.global_authority.load()?.check_not_paused()?;
+        if clock.unix_timestamp > raffle.sales_end() || !raffle.selling()? {
+            return Err(RaffleError::RaffleEnded.into());
+        }
+        if raffle.count() + 1 > raffle.max_entrants() {
+            return Err(RaffleError::NotEnoughTicketsLeft.into());
+        }
+        if raffle.count() == 0 {
+            raffle.no_repeat = 1;
+        }
+
+        raffle.add_entrant(ctx.accounts.entrant.key(), 1)?;
+        raffle.unique_entrants += 1;
+        raffle.add_entropy(ctx.accounts.entrant.key(), &nonce, clock.slot);
+
+        let free_entry = &mut ctx.accounts.free_entry;
+        free_entry.raffle = ctx.accounts.raffle.key();
+        free_entry.entrant = ctx.accounts.entrant.key();
+        free_entry.entered_at = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Add the next entrant page of a raffle whose own entrant slots are full
+     * new wallets buying tickets go to the raffle's last entrant page
+     * @Context has payer, raffle account and the EntrantPage PDA to create
+     * and the raffle's last entrant page as the remaining account once it has pages
+     * @param page_bump: EntrantPage's bump
+     * @param page_index: the new page's index, the raffle's page count
+     */
+    pub fn add_entrant_page(
+        ctx: Context<AddEntrantPage>,
+        _page_bump: u8,
+        page_index: u64,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if !raffle.selling()? {
+            return Err(RaffleError::RaffleEnded.into());
+        }
+        if page_index != raffle.page_count || (raffle.entrant_count as usize) < MAX_ENTRANTS {
+            return Err(RaffleError::InvalidEntrantPage.into());
+        }
+        // Pages are only added once the last page is full
+        if page_index > 0 {
+            let raffle_key = ctx.accounts.raffle.key();
+            let last_page =
+                load_entrant_page(ctx.remaining_accounts.get(0), &raffle_key, page_index - 1)?;
+            if (last_page.load()?.entrant_count as usize) < MAX_PAGE_ENTRANTS {
+                return Err(RaffleError::InvalidEntrantPage.into());
+            }
+        }
+
+        let mut page = ctx.accounts.entrant_page.load_init()?;
+        page.raffle = ctx.accounts.raffle.key();
+        page.page_index = page_index;
+        raffle.page_count += 1;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Reaveal winner function
+     * @Context has buyer and raffle account address and the slot hashes sysvar
+     * and the ORAO randomness request account as the first remaining account for ORAO raffles
+     * and the raffle's entrant pages in page order as the last remaining accounts
+     * @param secret: the preimage of the raffle's commitment, ignored for other randomness modes
+     */
+    pub fn reveal_winner<'info>(
+        ctx: Context<'_, '_, '_, 'info, RevealWinner<'info>>,
+        secret: [u8; 32],
+    ) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if !raffle.ended(timestamp) {
+            return Err(RaffleError::RaffleNotEnded.into());
+        }
+        if raffle.revealed == 1 || raffle.revealed_count != 0 {
+            return Err(RaffleError::WinnersAlreadyDrawn.into());
+        }
+        // Raffles short of their minimum entrants are cancelled instead of drawn
+        if raffle.count() < raffle.min_entrants {
+            let end_timestamp = raffle.end_timestamp;
+            raffle.cancel(end_timestamp)?;
+            return Ok(());
+        }
+        if raffle.prize_hidden() {
+            return Err(RaffleError::PrizeNotRevealed.into());
+        }
+
+        let seed = ctx
+            .accounts
+            .draw_seed(&raffle, timestamp, secret, ctx.remaining_accounts)?;
+        let raffle_key = ctx.accounts.raffle.key();
+        let pages = entrant_pages(&raffle_key, raffle.page_count, ctx.remaining_accounts)?;
+        let mut pages = pages
+            .iter()
+            .map(|page| page.load_mut())
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        raffle.draw_winners(seed, &mut pages)?;
+        raffle.finish_draw(timestamp)?;
+        pay_crank_tip(
+            &mut raffle,
+            &ctx.accounts.raffle.to_account_info(),
+            &ctx.accounts.buyer.to_account_info(),
+        )?;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Reveal a bounded chunk of winners so large draws fit in the compute budget
+     * @Context has buyer and raffle account address and the slot hashes sysvar
+     * and the ORAO randomness request account as the first remaining account for ORAO raffles
+     * and the raffle's entrant pages in page order as the last remaining accounts
+     * @param start: index of the first winner in this chunk, must follow the previous chunk
+     * @param count: how many winners to reveal in this chunk
+     * @param secret: the preimage of the raffle's commitment, ignored for other randomness modes
+     */
+    pub fn reveal_winners_chunk<'info>(
+        ctx: Context<'_, '_, '_, 'info, RevealWinner<'info>>,
+        start: u64,
+        count: u64,
+        secret: [u8; 32],
+    ) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if !raffle.ended(timestamp) {
+            return Err(RaffleError::RaffleNotEnded.into());
+        }
+        if raffle.revealed == 1 {
+            return Err(RaffleError::WinnersAlreadyDrawn.into());
+        }
+        if raffle.revealed_count == 0 && raffle.count() < raffle.min_entrants {
+            let end_timestamp = raffle.end_timestamp;
+            raffle.cancel(end_timestamp)?;
+            return Ok(());
+        }
+        if raffle.prize_hidden() {
+            return Err(RaffleError::PrizeNotRevealed.into());
+        }
+        if count == 0 || count > MAX_REVEAL_CHUNK || start != raffle.revealed_count {
+            return Err(RaffleError::InvalidRevealChunk.into());
+        }
+
+        if start == 0 {
+            let seed =
+                ctx.accounts
+                    .draw_seed(&raffle, timestamp, secret, ctx.remaining_accounts)?;
+            raffle.start_draw(seed)?;
+        }
+        let raffle_key = ctx.accounts.raffle.key();
+        let pages = entrant_pages(&raffle_key, raffle.page_count, ctx.remaining_accounts)?;
+        let mut pages = pages
+            .iter()
+            .map(|page| page.load_mut())
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        raffle.draw_next_winners(count, &mut pages)?;
+        if raffle.revealed == 1 {
+            raffle.finish_draw(timestamp)?;
+        } else {
+            raffle.set_status(RaffleStatus::Ended)?;
+        }
+        pay_crank_tip(
+            &mut raffle,
+            &ctx.accounts.raffle.to_account_info(),
+            &ctx.accounts.buyer.to_account_info(),
+        )?;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Close the ticket sales of an ended raffle, recording the slot they closed in
+     * commit-reveal raffles mix that slot's hash into the draw seed and are revealed
+     * within the 512 slots the slot hashes sysvar keeps
+     * @Context has payer and raffle account address
+     */
+    pub fn close_sales(ctx: Context<CloseSales>) -> ProgramResult {
+        let clock = Clock::get()?;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if !raffle.ended(clock.unix_timestamp) {
+            return Err(RaffleError::RaffleNotEnded.into());
+        }
+        if raffle.cancelled == 1 {
+            return Err(RaffleError::RaffleCancelled.into());
+        }
+        if raffle.revealed != 0 || raffle.revealed_count != 0 {
+            return Err(RaffleError::WinnersAlreadyDrawn.into());
+        }
+        if raffle.end_slot != 0 {
+            return Err(RaffleError::SalesAlreadyClosed.into());
+        }
+        raffle.end_slot = clock.slot;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Request draw function, asks ORAO VRF for randomness seeded by the raffle address
+     * @Context has payer and raffle account address
+     * and ORAO network state, treasury, randomness request account and program
+     */
+    pub fn request_draw(ctx: Context<RequestDraw>) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if !raffle.ended(timestamp) {
+            return Err(RaffleError::RaffleNotEnded.into());
+        }
+        if RandomnessMode::from_u64(raffle.randomness_mode)? != RandomnessMode::Orao {
+            return Err(RaffleError::InvalidRandomnessMode.into());
+        }
+        if raffle.randomness != Pubkey::default() {
+            return Err(RaffleError::RandomnessAlreadyRequested.into());
+        }
+        let orao_program_id = ORAO_VRF_PROGRAM_ID.parse::<Pubkey>().unwrap();
+        if ctx.accounts.orao_program.key() != orao_program_id {
+            return Err(RaffleError::InvalidVrfProgram.into());
+        }
+
+        let (network_state_key, _) =
+            Pubkey::find_program_address(&[ORAO_NETWORK_STATE_SEED.as_bytes()], &orao_program_id);
+        if ctx.accounts.network_state.key() != network_state_key {
+            return Err(RaffleError::InvalidRandomnessAccount.into());
+        }
+
+        let seed = ctx.accounts.raffle.key().to_bytes();
+        let (request_key, _) = Pubkey::find_program_address(
+            &[ORAO_RANDOMNESS_SEED.as_bytes(), &seed],
+            &orao_program_id,
+        );
+        if ctx.accounts.randomness.key() != request_key {
+            return Err(RaffleError::InvalidRandomnessAccount.into());
+        }
+
+        orao_request(
+            ctx.accounts.orao_program.to_account_info(),
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.network_state.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            ctx.accounts.randomness.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            seed,
+        )?;
+        raffle.randomness = request_key;
+        raffle.set_status(RaffleStatus::Ended)?;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Fulfill draw function, selects the winners from the fulfilled ORAO randomness
+     * @Context has payer and raffle account address and ORAO randomness request account
+     * and the raffle's entrant pages in page order as the remaining accounts
+     */
+    pub fn fulfill_draw(ctx: Context<FulfillDraw>) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if RandomnessMode::from_u64(raffle.randomness_mode)? != RandomnessMode::Orao {
+            return Err(RaffleError::InvalidRandomnessMode.into());
+        }
+        if raffle.revealed == 1 || raffle.revealed_count != 0 {
+            return Err(RaffleError::WinnersAlreadyDrawn.into());
+        }
+        if raffle.prize_hidden() {
+            return Err(RaffleError::PrizeNotRevealed.into());
+        }
+
+        let randomness = orao_randomness(&ctx.accounts.randomness, &raffle.randomness)?;
+        let raffle_key = ctx.accounts.raffle.key();
+        let pages = entrant_pages(&raffle_key, raffle.page_count, ctx.remaining_accounts)?;
+        let mut pages = pages
+            .iter()
+            .map(|page| page.load_mut())
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        raffle.draw_winners(keccak::hash(&randomness).to_bytes(), &mut pages)?;
+        raffle.finish_draw(timestamp)?;
+        pay_crank_tip(
+            &mut raffle,
+            &ctx.accounts.raffle.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+        )?;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Force draw function, the super admin's escape hatch when the randomness provider fails
+     * @Context has admin and global_authority and raffle account address
+     * @param global_bump: global_authority's bump
+     * @param winners: the winner list, each must be an entrant of the raffle
+     * @param reason: why the draw was forced, recorded in the emitted event
+     */
+    pub fn force_draw(
+        ctx: Context<ForceDraw>,
+        _global_bump: u8,
+        winners: Vec<Pubkey>,
+        reason: String,
+    ) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if ctx.accounts.global_authority.load()?.super_admin != ctx.accounts.admin.key() {
+            return Err(RaffleError::NotSuperAdmin.into());
+        }
+        if timestamp < raffle.end_timestamp + FORCE_DRAW_DELAY {
+            return Err(RaffleError::ForceDrawLocked.into());
+        }
+        if raffle.revealed == 1 || raffle.revealed_count != 0 {
+            return Err(RaffleError::WinnersAlreadyDrawn.into());
+        }
+        if raffle.prize_hidden() {
+            return Err(RaffleError::PrizeNotRevealed.into());
+        }
+
+        raffle.force_winners(&winners)?;
+        raffle.finish_draw(timestamp)?;
+
+        emit!(ForceDrawEvent {
+            raffle: ctx.accounts.raffle.key(),
+            admin: ctx.accounts.admin.key(),
+            winners,
+            reason,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    /**
+     * @dev Claim reward function
+     * @Context has claimer and global_authority account
+     * raffle account, the nft ATA of claimer and global_authority, the claimer's UserProfile PDA
+     * and the creator's CreatorStats PDA.
+     * pNFT raffles pass the pNFT transfer accounts as remaining accounts,
+     * Token-2022 prizes pass their transfer hook accounts instead
+     * @param global_bump: the global_authority's bump
+     * @param winner_index: the claimer's winner slot to claim, bundles are claimed through slot 0
+     */
+    pub fn claim_reward<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimReward<'info>>,
+        global_bump: u8,
+        winner_index: u64,
+    ) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if !raffle.ended(timestamp) {
+            return Err(RaffleError::RaffleNotEnded.into());
+        }
+        if raffle.revealed != 1 {
+            return Err(RaffleError::WinnerNotDrawn.into());
+        }
+        if raffle.prize_type == PRIZE_EDITION {
+            return Err(RaffleError::NoPrize.into());
+        }
+        ctx.accounts.check_token_accounts()?;
+        let unclaimed = raffle.unclaimed_winners();
+        if raffle.prize_type == PRIZE_TOKEN {
+            if ctx.accounts.nft_mint_address.key() != raffle.nft_mint {
+                return Err(RaffleError::NoPrize.into());
+            }
+            let winner_index = raffle.claimable_slot(ctx.accounts.claimer.key(), winner_index)?;
+
+            // Transfer the winner's share of the prize tokens
+            ctx.accounts.transfer_prize(
+                0,
+                raffle.prize_amount / raffle.winner_count(),
+                ctx.remaining_accounts,
+                global_bump,
+            )?;
+            raffle.set_claimed(winner_index)?;
+        } else if raffle.prize_type == PRIZE_BUNDLE {
+            if winner_index != 0
+                || raffle.winner_count() == 0
+                || raffle.winner[0] != ctx.accounts.claimer.key()
+            {
+                return Err(RaffleError::NotWinner.into());
+            }
+            let prize_index = raffle
+                .prize_index(ctx.accounts.nft_mint_address.key())
+                .ok_or(RaffleError::NoPrize)?;
+            if raffle.bundle_claimed(prize_index) {
+                return Err(RaffleError::NoPrize.into());
+            }
+
+            // Transfer one NFT of the bundle, the winner slot is claimed with the last one
+            ctx.accounts
+                .transfer_prize(0, 1, ctx.remaining_accounts, global_bump)?;
+            raffle.bundle_claimed |= 1 << prize_index;
+            if (0..raffle.prize_count as usize).all(|i| raffle.bundle_claimed(i)) {
+                raffle.set_claimed(0)?;
+            }
+        } else if raffle.tier_count > 0 {
+            let winner_index = raffle.claimable_slot(ctx.accounts.claimer.key(), winner_index)?;
+            let tier = raffle
+                .tier_index(winner_index)
+                .ok_or(RaffleError::NoPrize)?;
+
+            // Transfer the prize of the winner's tier, whitelist spots have nothing to transfer
+            let prize_mint = ctx.accounts.nft_mint_address.key();
+            if raffle.tier_types[tier] == PRIZE_NFT {
+                if prize_mint != raffle.prize_mints[winner_index] {
+                    return Err(RaffleError::NoPrize.into());
+                }
+                ctx.accounts.transfer_prize(
+                    raffle.programmable,
+                    1,
+                    ctx.remaining_accounts,
+                    global_bump,
+                )?;
+            } else if raffle.tier_types[tier] == PRIZE_TOKEN {
+                if prize_mint != raffle.tier_mint {
+                    return Err(RaffleError::NoPrize.into());
+                }
+                let amount = raffle.tier_amounts[tier];
+                ctx.accounts
+                    .transfer_prize(0, amount, ctx.remaining_accounts, global_bump)?;
+                raffle.tier_escrowed -= amount;
+            }
+            raffle.set_claimed(winner_index)?;
+        } else if raffle.whitelisted == 1 {
+            // each winner slot has its own prize NFT
+            let winner_index = raffle.claimable_slot(ctx.accounts.claimer.key(), winner_index)?;
+            if raffle.prize_mints[winner_index] != ctx.accounts.nft_mint_address.key() {
+                return Err(RaffleError::NoPrize.into());
+            }
+            // Transfer NFT to the winner's wallet
+            ctx.accounts.transfer_prize(
+                raffle.programmable,
+                1,
+                ctx.remaining_accounts,
+                global_bump,
+            )?;
+            raffle.set_claimed(winner_index)?;
+        } else {
+            let winner_index = raffle.claimable_slot(ctx.accounts.claimer.key(), winner_index)?;
+            raffle.set_claimed(winner_index)?;
+
+            // Transfer a whitelist token for the claimed winner slot
+            if raffle.wl_mint != Pubkey::default() {
+                if ctx.accounts.nft_mint_address.key() != raffle.wl_mint {
+                    return Err(RaffleError::NoPrize.into());
+                }
+                ctx.accounts
+                    .transfer_prize(0, 1, ctx.remaining_accounts, global_bump)?;
+                raffle.wl_escrowed -= 1;
+            }
+        }
+        raffle.mark_claimed();
+
+        // The claimer's UserProfile counts every winner slot they claimed
+        let wins = unclaimed - raffle.unclaimed_winners();
+        if wins > 0 {
+            let mut profile = load_user_profile(
+                &ctx.accounts.claimer.to_account_info(),
+                &ctx.accounts.claimer_profile,
+                &ctx.accounts.system_program.to_account_info(),
+            )?;
+            profile.wins += wins;
+            save_user_profile(&ctx.accounts.claimer_profile, &profile)?;
+
+            record_prizes_delivered(
+                &raffle.creator,
+                &ctx.accounts.claimer.to_account_info(),
+                &ctx.accounts.creator_stats,
+                &ctx.accounts.system_program.to_account_info(),
+                wins,
+            )?;
+        }
+
+        Ok(())
+    }
+    /**
+     * @dev Reclaim the prize of a winner who did not claim it before the claim deadline
+     * a bundle goes back one mint at a time, an unprinted edition is dropped so the master
+     * edition can be withdrawn, SOL, cNFT, Core and delegated prizes are reclaimed through
+     * withdraw_sol_prize, withdraw_cnft, withdraw_core and release_delegated_prize
+     * @Context has the creator as claimer, global_authority account, raffle account
+     * and creator's and global_authority's prize token accounts
+     * pNFT prizes pass the pNFT transfer accounts as remaining accounts
+     * @param global_bump: global_authority's bump
+     * @param winner_index: the unclaimed winner slot
+     */
+    pub fn reclaim_unclaimed<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimReward<'info>>,
+        global_bump: u8,
+        winner_index: u64,
+    ) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.claimer.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.revealed != 1 {
+            return Err(RaffleError::WinnerNotDrawn.into());
+        }
+        if !raffle.claim_window_closed(timestamp) {
+            return Err(RaffleError::ClaimWindowOpen.into());
+        }
+        let winner_index = winner_index as usize;
+        if winner_index >= raffle.winner_count() as usize || raffle.claimed(winner_index) {
+            return Err(RaffleError::NoPrize.into());
+        }
+        ctx.accounts.check_token_accounts()?;
+
+        // The prize owed to the winner slot, whitelist spots have nothing to transfer
+        let prize_mint = ctx.accounts.nft_mint_address.key();
+        let mut programmable = 0;
+        let amount;
+        if raffle.prize_type == PRIZE_TOKEN {
+            if prize_mint != raffle.nft_mint {
+                return Err(RaffleError::NoPrize.into());
+            }
+            amount = raffle.prize_amount / raffle.winner_count();
+        } else if raffle.prize_type == PRIZE_BUNDLE {
+            let prize_index = raffle.prize_index(prize_mint).ok_or(RaffleError::NoPrize)?;
+            if raffle.bundle_claimed(prize_index) {
+                return Err(RaffleError::NoPrize.into());
+            }
+            raffle.bundle_claimed |= 1 << prize_index;
+            amount = 1;
+        } else if raffle.prize_type == PRIZE_EDITION {
+            amount = 0;
+        } else if raffle.tier_count > 0 {
+            let tier = raffle
+                .tier_index(winner_index)
+                .ok_or(RaffleError::NoPrize)?;
+            if raffle.tier_types[tier] == PRIZE_NFT {
+                if prize_mint != raffle.prize_mints[winner_index] {
+                    return Err(RaffleError::NoPrize.into());
+                }
+                programmable = raffle.programmable;
+                amount = 1;
+            } else if raffle.tier_types[tier] == PRIZE_TOKEN {
+                if prize_mint != raffle.tier_mint {
+                    return Err(RaffleError::NoPrize.into());
+                }
+                amount = raffle.tier_amounts[tier];
+                raffle.tier_escrowed -= amount;
+            } else {
+                amount = 0;
+            }
+        } else if raffle.prize_type == PRIZE_NFT && raffle.whitelisted == 1 {
+            if prize_mint != raffle.prize_mints[winner_index] {
+                return Err(RaffleError::NoPrize.into());
+            }
+            programmable = raffle.programmable;
+            amount = 1;
+        } else if raffle.prize_type == PRIZE_NFT && raffle.wl_mint != Pubkey::default() {
+            if prize_mint != raffle.wl_mint {
+                return Err(RaffleError::NoPrize.into());
+            }
+            amount = 1;
+            raffle.wl_escrowed -= 1;
+        } else {
+            return Err(RaffleError::NoPrize.into());
+        }
+
+        if amount > 0 {
+            ctx.accounts.transfer_prize(
+                programmable,
+                amount,
+                ctx.remaining_accounts,
+                global_bump,
+            )?;
+        }
+        // the bundle's winner slot is reclaimed with its last mint
+        if raffle.prize_type != PRIZE_BUNDLE
+            || (0..raffle.prize_count as usize).all(|i| raffle.bundle_claimed(i))
+        {
+            raffle.set_claimed(winner_index)?;
+        }
+        raffle.mark_claimed();
+
+        Ok(())
+    }
+    /**
+     * @dev Redraw a winner who did not claim before the claim deadline from the remaining entrants
+     * the new winner gets a fresh claim window
+     * @Context has creator and raffle account
+     * and the raffle's entrant pages in page order as the remaining accounts
+     * @param winner_index: the unclaimed winner slot
+     */
+    pub fn reroll_winner(ctx: Context<UpdateRaffle>, winner_index: u64) -> ProgramResult {
+        let clock = Clock::get()?;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.status()? != RaffleStatus::Revealed {
+            return Err(RaffleError::WinnerNotDrawn.into());
+        }
+        if !raffle.claim_window_closed(clock.unix_timestamp) {
+            return Err(RaffleError::ClaimWindowOpen.into());
+        }
+        let winner_index = winner_index as usize;
+        if winner_index >= raffle.winner_count() as usize || raffle.claimed(winner_index) {
+            return Err(RaffleError::NoPrize.into());
+        }
+
+        let raffle_key = ctx.accounts.raffle.key();
+        let pages = entrant_pages(&raffle_key, raffle.page_count, ctx.remaining_accounts)?;
+        let mut pages = pages
+            .iter()
+            .map(|page| page.load_mut())
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        raffle.reroll(winner_index, clock.slot, &mut pages)?;
+        raffle.claim_deadline = clock.unix_timestamp + CLAIM_WINDOW;
+
+        Ok(())
+    }
+    /**
+     * @dev Copy the revealed winners into a WinnerSnapshot PDA seeded by the raffle,
+     * sized to the winner count, so airdrop and mint-gating tools can read them
+     * after the raffle account is closed, anyone can take it once
+     * @Context has payer, raffle account and the WinnerSnapshot PDA to create
+     */
+    pub fn snapshot_winners(ctx: Context<SnapshotWinners>) -> ProgramResult {
+        let raffle = ctx.accounts.raffle.load()?;
+        if raffle.revealed != 1 {
+            return Err(RaffleError::WinnerNotDrawn.into());
+        }
+        let snapshot = &ctx.accounts.winner_snapshot;
+        let raffle_key = ctx.accounts.raffle.key();
+        let (address, bump) = Pubkey::find_program_address(
+            &[WINNER_SNAPSHOT_SEED.as_ref(), raffle_key.as_ref()],
+            &crate::ID,
+        );
+        if snapshot.key() != address {
+            return Err(RaffleError::InvalidWinnerSnapshot.into());
+        }
+        if !snapshot.data_is_empty() {
+            return Err(RaffleError::WinnerSnapshotTaken.into());
+        }
+
+        let winner_snapshot = WinnerSnapshot {
+            raffle: raffle_key,
+            creator: raffle.creator,
+            taken_at: Clock::get()?.unix_timestamp,
+            winners: raffle.winner[..raffle.winner_count() as usize].to_vec(),
+        };
+        let seeds = &[
+            WINNER_SNAPSHOT_SEED.as_bytes(),
+            raffle_key.as_ref(),
+            &[bump],
+        ];
+        create_program_account(
+            ctx.accounts.payer.to_account_info(),
+            snapshot.clone(),
+            ctx.accounts.system_program.to_account_info(),
+            8 + 32 + 32 + 8 + 4 + 32 * winner_snapshot.winners.len(),
+            &[&seeds[..]],
+        )?;
+        let mut data = snapshot.try_borrow_mut_data()?;
+        let mut writer: &mut [u8] = &mut data;
+        winner_snapshot.try_serialize(&mut writer)
+    }
+
+    /**
+     * @dev Claim a whitelist spot as a WhitelistProof PDA seeded by the raffle and winner
+     * so mint programs can verify the win on-chain
+     * @Context has claimer, raffle account, the WhitelistProof PDA to create and the creator's CreatorStats PDA
+     * @param proof_bump: WhitelistProof's bump
+     */
+    pub fn claim_whitelist_proof(
+        ctx: Context<ClaimWhitelistProof>,
+        _proof_bump: u8,
+    ) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if !raffle.ended(timestamp) {
+            return Err(RaffleError::RaffleNotEnded.into());
+        }
+        if raffle.revealed != 1 {
+            return Err(RaffleError::WinnerNotDrawn.into());
+        }
+        if raffle.prize_type != PRIZE_NFT
+            || raffle.whitelisted == 1
+            || raffle.wl_mint != Pubkey::default()
+        {
+            return Err(RaffleError::NoPrize.into());
+        }
+        raffle
+            .unclaimed_winner_index(ctx.accounts.claimer.key())
+            .ok_or(RaffleError::NotWinner)?;
+
+        let slots: Vec<usize> = raffle.winner_slots(ctx.accounts.claimer.key()).collect();
+        let wins = slots.len() as u64;
+        for i in slots {
+            raffle.set_claimed(i)?;
+        }
+
+        let whitelist_proof = &mut ctx.accounts.whitelist_proof;
+        whitelist_proof.raffle = ctx.accounts.raffle.key();
+        whitelist_proof.winner = ctx.accounts.claimer.key();
+        whitelist_proof.claimed_at = timestamp;
+
+        record_prizes_delivered(
+            &raffle.creator,
+            &ctx.accounts.claimer.to_account_info(),
+            &ctx.accounts.creator_stats,
+            &ctx.accounts.system_program.to_account_info(),
+            wins,
+        )?;
+
+        raffle.mark_claimed();
+
+        Ok(())
+    }
+
+    /**
+     * @dev Print the winner's numbered edition of the escrowed master edition
+     * @Context has claimer and global_authority account, raffle account,
+     * the new edition's metadata, edition, mint and edition marker accounts,
+     * the master edition, its metadata, global_authority's master edition ATA and the creator's CreatorStats PDA
+     * @param global_bump: global_authority's bump
+     */
+    pub fn claim_edition(ctx: Context<ClaimEdition>, global_bump: u8) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if !raffle.ended(timestamp) {
+            return Err(RaffleError::RaffleNotEnded.into());
+        }
+        if raffle.revealed != 1 {
+            return Err(RaffleError::WinnerNotDrawn.into());
+        }
+        if raffle.prize_type != PRIZE_EDITION {
+            return Err(RaffleError::NoPrize.into());
+        }
+        check_token_account(
+            &ctx.accounts.master_token_account,
+            &ctx.accounts.token_program,
+            &raffle.nft_mint,
+            &ctx.accounts.global_authority.key(),
+        )?;
+        let winner_index = raffle
+            .unclaimed_winner_index(ctx.accounts.claimer.key())
+            .ok_or(RaffleError::NotWinner)?;
+
+        // every winner slot prints its own edition number after the master's prior supply
+        let seeds = &[GLOBAL_AUTHORITY_SEED.as_bytes(), &[global_bump]];
+        EditionPrint {
+            new_metadata: ctx.accounts.new_metadata.to_account_info(),
+            new_edition: ctx.accounts.new_edition.to_account_info(),
+            master_edition: ctx.accounts.master_edition.to_account_info(),
+            new_mint: ctx.accounts.new_mint.to_account_info(),
+            edition_mark: ctx.accounts.edition_mark.to_account_info(),
+            new_mint_authority: ctx.accounts.claimer.to_account_info(),
+            payer: ctx.accounts.claimer.to_account_info(),
+            token_account_owner: ctx.accounts.global_authority.to_account_info(),
+            token_account: ctx.accounts.master_token_account.to_account_info(),
+            update_authority: ctx.accounts.update_authority.to_account_info(),
+            metadata: ctx.accounts.master_metadata.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        }
+        .invoke_signed(
+            &ctx.accounts.token_metadata_program,
+            raffle.edition_base + winner_index as u64 + 1,
+            &[&seeds[..]],
+        )?;
+        raffle.set_claimed(winner_index)?;
+
+        record_prizes_delivered(
+            &raffle.creator,
+            &ctx.accounts.claimer.to_account_info(),
+            &ctx.accounts.creator_stats,
+            &ctx.accounts.system_program.to_account_info(),
+            1,
+        )?;
+
+        raffle.mark_claimed();
+
+        Ok(())
+    }
+
+    /**
+     * @dev Push back the end of a running raffle, up to the max raffle duration from its creation
+     * @Context has creator and raffle account
+     * @param end_timestamp: the new end timestamp, later than the current one
+     */
+    pub fn extend_raffle(ctx: Context<UpdateRaffle>, end_timestamp: i64) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if timestamp > raffle.sales_end() || !raffle.selling()? {
+            return Err(RaffleError::RaffleEnded.into());
+        }
+        if end_timestamp <= raffle.end_timestamp
+            || end_timestamp - raffle.created_at > MAX_RAFFLE_DURATION
+            || (raffle.draw_after_timestamp > 0 && end_timestamp > raffle.draw_after_timestamp)
+        {
+            return Err(RaffleError::EndTimeError.into());
+        }
+        raffle.end_timestamp = end_timestamp;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Cancel a raffle before any ticket is sold
+     * ends the raffle now without winners so the creator can withdraw the prizes right away
+     * @Context has creator, raffle account and the creator's CreatorStats PDA
+     */
+    pub fn cancel_raffle(ctx: Context<CancelUnsoldRaffle>) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.count() != 0 {
+            return Err(RaffleError::TicketsSold.into());
+        }
+        if raffle.revealed != 0 || raffle.revealed_count != 0 {
+            return Err(RaffleError::WinnersAlreadyDrawn.into());
+        }
+        raffle.cancel(timestamp)?;
+
+        // The creator's CreatorStats counts the raffles they cancelled
+        let mut stats = load_creator_stats(
+            &raffle.creator,
+            &ctx.accounts.creator.to_account_info(),
+            &ctx.accounts.creator_stats,
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        stats.cancellations += 1;
+        save_creator_stats(&ctx.accounts.creator_stats, &stats)?;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Cancel a refundable raffle after tickets are sold, by the creator or the admin
+     * ends the raffle now without winners so the creator can withdraw the prizes
+     * and each buyer can claim back their payments with claim_ticket_refund
+     * @Context has creator or admin, global_authority, raffle account and the creator's CreatorStats PDA
+     * @param global_bump: global_authority's bump
+     */
+    pub fn cancel_raffle_with_refunds(
+        ctx: Context<CancelRefundableRaffle>,
+        _global_bump: u8,
+    ) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        let authority = ctx.accounts.authority.key();
+        if raffle.creator != authority
+            && ctx.accounts.global_authority.load()?.super_admin != authority
+        {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.refundable != 1 {
+            return Err(RaffleError::NotRefundable.into());
+        }
+        if raffle.revealed != 0 || raffle.revealed_count != 0 {
+            return Err(RaffleError::WinnersAlreadyDrawn.into());
+        }
+        raffle.cancel(timestamp)?;
+
+        // The creator's CreatorStats counts the raffles cancelled by them or the admin
+        let mut stats = load_creator_stats(
+            &raffle.creator,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.creator_stats,
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        stats.cancellations += 1;
+        save_creator_stats(&ctx.accounts.creator_stats, &stats)?;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Pause ticket sales on a raffle, by the creator or the admin
+     * @Context has creator or admin, global_authority and raffle account
+     * @param global_bump: global_authority's bump
+     */
+    pub fn pause_raffle(ctx: Context<CancelRaffle>, _global_bump: u8) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        let authority = ctx.accounts.authority.key();
+        if raffle.creator != authority
+            && ctx.accounts.global_authority.load()?.super_admin != authority
+        {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if !raffle.selling()? {
+            return Err(RaffleError::RaffleEnded.into());
+        }
+        raffle.paused = 1;
+
+        emit!(RafflePausedEvent {
+            raffle: ctx.accounts.raffle.key(),
+            authority,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    /**
+     * @dev Resume ticket sales on a paused raffle, by the creator or the admin
+     * @Context has creator or admin, global_authority and raffle account
+     * @param global_bump: global_authority's bump
+     */
+    pub fn resume_raffle(ctx: Context<CancelRaffle>, _global_bump: u8) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        let authority = ctx.accounts.authority.key();
+        if raffle.creator != authority
+            && ctx.accounts.global_authority.load()?.super_admin != authority
+        {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.paused != 1 {
+            return Err(RaffleError::InvalidRaffleStatus.into());
+        }
+        raffle.paused = 0;
+
+        emit!(RaffleResumedEvent {
+            raffle: ctx.accounts.raffle.key(),
+            authority,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    /**
+     * @dev Withdraw NFT function
+     * @Context has claimer and global_authority account
+     * raffle account and creator's nft ATA and global_authority's nft ATA
+     * pNFT raffles pass the pNFT transfer accounts as remaining accounts,
+     * Token-2022 prizes pass their transfer hook accounts instead
+     * @param global_bump: global_authority's bump
+     */
+    pub fn withdraw_nft<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawNft<'info>>,
+        global_bump: u8,
+    ) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if !raffle.ended(timestamp) {
+            return Err(RaffleError::RaffleNotEnded.into());
+        }
+        if raffle.creator != ctx.accounts.claimer.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.revealed != 1 {
+            return Err(RaffleError::WinnerNotDrawn.into());
+        }
+        ctx.accounts.check_token_accounts()?;
+        let mut amount = 1;
+        if raffle.tier_count > 0 && ctx.accounts.nft_mint_address.key() == raffle.tier_mint {
+            // tier tokens not owed to a drawn winner go back to the creator
+            let owed = raffle.tier_tokens_owed();
+            amount = raffle
+                .tier_escrowed
+                .checked_sub(owed)
+                .ok_or(RaffleError::InvalidCalculation)?;
+            if amount == 0 {
+                return Err(RaffleError::NoPrize.into());
+            }
+            raffle.tier_escrowed = owed;
+        } else if raffle.wl_mint != Pubkey::default()
+            && ctx.accounts.nft_mint_address.key() == raffle.wl_mint
+        {
+            // whitelist tokens not owed to a drawn winner go back to the creator
+            let owed = raffle.unclaimed_winners();
+            amount = raffle
+                .wl_escrowed
+                .checked_sub(owed)
+                .ok_or(RaffleError::InvalidCalculation)?;
+            if amount == 0 {
+                return Err(RaffleError::NoPrize.into());
+            }
+            raffle.wl_escrowed = owed;
+        } else {
+            let prize_index = raffle
+                .prize_index(ctx.accounts.nft_mint_address.key())
+                .ok_or(RaffleError::NoPrize)?;
+            if raffle.prize_type == PRIZE_TOKEN {
+                // the creator gets back whatever does not split evenly across the winners
+                amount = if raffle.winner_count() == 0 {
+                    raffle.prize_amount
+                } else {
+                    raffle.prize_amount % raffle.winner_count()
+                };
+                if amount == 0 {
+                    return Err(RaffleError::NoPrize.into());
+                }
+                raffle.prize_amount -= amount;
+            } else if raffle.prize_type == PRIZE_BUNDLE {
+                // the bundle belongs to the winner when there is one
+                if raffle.winner_count() != 0 {
+                    return Err(RaffleError::OtherEntrants.into());
+                }
+                if raffle.bundle_claimed(prize_index) {
+                    return Err(RaffleError::NoPrize.into());
+                }
+                raffle.bundle_claimed |= 1 << prize_index;
+            } else if raffle.prize_type == PRIZE_EDITION {
+                // the master edition goes back once every winner printed an edition
+                if !raffle.all_claimed() {
+                    return Err(RaffleError::UnclaimedPrizes.into());
+                }
+            } else if raffle.whitelisted == 1 {
+                // prizes with a drawn winner belong to that winner
+                if prize_index < raffle.winner_count() as usize {
+                    return Err(RaffleError::OtherEntrants.into());
+                }
+                if raffle.claimed(prize_index) {
+                    return Err(RaffleError::NoPrize.into());
+                }
+                raffle.set_claimed(prize_index)?;
+            }
+        }
+
+        // Transfer NFT to the creator's wallet after the raffle ends
+        let src_token_account = &mut &ctx.accounts.src_nft_token_account;
+        let dest_token_account = &mut &ctx.accounts.claimer_nft_token_account;
+        let token_program = &mut &ctx.accounts.token_program;
+        let seeds = &[GLOBAL_AUTHORITY_SEED.as_bytes(), &[global_bump]];
+        let signer = &[&seeds[..]];
+
+        if raffle.programmable == 1 {
+            PnftTransfer {
+                token: src_token_account.to_account_info(),
+                token_owner: ctx.accounts.global_authority.to_account_info(),
+                destination: dest_token_account.to_account_info(),
+                destination_owner: ctx.accounts.claimer.to_account_info(),
+                mint: ctx.accounts.nft_mint_address.to_account_info(),
+                payer: ctx.accounts.claimer.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: token_program.to_account_info(),
+            }
+            .invoke_signed(ctx.remaining_accounts, signer)?;
+        } else {
+            TokenTransfer {
+                token_program: token_program.to_account_info(),
+                source: src_token_account.to_account_info(),
+                mint: ctx.accounts.nft_mint_address.to_account_info(),
+                destination: dest_token_account.to_account_info(),
+                authority: ctx.accounts.global_authority.to_account_info(),
+            }
+            .invoke_signed(amount, ctx.remaining_accounts, signer)?;
+        }
+        if raffle.whitelisted != 1 || raffle.winner_count() == 0 {
+            raffle.set_status(RaffleStatus::Withdrawn)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * @dev Claim a compressed NFT prize
+     * @Context has claimer and global_authority account, raffle account, the creator's CreatorStats PDA
+     * and the Bubblegum accounts with the cNFT's proof path as remaining accounts
+     * @param global_bump: global_authority's bump
+     * @param root: the current root of the merkle tree
+     */
+    pub fn claim_cnft_reward<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferCnft<'info>>,
+        global_bump: u8,
+        root: [u8; 32],
+    ) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if !raffle.ended(timestamp) {
+            return Err(RaffleError::RaffleNotEnded.into());
+        }
+        if raffle.revealed != 1 {
+            return Err(RaffleError::WinnerNotDrawn.into());
+        }
+        if raffle.prize_type != PRIZE_CNFT {
+            return Err(RaffleError::NoPrize.into());
+        }
+        let winner_index = raffle
+            .unclaimed_winner_index(ctx.accounts.claimer.key())
+            .ok_or(RaffleError::NotWinner)?;
+
+        ctx.accounts
+            .transfer_cnft(&raffle, global_bump, root, ctx.remaining_accounts)?;
+        raffle.set_claimed(winner_index)?;
+
+        record_prizes_delivered(
+            &raffle.creator,
+            &ctx.accounts.claimer.to_account_info(),
+            &ctx.accounts.creator_stats,
+            &ctx.accounts.system_program.to_account_info(),
+            1,
+        )?;
+
+        raffle.mark_claimed();
+
+        Ok(())
+    }
+
+    /**
+     * @dev Withdraw a compressed NFT prize back to the creator when nobody won it
+     * or its winner did not claim it before the claim deadline
+     * @Context has creator and global_authority account, raffle account
+     * and the Bubblegum accounts with the cNFT's proof path as remaining accounts
+     * @param global_bump: global_authority's bump
+     * @param root: the current root of the merkle tree
+     */
+    pub fn withdraw_cnft<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferCnft<'info>>,
+        global_bump: u8,
+        root: [u8; 32],
+    ) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if !raffle.ended(timestamp) {
+            return Err(RaffleError::RaffleNotEnded.into());
+        }
+        if raffle.creator != ctx.accounts.claimer.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.revealed != 1 {
+            return Err(RaffleError::WinnerNotDrawn.into());
+        }
+        if raffle.prize_type != PRIZE_CNFT {
+            return Err(RaffleError::NoPrize.into());
+        }
+        // a winner who missed the claim window forfeits the prize to the creator
+        if raffle.winner_count() != 0 && !raffle.claim_window_closed(timestamp) {
+            return Err(RaffleError::OtherEntrants.into());
+        }
+        if raffle.claimed(0) {
+            return Err(RaffleError::NoPrize.into());
+        }
+
+        ctx.accounts
+            .transfer_cnft(&raffle, global_bump, root, ctx.remaining_accounts)?;
+        raffle.set_claimed(0)?;
+        raffle.set_status(RaffleStatus::Withdrawn)?;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Claim an MPL Core asset prize
+     * @Context has claimer and global_authority account, raffle account,
+     * the asset, its collection (or the Core program if it has none), the Core program
+     * and the creator's CreatorStats PDA
+     * @param global_bump: global_authority's bump
+     */
+    pub fn claim_core_reward(ctx: Context<TransferCore>, global_bump: u8) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if !raffle.ended(timestamp) {
+            return Err(RaffleError::RaffleNotEnded.into());
+        }
+        if raffle.revealed != 1 {
+            return Err(RaffleError::WinnerNotDrawn.into());
+        }
+        if raffle.prize_type != PRIZE_CORE {
+            return Err(RaffleError::NoPrize.into());
+        }
+        let winner_index = raffle
+            .unclaimed_winner_index(ctx.accounts.claimer.key())
+            .ok_or(RaffleError::NotWinner)?;
+
+        ctx.accounts.transfer_core(&raffle, global_bump)?;
+        raffle.set_claimed(winner_index)?;
+
+        record_prizes_delivered(
+            &raffle.creator,
+            &ctx.accounts.claimer.to_account_info(),
+            &ctx.accounts.creator_stats,
+            &ctx.accounts.system_program.to_account_info(),
+            1,
+        )?;
+
+        raffle.mark_claimed();
+
+        Ok(())
+    }
+
+    /**
+     * @dev Withdraw an MPL Core asset prize back to the creator when nobody won it
+     * or its winner did not claim it before the claim deadline
+     * @Context has creator and global_authority account, raffle account,
+     * the asset, its collection (or the Core program if it has none) and the Core program
+     * @param global_bump: global_authority's bump
+     */
+    pub fn withdraw_core(ctx: Context<TransferCore>, global_bump: u8) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if !raffle.ended(timestamp) {
+            return Err(RaffleError::RaffleNotEnded.into());
+        }
+        if raffle.creator != ctx.accounts.claimer.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.revealed != 1 {
+            return Err(RaffleError::WinnerNotDrawn.into());
+        }
+        if raffle.prize_type != PRIZE_CORE {
+            return Err(RaffleError::NoPrize.into());
+        }
+        // a winner who missed the claim window forfeits the prize to the creator
+        if raffle.winner_count() != 0 && !raffle.claim_window_closed(timestamp) {
+            return Err(RaffleError::OtherEntrants.into());
+        }
+        if raffle.claimed(0) {
+            return Err(RaffleError::NoPrize.into());
+        }
+
+        ctx.accounts.transfer_core(&raffle, global_bump)?;
+        raffle.set_claimed(0)?;
+        raffle.set_status(RaffleStatus::Withdrawn)?;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Claim a prize NFT frozen in the creator's wallet, it is thawed and moved by global_authority
+     * @Context has claimer and global_authority account, raffle account,
+     * the creator's frozen nft ATA, claimer's nft ATA, nft mint address,
+     * its master edition, the creator's CreatorStats PDA and the Token Metadata program
+     * @param global_bump: global_authority's bump
+     */
+    pub fn claim_delegated_reward(
+        ctx: Context<ClaimDelegatedReward>,
+        global_bump: u8,
+    ) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if !raffle.ended(timestamp) {
+            return Err(RaffleError::RaffleNotEnded.into());
+        }
+        if raffle.revealed != 1 {
+            return Err(RaffleError::WinnerNotDrawn.into());
+        }
+        if raffle.prize_type != PRIZE_DELEGATED
+            || ctx.accounts.delegated_token_account.key() != raffle.delegated_account
+        {
+            return Err(RaffleError::NoPrize.into());
+        }
+        check_token_account(
+            &ctx.accounts.claimer_nft_token_account,
+            &ctx.accounts.token_program,
+            &raffle.nft_mint,
+            &ctx.accounts.claimer.key(),
+        )?;
+        let winner_index = raffle
+            .unclaimed_winner_index(ctx.accounts.claimer.key())
+            .ok_or(RaffleError::NotWinner)?;
+
+        // Thaw the NFT and transfer it to the winner's wallet as delegate
+        let seeds = &[GLOBAL_AUTHORITY_SEED.as_bytes(), &[global_bump]];
+        let signer = &[&seeds[..]];
+        DelegatedFreeze {
+            delegate: ctx.accounts.global_authority.to_account_info(),
+            token_account: ctx.accounts.delegated_token_account.to_account_info(),
+            edition: ctx.accounts.edition.to_account_info(),
+            mint: ctx.accounts.nft_mint_address.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        }
+        .invoke_signed(&ctx.accounts.token_metadata_program, true, signer)?;
+        TokenTransfer {
+            token_program: ctx.accounts.token_program.to_account_info(),
+            source: ctx.accounts.delegated_token_account.to_account_info(),
+            mint: ctx.accounts.nft_mint_address.to_account_info(),
+            destination: ctx.accounts.claimer_nft_token_account.to_account_info(),
+            authority: ctx.accounts.global_authority.to_account_info(),
+        }
+        .invoke_signed(1, &[], signer)?;
+        raffle.set_claimed(winner_index)?;
+
+        record_prizes_delivered(
+            &raffle.creator,
+            &ctx.accounts.claimer.to_account_info(),
+            &ctx.accounts.creator_stats,
+            &ctx.accounts.system_program.to_account_info(),
+            1,
+        )?;
+
+        raffle.mark_claimed();
+
+        Ok(())
+    }
+
+    /**
+     * @dev Thaw the creator's prize NFT and revoke the delegate when nobody won it
+     * or its winner did not claim it before the claim deadline
+     * @Context has creator and global_authority account, raffle account,
+     * the creator's frozen nft ATA, nft mint address, its master edition and the Token Metadata program
+     * @param global_bump: global_authority's bump
+     */
+    pub fn release_delegated_prize(
+        ctx: Context<ReleaseDelegatedPrize>,
+        global_bump: u8,
+    ) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if !raffle.ended(timestamp) {
+            return Err(RaffleError::RaffleNotEnded.into());
+        }
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.revealed != 1 {
+            return Err(RaffleError::WinnerNotDrawn.into());
+        }
+        if raffle.prize_type != PRIZE_DELEGATED
+            || ctx.accounts.delegated_token_account.key() != raffle.delegated_account
+        {
+            return Err(RaffleError::NoPrize.into());
+        }
+        // a winner who missed the claim window forfeits the prize to the creator
+        if raffle.winner_count() != 0 && !raffle.claim_window_closed(timestamp) {
+            return Err(RaffleError::OtherEntrants.into());
+        }
+        if raffle.claimed(0) {
+            return Err(RaffleError::NoPrize.into());
+        }
+
+        let seeds = &[GLOBAL_AUTHORITY_SEED.as_bytes(), &[global_bump]];
+        DelegatedFreeze {
+            delegate: ctx.accounts.global_authority.to_account_info(),
+            token_account: ctx.accounts.delegated_token_account.to_account_info(),
+            edition: ctx.accounts.edition.to_account_info(),
+            mint: ctx.accounts.nft_mint_address.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        }
+        .invoke_signed(&ctx.accounts.token_metadata_program, true, &[&seeds[..]])?;
+        let ix = revoke(
+            ctx.accounts.token_program.key,
+            ctx.accounts.delegated_token_account.key,
+            ctx.accounts.creator.key,
+            &[],
+        )?;
+        invoke(
+            &ix,
+            &[
+                ctx.accounts.delegated_token_account.to_account_info(),
+                ctx.accounts.creator.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+        )?;
+        raffle.set_claimed(0)?;
+        raffle.set_status(RaffleStatus::Withdrawn)?;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Pay the buyout price to end the raffle at once with the buyer as the sole winner
+     * the SOL ticket proceeds escrowed so far are refunded pro-rata with claim_refund
+     * @Context has buyer, global_authority, raffle account and the raffle's proceeds vault PDA
+     * @param global_bump: global_authority's bump
+     * @param vault_bump: the raffle's proceeds vault bump
+     */
+    pub fn buy_now(ctx: Context<BuyNow>, _global_bump: u8, _vault_bump: u8) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        // buyout refunds are paid to the raffle's own entrant slots only
+        if raffle.buyout_price == 0 || raffle.refundable == 1 || raffle.page_count > 0 {
+            return Err(RaffleError::NoBuyout.into());
+        }
+        // buyout refunds are paid from the SOL proceeds only
+        if raffle.accepts_token_payment() {
+            return Err(RaffleError::BuyoutSolOnly.into());
+        }
+        if timestamp < raffle.start_timestamp {
+            return Err(RaffleError::SalesNotStarted.into());
+        }
+        if raffle.paused == 1 {
+            return Err(RaffleError::RafflePaused.into());
+        }
+        ctx.accounts.global_authority.load()?.check_not_paused()?;
+        if timestamp > raffle.sales_end() || raffle.revealed != 0 {
+            return Err(RaffleError::RaffleEnded.into());
+        }
+        if raffle.prize_hidden() {
+            return Err(RaffleError::PrizeNotRevealed.into());
+        }
+
+        sol_transfer_user(
+            ctx.accounts.buyer.to_account_info(),
+            ctx.accounts.proceeds_vault.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            raffle.buyout_price,
+        )?;
+
+        raffle.refund_pool = raffle.proceeds;
+        raffle.proceeds = raffle.buyout_price;
+        raffle.bought_out = 1;
+        raffle.end_timestamp = timestamp;
+        raffle.winner[0] = ctx.accounts.buyer.key();
+        raffle.set_winner_count(1)?;
+        raffle.revealed_count = 1;
+        raffle.revealed = 1;
+        raffle.finish_draw(timestamp)?;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Refund a ticket holder's share of the SOL proceeds after a buyout
+     * refunds stay open while the protocol is paused
+     * @Context has buyer, global_authority, raffle account and the raffle's proceeds vault PDA
+     * @param global_bump: global_authority's bump
+     * @param vault_bump: the raffle's proceeds vault bump
+     */
+    pub fn claim_refund(ctx: Context<BuyNow>, _global_bump: u8, vault_bump: u8) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.bought_out != 1 {
+            return Err(RaffleError::NoBuyout.into());
+        }
+
+        // Clear the claimer's tickets so the refund is paid once
+        raffle.check_capacity()?;
+        let index = raffle
+            .entrant_index(&ctx.accounts.buyer.key())
+            .ok_or(RaffleError::NoRefund)?;
+        let tickets = raffle.entrant_tickets[index];
+        if tickets == 0 {
+            return Err(RaffleError::NoRefund.into());
+        }
+        raffle.entrant_tickets[index] = 0;
+        let refund = (raffle.refund_pool as u128 * tickets as u128 / raffle.count() as u128) as u64;
+        if refund == 0 {
+            return Err(RaffleError::NoRefund.into());
+        }
+
+        let raffle_key = ctx.accounts.raffle.key();
+        let seeds = &[PROCEEDS_SEED.as_bytes(), raffle_key.as_ref(), &[vault_bump]];
+        sol_transfer_with_signer(
+            ctx.accounts.proceeds_vault.to_account_info(),
+            ctx.accounts.buyer.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            &[&seeds[..]],
+            refund,
+        )?;
+        raffle.refunded += refund;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Unwrap the wSOL ticket proceeds into the proceeds vault once the winners are revealed
+     * closing the vault's wSOL account, so claim_proceeds and claim_refund pay them out as SOL
+     * @Context has raffle account, the raffle's proceeds vault PDA and its wSOL account
+     * @param vault_bump: the raffle's proceeds vault bump
+     */
+    pub fn unwrap_proceeds(ctx: Context<UnwrapProceeds>, vault_bump: u8) -> ProgramResult {
+        let raffle = ctx.accounts.raffle.load()?;
+        if raffle.revealed != 1 {
+            return Err(RaffleError::WinnerNotDrawn.into());
+        }
+        check_token_account(
+            &ctx.accounts.proceeds_token_account,
+            &ctx.accounts.token_program,
+            &spl_token::native_mint::id(),
+            &ctx.accounts.proceeds_vault.key(),
+        )?;
+
+        let raffle_key = ctx.accounts.raffle.key();
+        let seeds = &[PROCEEDS_SEED.as_bytes(), raffle_key.as_ref(), &[vault_bump]];
+        let ix = close_account(
+            ctx.accounts.token_program.key,
+            ctx.accounts.proceeds_token_account.key,
+            ctx.accounts.proceeds_vault.key,
+            ctx.accounts.proceeds_vault.key,
+            &[],
+        )?;
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.proceeds_token_account.to_account_info(),
+                ctx.accounts.proceeds_vault.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            &[&seeds[..]],
+        )?;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Claim back every ticket payment made to a cancelled refundable raffle
+     * the buyer's TicketReceipt records their tickets and what was paid for them
+     * wSOL payments are refunded in SOL once unwrap_proceeds has run
+     * @Context has buyer, raffle account, buyer's TicketReceipt PDA, the raffle's proceeds vault PDA
+     * and the payment token accounts of buyer and proceeds vault
+     * @param vault_bump: the raffle's proceeds vault bump
+     * @param receipt_bump: the TicketReceipt's bump
+     */
+    pub fn claim_ticket_refund(
+        ctx: Context<ClaimTicketRefund>,
+        vault_bump: u8,
+        _receipt_bump: u8,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+        if raffle.cancelled != 1 {
+            return Err(RaffleError::RaffleNotCancelled.into());
+        }
+        raffle.open_receipts = raffle
+            .open_receipts
+            .checked_sub(1)
+            .ok_or(RaffleError::InvalidCalculation)?;
+
+        let raffle_key = ctx.accounts.raffle.key();
+        let seeds = &[PROCEEDS_SEED.as_bytes(), raffle_key.as_ref(), &[vault_bump]];
+        let receipt = &ctx.accounts.receipt;
+        if receipt.paid_sol > 0 {
+            sol_transfer_with_signer(
+                ctx.accounts.proceeds_vault.to_account_info(),
+                ctx.accounts.buyer.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                &[&seeds[..]],
+                receipt.paid_sol,
+            )?;
+        }
+        if receipt.paid_token > 0 {
+            check_token_program(&ctx.accounts.token_program, &ctx.accounts.token_mint)?;
+            if ctx.accounts.token_mint.key() != raffle.payment_mint {
+                return Err(RaffleError::InvalidPaymentMint.into());
+            }
+            check_token_account(
+                &ctx.accounts.user_token_account,
+                &ctx.accounts.token_program,
+                &raffle.payment_mint,
+                &ctx.accounts.buyer.key(),
+            )?;
+            TokenTransfer {
+                token_program: ctx.accounts.token_program.to_account_info(),
+                source: ctx.accounts.proceeds_token_account.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                destination: ctx.accounts.user_token_account.to_account_info(),
+                authority: ctx.accounts.proceeds_vault.to_account_info(),
+            }
+            .invoke_signed(receipt.paid_token, &[], &[&seeds[..]])?;
+        }
+
+        emit!(TicketRefundedEvent {
+            raffle: raffle_key,
+            buyer: ctx.accounts.buyer.key(),
+            tickets: receipt.tickets,
+            paid_sol: receipt.paid_sol,
+            paid_token: receipt.paid_token,
+        });
+
+        Ok(())
+    }
+
+    /**
+     * @dev Release the escrow of a refundable raffle once the winners are revealed
+     * the held protocol fees go to the treasury and the escrowed tokens are burned and split
+     * between treasury and creator as buy_tickets does, the SOL proceeds are withdrawn with claim_proceeds
+     * @Context has global_authority, treasury, raffle account, the raffle's proceeds vault PDA
+     * and the payment token accounts of creator, treasury and proceeds vault
+     * @param global_bump: global_authority's bump
+     * @param vault_bump: the raffle's proceeds vault bump
+     */
+    pub fn release_escrow(
+        ctx: Context<ReleaseEscrow>,
+        _global_bump: u8,
+        vault_bump: u8,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+        if raffle.refundable != 1 {
+            return Err(RaffleError::NotRefundable.into());
+        }
+        if raffle.cancelled == 1 {
+            return Err(RaffleError::RaffleCancelled.into());
+        }
+        if raffle.revealed != 1 {
+            return Err(RaffleError::WinnerNotDrawn.into());
+        }
+
+        let raffle_key = ctx.accounts.raffle.key();
+        let seeds = &[PROCEEDS_SEED.as_bytes(), raffle_key.as_ref(), &[vault_bump]];
+        if raffle.held_fees > 0 {
+            if ctx.accounts.treasury.key() != ctx.accounts.global_authority.load()?.treasury {
+                return Err(RaffleError::InvalidTreasury.into());
+            }
+            sol_transfer_with_signer(
+                ctx.accounts.proceeds_vault.to_account_info(),
+                ctx.accounts.treasury.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                &[&seeds[..]],
+                raffle.held_fees,
+            )?;
+            ctx.accounts
+                .global_authority
+                .load_mut()?
+                .record_fees(raffle.held_fees)?;
+            raffle.held_fees = 0;
+        }
+        if raffle.token_proceeds > 0 {
+            check_token_program(&ctx.accounts.token_program, &ctx.accounts.token_mint)?;
+            if ctx.accounts.token_mint.key() != raffle.payment_mint {
+                return Err(RaffleError::InvalidPaymentMint.into());
+            }
+            let burn_amount = bps_share(raffle.token_proceeds, raffle.burn_bps)?;
+            let treasury_amount = bps_share(raffle.token_proceeds, raffle.treasury_bps)?;
+            let creator_amount = raffle
+                .token_proceeds
+                .checked_sub(burn_amount)
+                .and_then(|amount| amount.checked_sub(treasury_amount))
+                .ok_or(RaffleError::InvalidCalculation)?;
+            if burn_amount > 0 {
+                token_burn(
+                    ctx.accounts.token_program.to_account_info(),
+                    ctx.accounts.proceeds_token_account.to_account_info(),
+                    ctx.accounts.token_mint.to_account_info(),
+                    ctx.accounts.proceeds_vault.to_account_info(),
+                    burn_amount,
+                    &[&seeds[..]],
+                )?;
+                raffle.total_tokens_burned = raffle
+                    .total_tokens_burned
+                    .checked_add(burn_amount)
+                    .ok_or(RaffleError::AmountOverflow)?;
+                ctx.accounts
+                    .global_authority
+                    .load_mut()?
+                    .record_burn(burn_amount)?;
+            }
+            if treasury_amount > 0 {
+                check_token_account(
+                    &ctx.accounts.treasury_token_account,
+                    &ctx.accounts.token_program,
+                    &raffle.payment_mint,
+                    &ctx.accounts.global_authority.load()?.treasury,
+                )?;
+                TokenTransfer {
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                    source: ctx.accounts.proceeds_token_account.to_account_info(),
+                    mint: ctx.accounts.token_mint.to_account_info(),
+                    destination: ctx.accounts.treasury_token_account.to_account_info(),
+                    authority: ctx.accounts.proceeds_vault.to_account_info(),
+                }
+                .invoke_signed(treasury_amount, &[], &[&seeds[..]])?;
+            }
+            if creator_amount > 0 {
+                check_token_account(
+                    &ctx.accounts.creator_token_account,
+                    &ctx.accounts.token_program,
+                    &raffle.payment_mint,
+                    &raffle.creator,
+                )?;
+                TokenTransfer {
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                    source: ctx.accounts.proceeds_token_account.to_account_info(),
+                    mint: ctx.accounts.token_mint.to_account_info(),
+                    destination: ctx.accounts.creator_token_account.to_account_info(),
+                    authority: ctx.accounts.proceeds_vault.to_account_info(),
+                }
+                .invoke_signed(creator_amount, &[], &[&seeds[..]])?;
+            }
+            raffle.token_proceeds = 0;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * @dev Withdraw the escrowed SOL ticket proceeds once the winners are revealed
+     * each split payee, the charity and the prize NFT's creators are paid their share
+     * and the rest goes to the creator
+     * @Context has creator, raffle account and the raffle's proceeds vault PDA
+     * and the split payees as remaining accounts in registration order followed by the charity
+     * and the prize NFT's metadata and its creators in metadata order if they are set
+     * @param vault_bump: the raffle's proceeds vault bump
+     */
+    pub fn claim_proceeds<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimProceeds<'info>>,
+        vault_bump: u8,
+    ) -> ProgramResult {
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.revealed != 1 {
+            return Err(RaffleError::WinnerNotDrawn.into());
+        }
+        if raffle.cancelled == 1 {
+            return Err(RaffleError::RaffleCancelled.into());
+        }
+        // Refunds owed to ticket holders after a buyout and unreleased fees stay in the vault
+        let amount = raffle
+            .refund_pool
+            .checked_sub(raffle.refunded)
+            .and_then(|owed| ctx.accounts.proceeds_vault.lamports().checked_sub(owed))
+            .and_then(|amount| amount.checked_sub(raffle.held_fees))
+            .ok_or(RaffleError::InvalidCalculation)?;
+        if amount == 0 {
+            return Err(RaffleError::NoPrize.into());
+        }
+
+        if ctx.remaining_accounts.len() < raffle.split_count as usize {
+            return Err(RaffleError::InvalidSplitPayee.into());
+        }
+
+        let raffle_key = ctx.accounts.raffle.key();
+        let seeds = &[PROCEEDS_SEED.as_bytes(), raffle_key.as_ref(), &[vault_bump]];
+        let mut paid: u64 = 0;
+        for i in 0..raffle.split_count as usize {
+            let payee = &ctx.remaining_accounts[i];
+            if payee.key() != raffle.split_payees[i] {
+                return Err(RaffleError::InvalidSplitPayee.into());
+            }
+            let share = bps_share(amount, raffle.split_bps[i])?;
+            if share == 0 {
+                continue;
+            }
+            sol_transfer_with_signer(
+                ctx.accounts.proceeds_vault.to_account_info(),
+                payee.clone(),
+                ctx.accounts.system_program.to_account_info(),
+                &[&seeds[..]],
+                share,
+            )?;
+            paid = paid
+                .checked_add(share)
+                .ok_or(RaffleError::InvalidCalculation)?;
+        }
+
+        let mut next = raffle.split_count as usize;
+        let charity_share = bps_share(amount, raffle.charity_bps)?;
+        if raffle.charity_bps > 0 {
+            let charity = ctx
+                .remaining_accounts
+                .get(next)
+                .ok_or(RaffleError::InvalidCharity)?;
+            if charity.key() != raffle.charity {
+                return Err(RaffleError::InvalidCharity.into());
+            }
+            if charity_share > 0 {
+                sol_transfer_with_signer(
+                    ctx.accounts.proceeds_vault.to_account_info(),
+                    charity.clone(),
+                    ctx.accounts.system_program.to_account_info(),
+                    &[&seeds[..]],
+                    charity_share,
+                )?;
+                paid = paid
+                    .checked_add(charity_share)
+                    .ok_or(RaffleError::InvalidCalculation)?;
+            }
+            next += 1;
+        }
+
+        // Royalties are shared between the metadata creators like marketplaces do
+        let royalty = bps_share(amount, raffle.royalty_bps)?;
+        if raffle.royalty_bps > 0 {
+            let metadata = ctx
+                .remaining_accounts
+                .get(next)
+                .ok_or(RaffleError::InvalidRoyalty)?;
+            let creators = metadata_creators(metadata, &raffle.nft_mint)?;
+            if ctx.remaining_accounts.len() < next + 1 + creators.len() {
+                return Err(RaffleError::InvalidRoyalty.into());
+            }
+            for (j, (address, share)) in creators.iter().enumerate() {
+                let creator = &ctx.remaining_accounts[next + 1 + j];
+                if creator.key() != *address {
+                    return Err(RaffleError::InvalidRoyalty.into());
+                }
+                // metadata shares are percentages, 1% is 100 bps
+                let creator_share = bps_share(royalty, *share as u64 * 100)?;
+                if creator_share == 0 {
+                    continue;
+                }
+                sol_transfer_with_signer(
+                    ctx.accounts.proceeds_vault.to_account_info(),
+                    creator.clone(),
+                    ctx.accounts.system_program.to_account_info(),
+                    &[&seeds[..]],
+                    creator_share,
+                )?;
+                paid = paid
+                    .checked_add(creator_share)
+                    .ok_or(RaffleError::InvalidCalculation)?;
+            }
+        }
+
+        sol_transfer_with_signer(
+            ctx.accounts.proceeds_vault.to_account_info(),
+            ctx.accounts.creator.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            &[&seeds[..]],
+            amount
+                .checked_sub(paid)
+                .ok_or(RaffleError::InvalidCalculation)?,
+        )?;
+        raffle.proceeds = 0;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Close a settled raffle and return its rent to the creator
+     * the proceeds vault is swept and its emptied token accounts are closed too
+     * @Context has creator, global_authority, raffle account and the raffle's proceeds vault PDA
+     * and the proceeds vault's empty token accounts as remaining accounts
+     * @param global_bump: global_authority's bump
+     * @param vault_bump: the raffle's proceeds vault bump
+     */
+    pub fn close_raffle<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseRaffle<'info>>,
+        _global_bump: u8,
+        vault_bump: u8,
+    ) -> ProgramResult {
+        let raffle = ctx.accounts.raffle.load()?;
+
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if !raffle.settled()? {
+            return Err(RaffleError::RaffleNotSettled.into());
+        }
+
+        let raffle_key = ctx.accounts.raffle.key();
+        let vault_seeds = &[PROCEEDS_SEED.as_bytes(), raffle_key.as_ref(), &[vault_bump]];
+        let token_program = &ctx.accounts.token_program;
+        if *token_program.key != spl_token::id()
+            && *token_program.key != TOKEN_2022_PROGRAM_ID.parse::<Pubkey>().unwrap()
+        {
+            return Err(RaffleError::InvalidTokenProgram.into());
+        }
+        // Only this raffle's proceeds vault token accounts, the global authority's escrows are shared
+        for account in ctx.remaining_accounts {
+            if account.owner != token_program.key {
+                return Err(RaffleError::InvalidTokenAccount.into());
+            }
+            check_closable_token_account(account, &ctx.accounts.proceeds_vault.key())?;
+            token_close(
+                token_program.to_account_info(),
+                account.clone(),
+                ctx.accounts.creator.to_account_info(),
+                ctx.accounts.proceeds_vault.to_account_info(),
+                &[&vault_seeds[..]],
+            )?;
+        }
+
+        let vault_lamports = ctx.accounts.proceeds_vault.lamports();
+        if vault_lamports > 0 {
+            sol_transfer_with_signer(
+                ctx.accounts.proceeds_vault.to_account_info(),
+                ctx.accounts.creator.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                &[&vault_seeds[..]],
+                vault_lamports,
+            )?;
+        }
+        ctx.accounts.global_authority.load_mut()?.record_close();
+
+        Ok(())
+    }
+
+    /**
+     * @dev Claim the winner's share of a SOL prize pot
+     * @Context has claimer, raffle account, the raffle's vault PDA and the creator's CreatorStats PDA
+     * @param vault_bump: raffle vault's bump
+     */
+    pub fn claim_sol_reward(ctx: Context<ClaimSolReward>, vault_bump: u8) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if !raffle.ended(timestamp) {
+            return Err(RaffleError::RaffleNotEnded.into());
+        }
+        if raffle.revealed != 1 {
+            return Err(RaffleError::WinnerNotDrawn.into());
+        }
+        if raffle.prize_type != PRIZE_SOL {
+            return Err(RaffleError::NoPrize.into());
+        }
+        let winner_index = raffle
+            .unclaimed_winner_index(ctx.accounts.claimer.key())
+            .ok_or(RaffleError::NotWinner)?;
+
+        let raffle_key = ctx.accounts.raffle.key();
+        let seeds = &[VAULT_SEED.as_bytes(), raffle_key.as_ref(), &[vault_bump]];
+        sol_transfer_with_signer(
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.claimer.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            &[&seeds[..]],
+            raffle.prize_amount / raffle.winner_count(),
+        )?;
+        raffle.set_claimed(winner_index)?;
+
+        record_prizes_delivered(
+            &raffle.creator,
+            &ctx.accounts.claimer.to_account_info(),
+            &ctx.accounts.creator_stats,
+            &ctx.accounts.system_program.to_account_info(),
+            1,
+        )?;
+
+        raffle.mark_claimed();
+
+        Ok(())
+    }
+
+    /**
+     * @dev Withdraw what is left in a SOL prize vault once every winner has claimed
+     * or the claim deadline has passed, unclaimed shares go to the creator
+     * @Context has creator, raffle account and the raffle's vault PDA
+     * @param vault_bump: raffle vault's bump
+     */
+    pub fn withdraw_sol_prize(ctx: Context<WithdrawSolPrize>, vault_bump: u8) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut raffle = ctx.accounts.raffle.load_mut()?;
+
+        if !raffle.ended(timestamp) {
+            return Err(RaffleError::RaffleNotEnded.into());
+        }
+        if raffle.creator != ctx.accounts.creator.key() {
+            return Err(RaffleError::NotCreator.into());
+        }
+        if raffle.revealed != 1 {
+            return Err(RaffleError::WinnerNotDrawn.into());
+        }
+        if raffle.prize_type != PRIZE_SOL {
+            return Err(RaffleError::NoPrize.into());
+        }
+        if !raffle.all_claimed() && !raffle.claim_window_closed(timestamp) {
+            return Err(RaffleError::UnclaimedPrizes.into());
+        }
+
+        let raffle_key = ctx.accounts.raffle.key();
+        let seeds = &[VAULT_SEED.as_bytes(), raffle_key.as_ref(), &[vault_bump]];
+        sol_transfer_with_signer(
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.creator.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            &[&seeds[..]],
+            ctx.accounts.vault.lamports(),
+        )?;
+        raffle.prize_amount = 0;
+        // the winners who missed the claim window have nothing left to claim
+        for i in 0..raffle.winner_count() as usize {
+            raffle.set_claimed(i)?;
+        }
+
+        raffle.set_status(RaffleStatus::Withdrawn)?;
+
+        Ok(())
+    }
+
+    /**
+     * @dev Propose recovering prize tokens the global authority escrows for a raffle
+     * the withdrawal can only be executed once EMERGENCY_WITHDRAW_DELAY has passed
+     * @Context has admin, global_authority, raffle account, the EmergencyWithdrawal PDA to create,
+     * global_authority's token account to drain, the destination token account and the token mint
+     * @param global_bump: global authority's bump
+     * @param proposal_bump: EmergencyWithdrawal's bump
+     * @param amount: token amount to withdraw
+     */
+    pub fn propose_emergency_withdraw(
+        ctx: Context<ProposeEmergencyWithdraw>,
+        _global_bump: u8,
+        _proposal_bump: u8,
+        amount: u64,
+    ) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        if ctx.accounts.global_authority.load()?.super_admin != ctx.accounts.admin.key() {
+            return Err(RaffleError::NotSuperAdmin.into());
+        }
+        check_token_program(&ctx.accounts.token_program, &ctx.accounts.token_mint)?;
+        check_token_account(
+            &ctx.accounts.source_token_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.token_mint.key(),
+            &ctx.accounts.global_authority.key(),
+        )?;
+        // only the prizes the global authority escrows for this raffle can be recovered
+        if !ctx
+            .accounts
+            .raffle
+            .load()?
+            .escrows_mint(&ctx.accounts.token_mint.key())
+        {
+            return Err(RaffleError::NoPrize.into());
+        }
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.raffle = ctx.accounts.raffle.key();
+        proposal.source = ctx.accounts.source_token_account.key();
+        proposal.destination = ctx.accounts.dest_token_account.key();
+        proposal.amount = amount;
+        proposal.proposed_at = timestamp;
+        proposal.executable_at = timestamp + EMERGENCY_WITHDRAW_DELAY;
+
+        emit!(EmergencyWithdrawProposedEvent {
+            raffle: proposal.raffle,
+            source: proposal.source,
+            destination: proposal.destination,
+            amount,
+            executable_at: proposal.executable_at,
+        });
+
+        Ok(())
+    }
+
+    /**
+     * @dev Execute a proposed emergency withdrawal once its timelock has passed
+     * @Context has admin, global_authority, raffle account, the EmergencyWithdrawal PDA,
+     * global_authority's token account, the proposed destination token account and the token mint,
+     * Token-2022 transfer hook accounts as remaining accounts
+     * @param global_bump: global authority's bump
+     * @param proposal_bump: EmergencyWithdrawal's bump
+     */
+    pub fn execute_emergency_withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteEmergencyWithdraw<'info>>,
+        global_bump: u8,
+        _proposal_bump: u8,
+    ) -> ProgramResult {
+        let timestamp = Clock::get()?.unix_timestamp;
+        if ctx.accounts.global_authority.load()?.super_admin != ctx.accounts.admin.key() {
+            return Err(RaffleError::NotSuperAdmin.into());
+        }
+        let proposal = &ctx.accounts.proposal;
+        if timestamp < proposal.executable_at {
+            return Err(RaffleError::EmergencyTimelocked.into());
+        }
+        if proposal.destination != ctx.accounts.dest_token_account.key() {
+            return Err(RaffleError::InvalidTokenAccount.into());
+        }
+        check_token_program(&ctx.accounts.token_program, &ctx.accounts.token_mint)?;
+
+        let seeds = &[GLOBAL_AUTHORITY_SEED.as_bytes(), &[global_bump]];
+        TokenTransfer {
+            token_program: ctx.accounts.token_program.to_account_info(),
+            source: ctx.accounts.source_token_account.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
+            destination: ctx.accounts.dest_token_account.to_account_info(),
+            authority: ctx.accounts.global_authority.to_account_info(),
+        }
+        .invoke_signed(proposal.amount, ctx.remaining_accounts, &[&seeds[..]])?;
+
+        emit!(EmergencyWithdrawExecutedEvent {
+            raffle: proposal.raffle,
+            source: proposal.source,
+            destination: proposal.destination,
+            amount: proposal.amount,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    /**
+     * @dev Cancel a pending emergency withdrawal, refunding its rent to the admin
+     * @Context has admin, global_authority and the EmergencyWithdrawal PDA
+     * @param global_bump: global authority's bump
+     */
+    pub fn cancel_emergency_withdraw(
+        ctx: Context<CancelEmergencyWithdraw>,
+        _global_bump: u8,
+    ) -> ProgramResult {
+        if ctx.accounts.global_authority.load()?.super_admin != ctx.accounts.admin.key() {
+            return Err(RaffleError::NotSuperAdmin.into());
+        }
+
+        emit!(EmergencyWithdrawCancelledEvent {
+            raffle: ctx.accounts.proposal.raffle,
+            source: ctx.accounts.proposal.source,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /**
+     * @dev Return a compact summary of the raffle as return data, for clients that simulate
+     * this instruction instead of parsing the raffle account
+     * the data is the borsh encoded RaffleState, led by its layout version
+     * @Context has raffle account
+     */
+    pub fn get_raffle_state(ctx: Context<ViewRaffle>) -> ProgramResult {
+        let raffle = ctx.accounts.raffle.load()?;
+        set_return_data(&RaffleState::new(&raffle).try_to_vec()?);
+        Ok(())
+    }
+
+    /**
+     * @dev Return a wallet's tickets, the raffle's total tickets and the wallet's chance to win
+     * as return data, for clients that simulate this instruction
+     * the data is the borsh encoded WinningOdds, the chance is a fraction of ODDS_SCALE
+     * @Context has raffle account and the wallet's TicketRecord PDA, whether or not it has been created
+     * @param wallet: the wallet to estimate the odds of
+     */
+    pub fn get_winning_odds(ctx: Context<ViewOdds>, wallet: Pubkey) -> ProgramResult {
+        let raffle = ctx.accounts.raffle.load()?;
+        let raffle_key = ctx.accounts.raffle.key();
+        let (address, _) = Pubkey::find_program_address(
+            &[
+                TICKET_RECORD_SEED.as_ref(),
+                raffle_key.as_ref(),
+                wallet.as_ref(),
+            ],
+            &crate::ID,
+        );
+        let ticket_record = &ctx.accounts.ticket_record;
+        if ticket_record.key() != address {
+            return Err(RaffleError::InvalidTicketRecord.into());
+        }
+        let tickets = if ticket_record.data_is_empty() {
+            0
+        } else {
+            let data = ticket_record.try_borrow_data()?;
+            TicketRecord::try_deserialize(&mut &data[..])?.tickets
+        };
+
+        let odds = WinningOdds::new(tickets, raffle.count(), raffle.winner_count());
+        set_return_data(&odds.try_to_vec()?);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+        payer = admin,
+        space = 8 + std::mem::size_of::<GlobalPool>()
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub account: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct SetProtocolFee<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8, leaderboard_bump: u8)]
+pub struct InitializeLeaderboard<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(
+        init,
+        seeds = [LEADERBOARD_SEED.as_ref()],
+        bump = leaderboard_bump,
+        payer = admin,
+        space = 8 + std::mem::size_of::<Leaderboard>()
+    )]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8, leaderboard_bump: u8)]
+pub struct StartLeaderboardSeason<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(
+        mut,
+        seeds = [LEADERBOARD_SEED.as_ref()],
+        bump = leaderboard_bump,
+    )]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8, exemption_bump: u8, wallet: Pubkey)]
+pub struct AddFeeExemption<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(
+        init,
+        seeds = [FEE_EXEMPTION_SEED.as_ref(), wallet.as_ref()],
+        bump = exemption_bump,
+        payer = admin
+    )]
+    pub fee_exemption: Account<'info, FeeExemption>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct RemoveFeeExemption<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(mut, close = admin)]
+    pub fee_exemption: Account<'info, FeeExemption>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8, raffle_bump: u8, raffle_id: u64)]
+pub struct AllocateRaffle<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(
+        mut,
+        seeds = [RAFFLE_SEED.as_ref(), raffle_id.to_le_bytes().as_ref()],
+        bump = raffle_bump,
+    )]
+    pub raffle: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct CreateRaffle<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    // the admin's FeeExemption PDA, whether or not it has been created
+    pub fee_exemption: AccountInfo<'info>,
+    // the admin's CreatorStats PDA, whether or not it has been created
+    #[account(mut)]
+    pub creator_stats: AccountInfo<'info>,
+
+    #[account(zero)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    #[account(mut)]
+    pub owner_temp_nft_account: AccountInfo<'info>,
+    #[account(mut)]
+    pub dest_nft_token_account: AccountInfo<'info>,
+
+    pub nft_mint_address: AccountInfo<'info>,
+
+    pub token_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateRaffle<'info> {
+    // the prize mint may live under SPL Token or Token-2022
+    pub fn check_token_accounts(&self) -> ProgramResult {
+        check_token_program(&self.token_program, &self.nft_mint_address)?;
+        check_token_account(
+            &self.owner_temp_nft_account,
+            &self.token_program,
+            &self.nft_mint_address.key(),
+            &self.admin.key(),
+        )?;
+        check_token_account(
+            &self.dest_nft_token_account,
+            &self.token_program,
+            &self.nft_mint_address.key(),
+            &self.global_authority.key(),
+        )
+    }
+
+    // escrow the crank tip in the raffle account
+    pub fn escrow_crank_tip(&self, crank_tip: u64) -> ProgramResult {
+        if crank_tip > 0 {
+            sol_transfer_user(
+                self.admin.to_account_info(),
+                self.raffle.to_account_info(),
+                self.system_program.to_account_info(),
+                crank_tip,
+            )?;
+        }
+        Ok(())
+    }
+
+    // transfer the extra prize NFTs to the PDA
+    // prizes: owner's nft ATA, global_authority's nft ATA and nft mint per prize
+    pub fn escrow_extra_prizes(
+        &self,
+        raffle: &mut RafflePool,
+        prizes: &[AccountInfo<'info>],
+    ) -> ProgramResult {
+        if prizes.len() % 3 != 0 || prizes.len() / 3 + 1 > MAX_WINNERS {
+            return Err(RaffleError::InvalidPrizeAccounts.into());
+        }
+        for prize in prizes.chunks(3) {
+            let prize_mint = prize[2].key();
+            check_token_program(&self.token_program, &prize[2])?;
+            check_token_account(
+                &prize[0],
+                &self.token_program,
+                &prize_mint,
+                &self.admin.key(),
+            )?;
+            check_token_account(
+                &prize[1],
+                &self.token_program,
+                &prize_mint,
+                &self.global_authority.key(),
+            )?;
+
+            TokenTransfer {
+                token_program: self.token_program.to_account_info(),
+                source: prize[0].clone(),
+                mint: prize[2].clone(),
+                destination: prize[1].clone(),
+                authority: self.admin.to_account_info(),
+            }
+            .invoke_signed(1, &[], &[])?;
+            let prize_index = raffle.prize_count as usize;
+            raffle.prize_mints[prize_index] = prize_mint;
+            raffle.prize_count += 1;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(vault_bump: u8)]
+pub struct CreateRaffleSolPrize<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    // only the global authority PDA holds a GlobalPool
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    // the admin's FeeExemption PDA, whether or not it has been created
+    pub fee_exemption: AccountInfo<'info>,
+    // the admin's CreatorStats PDA, whether or not it has been created
+    #[account(mut)]
+    pub creator_stats: AccountInfo<'info>,
+
+    #[account(zero)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED.as_ref(), raffle.key().as_ref()],
+        bump = vault_bump,
+    )]
+    pub vault: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct CreateRaffleCnft<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    // the admin's FeeExemption PDA, whether or not it has been created
+    pub fee_exemption: AccountInfo<'info>,
+    // the admin's CreatorStats PDA, whether or not it has been created
+    #[account(mut)]
+    pub creator_stats: AccountInfo<'info>,
+
+    #[account(zero)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    pub tree_authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub merkle_tree: AccountInfo<'info>,
+    pub log_wrapper: AccountInfo<'info>,
+    pub compression_program: AccountInfo<'info>,
+    pub bubblegum_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct CreateRaffleCore<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    // the admin's FeeExemption PDA, whether or not it has been created
+    pub fee_exemption: AccountInfo<'info>,
+    // the admin's CreatorStats PDA, whether or not it has been created
+    #[account(mut)]
+    pub creator_stats: AccountInfo<'info>,
+
+    #[account(zero)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    #[account(mut)]
+    pub asset: AccountInfo<'info>,
+    pub collection: AccountInfo<'info>,
+    pub core_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct AddPrize<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    #[account(mut)]
+    pub owner_temp_nft_account: AccountInfo<'info>,
+    #[account(mut)]
+    pub dest_nft_token_account: AccountInfo<'info>,
+
+    pub nft_mint_address: AccountInfo<'info>,
+
+    pub token_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddPrize<'info> {
+    // the prize mint may live under SPL Token or Token-2022
+    pub fn check_token_accounts(&self) -> ProgramResult {
+        check_token_program(&self.token_program, &self.nft_mint_address)?;
+        check_token_account(
+            &self.owner_temp_nft_account,
+            &self.token_program,
+            &self.nft_mint_address.key(),
+            &self.creator.key(),
+        )?;
+        check_token_account(
+            &self.dest_nft_token_account,
+            &self.token_program,
+            &self.nft_mint_address.key(),
+            &self.global_authority.key(),
+        )
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct SetPrizeTiers<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    #[account(mut)]
+    pub creator_token_account: AccountInfo<'info>,
+    #[account(mut)]
+    pub dest_token_account: AccountInfo<'info>,
+
+    pub token_mint: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> SetPrizeTiers<'info> {
+    // the tier token mint may live under SPL Token or Token-2022
+    pub fn check_token_accounts(&self) -> ProgramResult {
+        check_token_program(&self.token_program, &self.token_mint)?;
+        check_token_account(
+            &self.creator_token_account,
+            &self.token_program,
+            &self.token_mint.key(),
+            &self.creator.key(),
+        )?;
+        check_token_account(
+            &self.dest_token_account,
+            &self.token_program,
+            &self.token_mint.key(),
+            &self.global_authority.key(),
+        )
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct CreateRaffleDelegated<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    // the admin's FeeExemption PDA, whether or not it has been created
+    pub fee_exemption: AccountInfo<'info>,
+    // the admin's CreatorStats PDA, whether or not it has been created
+    #[account(mut)]
+    pub creator_stats: AccountInfo<'info>,
+
+    #[account(zero)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    #[account(mut)]
+    pub owner_nft_token_account: AccountInfo<'info>,
+    pub nft_mint_address: AccountInfo<'info>,
+    pub edition: AccountInfo<'info>,
+
+    pub token_metadata_program: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct CreateRaffleMystery<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    // the admin's FeeExemption PDA, whether or not it has been created
+    pub fee_exemption: AccountInfo<'info>,
+    // the admin's CreatorStats PDA, whether or not it has been created
+    #[account(mut)]
+    pub creator_stats: AccountInfo<'info>,
+
+    #[account(zero)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRaffle<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8, vault_bump: u8)]
+pub struct BuyTickets<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    // the buyer's FeeExemption PDA, whether or not it has been created
+    pub fee_exemption: AccountInfo<'info>,
+    // the buyer's TicketRecord PDA, whether or not it has been created
+    #[account(mut)]
+    pub ticket_record: AccountInfo<'info>,
+    // the buyer's UserProfile PDA, whether or not it has been created
+    #[account(mut)]
+    pub user_profile: AccountInfo<'info>,
+    // the raffle creator's CreatorStats PDA, whether or not it has been created
+    #[account(mut)]
+    pub creator_stats: AccountInfo<'info>,
+    // the Leaderboard PDA, the only account of its type as it is only created at its seeds
+    #[account(mut)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(
+        mut,
+        seeds = [PROCEEDS_SEED.as_ref(), raffle.key().as_ref()],
+        bump = vault_bump,
+    )]
+    pub proceeds_vault: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub user_token_account: AccountInfo<'info>,
+    #[account(mut)]
+    pub creator_token_account: AccountInfo<'info>,
+    #[account(mut)]
+    pub treasury_token_account: AccountInfo<'info>,
+    #[account(mut)]
+    pub proceeds_token_account: AccountInfo<'info>,
+    #[account(mut)]
+    pub token_mint: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> BuyTickets<'info> {
+    // pay the SOL price from the buyer's wSOL account, syncing lamports sent to it first
+    // the fee goes to the treasury's wSOL account and the rest to the proceeds vault's
+    pub fn pay_wsol(&self, fee_sol: u64, proceeds_sol: u64) -> ProgramResult {
+        let ix = sync_native(self.token_program.key, self.user_token_account.key)?;
+        invoke(
+            &ix,
+            &[
+                self.user_token_account.to_account_info(),
+                self.token_program.to_account_info(),
+            ],
+        )?;
+
+        if fee_sol > 0 {
+            check_token_account(
+                &self.treasury_token_account,
+                &self.token_program,
+                &spl_token::native_mint::id(),
+                &self.global_authority.load()?.treasury,
+            )?;
+            TokenTransfer {
+                token_program: self.token_program.to_account_info(),
+                source: self.user_token_account.to_account_info(),
+                mint: self.token_mint.to_account_info(),
+                destination: self.treasury_token_account.to_account_info(),
+                authority: self.buyer.to_account_info(),
+            }
+            .invoke_signed(fee_sol, &[], &[])?;
+        }
+        if proceeds_sol > 0 {
+            check_token_account(
+                &self.proceeds_token_account,
+                &self.token_program,
+                &spl_token::native_mint::id(),
+                &self.proceeds_vault.key(),
+            )?;
+            TokenTransfer {
+                token_program: self.token_program.to_account_info(),
+                source: self.user_token_account.to_account_info(),
+                mint: self.token_mint.to_account_info(),
+                destination: self.proceeds_token_account.to_account_info(),
+                authority: self.buyer.to_account_info(),
+            }
+            .invoke_signed(proceeds_sol, &[], &[])?;
+        }
+        Ok(())
+    }
+
+    // the protocol fee of a SOL payment, waived for fee exempt buyers
+    pub fn protocol_fee(&self, total_amount_sol: u64) -> std::result::Result<u64, ProgramError> {
+        if is_fee_exempt(&self.fee_exemption, &self.buyer.key())? {
+            return Ok(0);
+        }
+        bps_share(total_amount_sol, self.global_authority.load()?.fee_bps)
+    }
+
+    // move SOL from the buyer into the proceeds vault
+    // the first deposit tops the vault up to rent exemption if needed
+    pub fn deposit_proceeds(&self, amount: u64) -> ProgramResult {
+        let rent = Rent::get()?.minimum_balance(0);
+        let vault_lamports = self.proceeds_vault.lamports();
+        let top_up = if vault_lamports + amount < rent {
+            rent - vault_lamports - amount
+        } else {
+            0
+        };
+        sol_transfer_user(
+            self.buyer.to_account_info(),
+            self.proceeds_vault.to_account_info(),
+            self.system_program.to_account_info(),
+            amount + top_up,
+        )
+    }
+
+    // add the buyer's tickets to the entrant slot their TicketRecord PDA points to
+    // and record the purchase in it, the record holds what the buyer bought and paid in total
+    // the first purchase takes the next slot, on the raffle's last entrant page once its own
+    // slots are full, and creates the record so repeat buyers are found without a scan
+    pub fn enter(
+        &self,
+        raffle: &mut RafflePool,
+        amount: u64,
+        paid_sol: u64,
+        paid_token: u64,
+        timestamp: i64,
+        entrant_page: Option<&AccountInfo<'info>>,
+    ) -> ProgramResult {
+        let raffle_key = self.raffle.key();
+        let buyer_key = self.buyer.key();
+        let (address, bump) = Pubkey::find_program_address(
+            &[
+                TICKET_RECORD_SEED.as_ref(),
+                raffle_key.as_ref(),
+                buyer_key.as_ref(),
+            ],
+            &crate::ID,
+        );
+        if self.ticket_record.key() != address {
+            return Err(RaffleError::InvalidTicketRecord.into());
+        }
+
+        let mut record = if self.ticket_record.data_is_empty() {
+            let mut record = TicketRecord {
+                raffle: raffle_key,
+                buyer: buyer_key,
+                first_purchase_at: timestamp,
+                ..Default::default()
+            };
+            if (raffle.entrant_count as usize) < MAX_ENTRANTS {
+                record.slot = raffle.entrant_count;
+                raffle.add_entrant(buyer_key, amount)?;
+            } else {
+                if raffle.page_count == 0 {
+                    return Err(RaffleError::InvalidEntrantPage.into());
+                }
+                let page = load_entrant_page(entrant_page, &raffle_key, raffle.page_count - 1)?;
+                let mut page = page.load_mut()?;
+                record.page = raffle.page_count;
+                record.slot = page.entrant_count;
+                page.add_entrant(buyer_key, amount)?;
+                raffle.add_page_tickets(amount)?;
+            }
+
+            let seeds = &[
+                TICKET_RECORD_SEED.as_bytes(),
+                raffle_key.as_ref(),
+                buyer_key.as_ref(),
+                &[bump],
+            ];
+            create_program_account(
+                self.buyer.to_account_info(),
+                self.ticket_record.clone(),
+                self.system_program.to_account_info(),
+                8 + std::mem::size_of::<TicketRecord>(),
+                &[&seeds[..]],
+            )?;
+            record
+        } else {
+            let record = {
+                let data = self.ticket_record.try_borrow_data()?;
+                TicketRecord::try_deserialize(&mut &data[..])?
+            };
+            if record.page == 0 {
+                raffle.add_slot_tickets(buyer_key, record.slot as usize, amount)?;
+            } else {
+                let page = load_entrant_page(entrant_page, &raffle_key, record.page - 1)?;
+                page.load_mut()?
+                    .add_slot_tickets(buyer_key, record.slot as usize, amount)?;
+                raffle.add_page_tickets(amount)?;
+            }
+            record
+        };
+        record.tickets = record
+            .tickets
+            .checked_add(amount)
+            .ok_or(RaffleError::AmountOverflow)?;
+        if raffle.max_tickets_per_user > 0 && record.tickets > raffle.max_tickets_per_user {
+            return Err(RaffleError::WalletTicketLimit.into());
+        }
+        record.paid_sol = record
+            .paid_sol
+            .checked_add(paid_sol)
+            .ok_or(RaffleError::AmountOverflow)?;
+        record.paid_token = record
+            .paid_token
+            .checked_add(paid_token)
+            .ok_or(RaffleError::AmountOverflow)?;
+        record.last_purchase_at = timestamp;
+
+        let mut data = self.ticket_record.try_borrow_mut_data()?;
+        let mut writer: &mut [u8] = &mut data;
+        record.try_serialize(&mut writer)
+    }
+
+    // add a payment to the buyer's TicketReceipt PDA, creating it on the first purchase
+    pub fn record_payment(
+        &self,
+        receipt: &AccountInfo<'info>,
+        tickets: u64,
+        paid_sol: u64,
+        paid_token: u64,
+    ) -> ProgramResult {
+        let raffle_key = self.raffle.key();
+        let buyer_key = self.buyer.key();
+        let (address, bump) = Pubkey::find_program_address(
+            &[
+                RECEIPT_SEED.as_ref(),
+                raffle_key.as_ref(),
+                buyer_key.as_ref(),
+            ],
+            &crate::ID,
+        );
+        if receipt.key() != address {
+            return Err(RaffleError::InvalidReceipt.into());
+        }
+        let mut record = if receipt.data_is_empty() {
+            let seeds = &[
+                RECEIPT_SEED.as_bytes(),
+                raffle_key.as_ref(),
+                buyer_key.as_ref(),
+                &[bump],
+            ];
+            create_program_account(
+                self.buyer.to_account_info(),
+                receipt.clone(),
+                self.system_program.to_account_info(),
+                8 + std::mem::size_of::<TicketReceipt>(),
+                &[&seeds[..]],
+            )?;
+            TicketReceipt {
+                raffle: raffle_key,
+                buyer: buyer_key,
+                ..Default::default()
+            }
+        } else {
+            let data = receipt.try_borrow_data()?;
+            TicketReceipt::try_deserialize(&mut &data[..])?
+        };
+        record.paid_sol = record
+            .paid_sol
+            .checked_add(paid_sol)
+            .ok_or(RaffleError::AmountOverflow)?;
+        record.paid_token = record
+            .paid_token
+            .checked_add(paid_token)
+            .ok_or(RaffleError::AmountOverflow)?;
+        record.tickets = record
+            .tickets
+            .checked_add(tickets)
+            .ok_or(RaffleError::AmountOverflow)?;
+
+        let mut data = receipt.try_borrow_mut_data()?;
+        let mut writer: &mut [u8] = &mut data;
+        record.try_serialize(&mut writer)
+    }
+
+    // enter the buyer and take the ticket payment, less the given discount
+    // the referrer, if any, gets its share of the SOL payment
+    // and refundable raffles record the payment in the buyer's receipt
+    // returning buyers whose TicketRecord points to an entrant page pass that page
+    // and new wallets the raffle's last page once its own entrant slots are full
+    pub fn buy(
+        &self,
+        feeds: &[AccountInfo<'info>],
+        amount: u64,
+        nonce: &[u8; 32],
+        pay_with: u64,
+        discount_bps: u64,
+        referrer: Option<&AccountInfo<'info>>,
+        receipt: Option<&AccountInfo<'info>>,
+        entrant_page: Option<&AccountInfo<'info>>,
+    ) -> ProgramResult {
+        let clock = Clock::get()?;
+        let timestamp = clock.unix_timestamp;
+        let mut raffle = self.raffle.load_mut()?;
+        if amount == 0 {
+            return Err(RaffleError::ZeroTicketAmount.into());
+        }
+        if raffle.cancelled == 1 {
+            return Err(RaffleError::RaffleCancelled.into());
+        }
+        // bought out or drawn raffles may still read as selling to older status values
+        if raffle.revealed != 0 || raffle.revealed_count != 0 || raffle.bought_out == 1 {
+            return Err(RaffleError::RaffleAlreadyRevealed.into());
+        }
+        if !raffle.selling()? {
+            return Err(RaffleError::RaffleEnded.into());
+        }
+        if raffle.paused == 1 {
+            return Err(RaffleError::RafflePaused.into());
+        }
+        self.global_authority.load()?.check_not_paused()?;
+        if !raffle.payment_allowed(pay_with) {
+            return Err(RaffleError::PaymentNotAllowed.into());
+        }
+        if raffle.free_entry() {
+            return Err(RaffleError::FreeEntryOnly.into());
+        }
+        if pay_with == PAY_WSOL {
+            check_token_program(&self.token_program, &self.token_mint)?;
+            if self.token_mint.key() != spl_token::native_mint::id() {
+                return Err(RaffleError::InvalidPaymentMint.into());
+            }
+            check_token_account(
+                &self.user_token_account,
+                &self.token_program,
+                &spl_token::native_mint::id(),
+                &self.buyer.key(),
+            )?;
+        } else if pay_with != PAY_SOL {
+            check_token_program(&self.token_program, &self.token_mint)?;
+            if self.token_mint.key() != raffle.payment_mint
+                || token_decimals(&self.token_mint)? as u64 != raffle.payment_decimals
+            {
+                return Err(RaffleError::InvalidPaymentMint.into());
+            }
+            check_token_account(
+                &self.user_token_account,
+                &self.token_program,
+                &raffle.payment_mint,
+                &self.buyer.key(),
+            )?;
+        }
+
+        if timestamp < raffle.start_timestamp {
+            return Err(RaffleError::SalesNotStarted.into());
+        }
+        if timestamp > raffle.sales_end() {
+            return Err(RaffleError::RaffleEnded.into());
+        }
+        let count = raffle
+            .count()
+            .checked_add(amount)
+            .ok_or(RaffleError::AmountOverflow)?;
+        if count > raffle.max_entrants() {
+            return Err(RaffleError::NotEnoughTicketsLeft.into());
+        }
+
+        // Early-bird windows override the base or dutch auction prices until they end
+        let (mut ticket_price_reap, mut ticket_price_sol) = raffle.ticket_prices(timestamp)?;
+        // USD priced tickets resolve the pegged asset's price from the Pyth feed
+        if raffle.usd_price_cents > 0 {
+            let feed = feeds.get(0).ok_or(RaffleError::InvalidPriceFeed)?;
+            if feed.key() != raffle.price_feed {
+                return Err(RaffleError::InvalidPriceFeed.into());
+            }
+            let (price, expo) = pyth_price(feed, timestamp)?;
+            if raffle.usd_peg_token == 1 {
+                ticket_price_reap =
+                    usd_to_amount(raffle.usd_price_cents, price, expo, raffle.payment_decimals)?;
+            } else {
+                ticket_price_sol = usd_to_amount(raffle.usd_price_cents, price, expo, 9)?;
+            }
+        }
+
+        // Bundle discounts reduce the number of tickets paid for
+        let paid_tickets = raffle.paid_tickets(amount)?;
+        let total_amount_reap = if pay_with == PAY_SOL || pay_with == PAY_WSOL {
+            0
+        } else {
+            ticket_total(paid_tickets, ticket_price_reap, discount_bps)?
+        };
+        let total_amount_sol = if pay_with == PAY_TOKEN {
+            0
+        } else {
+            ticket_total(paid_tickets, ticket_price_sol, discount_bps)?
+        };
+
+        if pay_with != PAY_WSOL && self.buyer.to_account_info().lamports() < total_amount_sol {
+            return Err(RaffleError::NotEnoughSOL.into());
+        }
+        let first_purchase = self.ticket_record.data_is_empty();
+        if first_purchase {
+            raffle.unique_entrants += 1;
+        }
+        if raffle.count() == 0 {
+            raffle.no_repeat = 1;
+        } else if !first_purchase {
+            raffle.no_repeat += 1;
+        }
+
+        self.enter(
+            &mut raffle,
+            amount,
+            total_amount_sol,
+            total_amount_reap,
+            timestamp,
+            entrant_page,
+        )?;
+        raffle.total_sol_collected = raffle
+            .total_sol_collected
+            .checked_add(total_amount_sol)
+            .ok_or(RaffleError::AmountOverflow)?;
+        self.global_authority
+            .load_mut()?
+            .record_sale(amount, total_amount_sol)?;
+        // unit prices are the prices in effect at purchase time, before bundle and holder discounts
+        emit!(TicketsPurchasedEvent {
+            raffle: self.raffle.key(),
+            buyer: self.buyer.key(),
+            tickets: amount,
+            unit_price_sol: ticket_price_sol,
+            unit_price_token: ticket_price_reap,
+            paid_sol: total_amount_sol,
+            paid_token: total_amount_reap,
+            slot: clock.slot,
+            timestamp,
+        });
+
+        // The buyer's UserProfile tracks the raffles they entered and the tickets they bought
+        let mut profile = load_user_profile(
+            &self.buyer.to_account_info(),
+            &self.user_profile,
+            &self.system_program.to_account_info(),
+        )?;
+        if first_purchase {
+            profile.add_raffle(self.raffle.key());
+        }
+        profile.tickets_bought = profile
+            .tickets_bought
+            .checked_add(amount)
+            .ok_or(RaffleError::AmountOverflow)?;
+
+        // The Leaderboard ranks the buyer by the tickets they bought this season
+        let mut leaderboard = self.leaderboard.load_mut()?;
+        let season_tickets = profile.add_season_tickets(leaderboard.season, amount)?;
+        leaderboard.record(self.buyer.key(), season_tickets);
+        save_user_profile(&self.user_profile, &profile)?;
+
+        // The creator's CreatorStats adds the SOL paid to their volume
+        let mut stats = load_creator_stats(
+            &raffle.creator,
+            &self.buyer.to_account_info(),
+            &self.creator_stats,
+            &self.system_program.to_account_info(),
+        )?;
+        stats.volume_sol = stats
+            .volume_sol
+            .checked_add(total_amount_sol)
+            .ok_or(RaffleError::AmountOverflow)?;
+        save_creator_stats(&self.creator_stats, &stats)?;
+        raffle.add_entropy(self.buyer.key(), nonce, clock.slot);
+        if raffle.extend_for_snipe(timestamp) {
+            emit!(RaffleExtendedEvent {
+                raffle: self.raffle.key(),
+                buyer: self.buyer.key(),
+                end_timestamp: raffle.end_timestamp,
+                extensions: raffle.extensions,
+            });
+        }
+
+        let src_account_info = &mut &self.user_token_account;
+        let mint_info = &mut &self.token_mint;
+        let token_program = &mut &self.token_program;
+        let epoch = clock.epoch;
+
+        // Refundable raffles escrow the whole payment and record it in the buyer's receipt
+        if raffle.refundable == 1 {
+            let receipt = receipt.ok_or(RaffleError::InvalidReceipt)?;
+            if total_amount_reap > 0 {
+                check_token_account(
+                    &self.proceeds_token_account,
+                    &self.token_program,
+                    &raffle.payment_mint,
+                    &self.proceeds_vault.key(),
+                )?;
+                TokenTransfer {
+                    token_program: token_program.to_account_info(),
+                    source: src_account_info.to_account_info(),
+                    mint: mint_info.to_account_info(),
+                    destination: self.proceeds_token_account.to_account_info(),
+                    authority: self.buyer.to_account_info(),
+                }
+                .invoke_signed(
+                    gross_transfer_amount(mint_info, total_amount_reap, epoch)?,
+                    &[],
+                    &[],
+                )?;
+                raffle.token_proceeds = raffle
+                    .token_proceeds
+                    .checked_add(total_amount_reap)
+                    .ok_or(RaffleError::AmountOverflow)?;
+            }
+            if pay_with == PAY_WSOL {
+                self.pay_wsol(0, total_amount_sol)?;
+            } else if total_amount_sol > 0 {
+                self.deposit_proceeds(total_amount_sol)?;
+            }
+            let fee_sol = self.protocol_fee(total_amount_sol)?;
+            raffle.held_fees = raffle
+                .held_fees
+                .checked_add(fee_sol)
+                .ok_or(RaffleError::AmountOverflow)?;
+            raffle.proceeds = total_amount_sol
+                .checked_sub(fee_sol)
+                .and_then(|amount| raffle.proceeds.checked_add(amount))
+                .ok_or(RaffleError::AmountOverflow)?;
+            if receipt.data_is_empty() {
+                raffle.open_receipts = raffle
+                    .open_receipts
+                    .checked_add(1)
+                    .ok_or(RaffleError::InvalidCalculation)?;
+            }
+            return self.record_payment(receipt, amount, total_amount_sol, total_amount_reap);
+        }
+
+        // Split the token payment between burn, treasury and creator
+        let burn_amount = bps_share(total_amount_reap, raffle.burn_bps)?;
+        let treasury_amount = bps_share(total_amount_reap, raffle.treasury_bps)?;
+        let creator_amount = total_amount_reap
+            .checked_sub(burn_amount)
+            .and_then(|amount| amount.checked_sub(treasury_amount))
+            .ok_or(RaffleError::InvalidCalculation)?;
+        if burn_amount > 0 {
+            token_burn(
+                token_program.to_account_info(),
+                src_account_info.to_account_info(),
+                mint_info.to_account_info(),
+                self.buyer.to_account_info(),
+                burn_amount,
+                &[],
+            )?;
+            raffle.total_tokens_burned = raffle
+                .total_tokens_burned
+                .checked_add(burn_amount)
+                .ok_or(RaffleError::AmountOverflow)?;
+            self.global_authority.load_mut()?.record_burn(burn_amount)?;
+        }
+        // Token-2022 transfer fees are added on top so treasury and creator get their full share
+        if treasury_amount > 0 {
+            check_token_account(
+                &self.treasury_token_account,
+                &self.token_program,
+                &raffle.payment_mint,
+                &self.global_authority.load()?.treasury,
+            )?;
+            TokenTransfer {
+                token_program: token_program.to_account_info(),
+                source: src_account_info.to_account_info(),
+                mint: mint_info.to_account_info(),
+                destination: self.treasury_token_account.to_account_info(),
+                authority: self.buyer.to_account_info(),
+            }
+            .invoke_signed(
+                gross_transfer_amount(mint_info, treasury_amount, epoch)?,
+                &[],
+                &[],
+            )?;
+        }
+        if creator_amount > 0 {
+            check_token_account(
+                &self.creator_token_account,
+                &self.token_program,
+                &raffle.payment_mint,
+                &raffle.creator,
+            )?;
+            TokenTransfer {
+                token_program: token_program.to_account_info(),
+                source: src_account_info.to_account_info(),
+                mint: mint_info.to_account_info(),
+                destination: self.creator_token_account.to_account_info(),
+                authority: self.buyer.to_account_info(),
+            }
+            .invoke_signed(
+                gross_transfer_amount(mint_info, creator_amount, epoch)?,
+                &[],
+                &[],
+            )?;
+        }
+
+        // The protocol fee of the SOL payment goes to the treasury
+        let fee_sol = self.protocol_fee(total_amount_sol)?;
+        let referral_sol = match referrer {
+            Some(_) if pay_with != PAY_WSOL => {
+                bps_share(total_amount_sol, self.global_authority.load()?.referral_bps)?
+            }
+            _ => 0,
+        };
+        let proceeds_sol = total_amount_sol - fee_sol - referral_sol;
+        self.global_authority.load_mut()?.record_fees(fee_sol)?;
+        if pay_with == PAY_WSOL {
+            self.pay_wsol(fee_sol, proceeds_sol)?;
+            raffle.proceeds = raffle
+                .proceeds
+                .checked_add(proceeds_sol)
+                .ok_or(RaffleError::AmountOverflow)?;
+            return Ok(());
+        }
+        if fee_sol > 0 {
+            if self.treasury.key() != self.global_authority.load()?.treasury {
+                return Err(RaffleError::InvalidTreasury.into());
+            }
+            sol_transfer_user(
+                self.buyer.to_account_info(),
+                self.treasury.to_account_info(),
+                self.system_program.to_account_info(),
+                fee_sol,
+            )?;
+        }
+        // The referral share accrues in the referrer's account until withdrawn
+        if let Some(referrer) = referrer {
+            if referral_sol > 0 {
+                sol_transfer_user(
+                    self.buyer.to_account_info(),
+                    referrer.clone(),
+                    self.system_program.to_account_info(),
+                    referral_sol,
+                )?;
+            }
+        }
+        // The creator's SOL is escrowed until the winners are revealed
+        if proceeds_sol > 0 {
+            self.deposit_proceeds(proceeds_sol)?;
+            raffle.proceeds = raffle
+                .proceeds
+                .checked_add(proceeds_sol)
+                .ok_or(RaffleError::AmountOverflow)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(page_bump: u8, page_index: u64)]
+pub struct AddEntrantPage<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    #[account(
+        init,
+        seeds = [ENTRANT_PAGE_SEED.as_ref(), raffle.key().as_ref(), page_index.to_le_bytes().as_ref()],
+        bump = page_bump,
+        payer = payer,
+        space = 8 + std::mem::size_of::<EntrantPage>()
+    )]
+    pub entrant_page: AccountLoader<'info, EntrantPage>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct RevealWinner<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    pub slot_hashes: AccountInfo<'info>,
+}
+
+impl<'info> RevealWinner<'info> {
+    // derive the draw seed according to the raffle's randomness mode
+    pub fn draw_seed(
+        &self,
+        raffle: &RafflePool,
+        timestamp: i64,
+        secret: [u8; 32],
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> std::result::Result<[u8; 32], ProgramError> {
+        let seed = match RandomnessMode::from_u64(raffle.randomness_mode)? {
+            RandomnessMode::Pseudo => {
+                keccak::hashv(&[&self.raffle.key().to_bytes(), &timestamp.to_le_bytes()]).to_bytes()
+            }
+            RandomnessMode::SlotHash => {
+                let slot_hash = recent_slot_hash(&self.slot_hashes)?;
+                keccak::hashv(&[
+                    &slot_hash,
+                    &self.raffle.key().to_bytes(),
+                    &timestamp.to_le_bytes(),
+                ])
+                .to_bytes()
+            }
+            RandomnessMode::CommitReveal => {
+                if keccak::hash(&secret).to_bytes() != raffle.commitment {
+                    return Err(RaffleError::InvalidRevealedData.into());
+                }
+                // the hash of the slot the sales closed in was unknown when they closed
+                if raffle.end_slot == 0 {
+                    return Err(RaffleError::SalesNotClosed.into());
+                }
+                let end_slot_hash = slot_hash_at(&self.slot_hashes, raffle.end_slot)?;
+                keccak::hashv(&[&secret, &end_slot_hash]).to_bytes()
+            }
+            RandomnessMode::Orao => {
+                let request = remaining_accounts
+                    .get(0)
+                    .ok_or(RaffleError::InvalidRandomnessAccount)?;
+                let randomness = orao_randomness(request, &raffle.randomness)?;
+                keccak::hash(&randomness).to_bytes()
+            }
+            RandomnessMode::Switchboard => {
+                return Err(RaffleError::RandomnessModeUnsupported.into());
+            }
+        };
+        Ok(seed)
+    }
+}
+
+#[derive(Accounts)]
+pub struct CloseSales<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+}
+
+#[derive(Accounts)]
+pub struct RequestDraw<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    #[account(mut)]
+    pub network_state: AccountInfo<'info>,
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    #[account(mut)]
+    pub randomness: AccountInfo<'info>,
+
+    pub orao_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FulfillDraw<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    pub randomness: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct ForceDraw<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct ClaimReward<'info> {
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    #[account(mut)]
+    pub claimer_nft_token_account: AccountInfo<'info>,
+    #[account(mut)]
+    pub src_nft_token_account: AccountInfo<'info>,
+    // the claimer's UserProfile PDA, whether or not it has been created
+    #[account(mut)]
+    pub claimer_profile: AccountInfo<'info>,
+    // the raffle creator's CreatorStats PDA, whether or not it has been created
+    #[account(mut)]
+    pub creator_stats: AccountInfo<'info>,
+
+    pub nft_mint_address: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimReward<'info> {
+    // the prize mint may live under SPL Token or Token-2022
+    pub fn check_token_accounts(&self) -> ProgramResult {
+        check_token_program(&self.token_program, &self.nft_mint_address)?;
+        check_token_account(
+            &self.claimer_nft_token_account,
+            &self.token_program,
+            &self.nft_mint_address.key(),
+            &self.claimer.key(),
+        )?;
+        check_token_account(
+            &self.src_nft_token_account,
+            &self.token_program,
+            &self.nft_mint_address.key(),
+            &self.global_authority.key(),
+        )
+    }
+
+    // transfer a prize held by the global authority to the claimer
+    pub fn transfer_prize(
+        &self,
+        programmable: u64,
+        amount: u64,
+        remaining_accounts: &[AccountInfo<'info>],
+        global_bump: u8,
+    ) -> ProgramResult {
+        let seeds = &[GLOBAL_AUTHORITY_SEED.as_bytes(), &[global_bump]];
+        let signer = &[&seeds[..]];
+        if programmable == 1 {
+            PnftTransfer {
+                token: self.src_nft_token_account.to_account_info(),
+                token_owner: self.global_authority.to_account_info(),
+                destination: self.claimer_nft_token_account.to_account_info(),
+                destination_owner: self.claimer.to_account_info(),
+                mint: self.nft_mint_address.to_account_info(),
+                payer: self.claimer.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+            }
+            .invoke_signed(remaining_accounts, signer)
+        } else {
+            TokenTransfer {
+                token_program: self.token_program.to_account_info(),
+                source: self.src_nft_token_account.to_account_info(),
+                mint: self.nft_mint_address.to_account_info(),
+                destination: self.claimer_nft_token_account.to_account_info(),
+                authority: self.global_authority.to_account_info(),
+            }
+            .invoke_signed(amount, remaining_accounts, signer)
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct SnapshotWinners<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    // the raffle's WinnerSnapshot PDA to create
+    #[account(mut)]
+    pub winner_snapshot: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proof_bump: u8)]
+pub struct ClaimWhitelistProof<'info> {
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    #[account(
+        init,
+        seeds = [raffle.key().as_ref(), claimer.key().as_ref()],
+        bump = proof_bump,
+        payer = claimer
+    )]
+    pub whitelist_proof: Account<'info, WhitelistProof>,
+    // the raffle creator's CreatorStats PDA, whether or not it has been created
+    #[account(mut)]
+    pub creator_stats: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct ClaimEdition<'info> {
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    #[account(mut)]
+    pub new_metadata: AccountInfo<'info>,
+    #[account(mut)]
+    pub new_edition: AccountInfo<'info>,
+    #[account(mut)]
+    pub new_mint: AccountInfo<'info>,
+    #[account(mut)]
+    pub edition_mark: AccountInfo<'info>,
+    #[account(mut)]
+    pub master_edition: AccountInfo<'info>,
+    pub master_metadata: AccountInfo<'info>,
+    pub master_token_account: AccountInfo<'info>,
+    pub update_authority: AccountInfo<'info>,
+    // the raffle creator's CreatorStats PDA, whether or not it has been created
+    #[account(mut)]
+    pub creator_stats: AccountInfo<'info>,
+
+    pub token_metadata_program: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct WithdrawNft<'info> {
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    #[account(mut)]
+    pub claimer_nft_token_account: AccountInfo<'info>,
+    #[account(mut)]
+    pub src_nft_token_account: AccountInfo<'info>,
+
+    pub nft_mint_address: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawNft<'info> {
+    // the prize mint may live under SPL Token or Token-2022
+    pub fn check_token_accounts(&self) -> ProgramResult {
+        check_token_program(&self.token_program, &self.nft_mint_address)?;
+        check_token_account(
+            &self.claimer_nft_token_account,
+            &self.token_program,
+            &self.nft_mint_address.key(),
+            &self.claimer.key(),
+        )?;
+        check_token_account(
+            &self.src_nft_token_account,
+            &self.token_program,
+            &self.nft_mint_address.key(),
+            &self.global_authority.key(),
+        )
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct TransferCnft<'info> {
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    pub tree_authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub merkle_tree: AccountInfo<'info>,
+    pub log_wrapper: AccountInfo<'info>,
+    pub compression_program: AccountInfo<'info>,
+    pub bubblegum_program: AccountInfo<'info>,
+    // the raffle creator's CreatorStats PDA, whether or not it has been created
+    #[account(mut)]
+    pub creator_stats: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TransferCnft<'info> {
+    // transfer the raffle's escrowed cNFT from the global authority to the claimer
+    pub fn transfer_cnft(
+        &self,
+        raffle: &RafflePool,
+        global_bump: u8,
+        root: [u8; 32],
+        proof: &[AccountInfo<'info>],
+    ) -> ProgramResult {
+        if self.bubblegum_program.key() != BUBBLEGUM_PROGRAM_ID.parse::<Pubkey>().unwrap() {
+            return Err(RaffleError::InvalidBubblegumProgram.into());
+        }
+        if self.merkle_tree.key() != raffle.merkle_tree {
+            return Err(RaffleError::InvalidMerkleTree.into());
+        }
+
+        let leaf = CnftLeaf {
+            root,
+            data_hash: raffle.cnft_data_hash,
+            creator_hash: raffle.cnft_creator_hash,
+            nonce: raffle.cnft_nonce,
+            index: raffle.cnft_index as u32,
+        };
+        let mut accounts = vec![
+            self.tree_authority.to_account_info(),
+            self.global_authority.to_account_info(),
+            self.global_authority.to_account_info(),
+            self.claimer.to_account_info(),
+            self.merkle_tree.to_account_info(),
+            self.log_wrapper.to_account_info(),
+            self.compression_program.to_account_info(),
+            self.system_program.to_account_info(),
+        ];
+        accounts.extend_from_slice(proof);
+        let seeds = &[GLOBAL_AUTHORITY_SEED.as_bytes(), &[global_bump]];
+        bubblegum_transfer(&self.bubblegum_program, &accounts, &leaf, &[&seeds[..]])
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct TransferCore<'info> {
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    #[account(mut)]
+    pub asset: AccountInfo<'info>,
+    pub collection: AccountInfo<'info>,
+    pub core_program: AccountInfo<'info>,
+    // the raffle creator's CreatorStats PDA, whether or not it has been created
+    #[account(mut)]
+    pub creator_stats: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TransferCore<'info> {
+    // transfer the raffle's escrowed Core asset from the global authority to the claimer
+    pub fn transfer_core(&self, raffle: &RafflePool, global_bump: u8) -> ProgramResult {
+        if self.asset.key() != raffle.nft_mint {
+            return Err(RaffleError::InvalidCoreAsset.into());
+        }
+        let seeds = &[GLOBAL_AUTHORITY_SEED.as_bytes(), &[global_bump]];
+        CoreTransfer {
+            asset: self.asset.to_account_info(),
+            collection: self.collection.to_account_info(),
+            payer: self.claimer.to_account_info(),
+            authority: self.global_authority.to_account_info(),
+            new_owner: self.claimer.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+        }
+        .invoke_signed(&self.core_program, &[&seeds[..]])
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct ClaimDelegatedReward<'info> {
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    #[account(mut)]
+    pub delegated_token_account: AccountInfo<'info>,
+    #[account(mut)]
+    pub claimer_nft_token_account: AccountInfo<'info>,
+    pub nft_mint_address: AccountInfo<'info>,
+    pub edition: AccountInfo<'info>,
+    // the raffle creator's CreatorStats PDA, whether or not it has been created
+    #[account(mut)]
+    pub creator_stats: AccountInfo<'info>,
+
+    pub token_metadata_program: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct ReleaseDelegatedPrize<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    #[account(mut)]
+    pub delegated_token_account: AccountInfo<'info>,
+    pub nft_mint_address: AccountInfo<'info>,
+    pub edition: AccountInfo<'info>,
+
+    pub token_metadata_program: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(referrer_bump: u8)]
+pub struct RegisterReferrer<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [REFERRER_SEED.as_ref(), wallet.key().as_ref()],
+        bump = referrer_bump,
+        payer = wallet
+    )]
+    pub referrer: Account<'info, Referrer>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(referrer_bump: u8)]
+pub struct WithdrawReferral<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [REFERRER_SEED.as_ref(), wallet.key().as_ref()],
+        bump = referrer_bump,
+    )]
+    pub referrer: Account<'info, Referrer>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8, entry_bump: u8)]
+pub struct EnterFreeRaffle<'info> {
+    #[account(mut)]
+    pub entrant: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
+    #[account(
+        init,
+        seeds = [ENTRY_SEED.as_ref(), raffle.key().as_ref(), entrant.key().as_ref()],
+        bump = entry_bump,
+        payer = entrant
+    )]
+    pub free_entry: Account<'info, FreeEntry>,
 
-        let total_amount_reap = amount * raffle.ticket_price_reap;
-        let total_amount_sol = amount * raffle.ticket_price_sol;
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
 
-        if ctx.accounts.buyer.to_account_info().lamports() < total_amount_sol {
-            return Err(RaffleError::NotEnoughSOL.into());
-        }
-        if raffle.count == 0 {
-            raffle.no_repeat = 1;
-        } else {
-            let mut index: u64 = 0;
-            for i in 0..raffle.count {
-                if raffle.entrants[i as usize] == ctx.accounts.buyer.key() {
-                    index = i + 1 as u64;
-                }
-            }
-            if index != 0 {
-                raffle.no_repeat += 1;
-            }
-        }
+#[derive(Accounts)]
+#[instruction(global_bump: u8, vault_bump: u8)]
+pub struct BuyNow<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
 
-        for _ in 0..amount {
-            raffle.append(ctx.accounts.buyer.key());
-        }
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
 
-        let src_account_info = &mut &ctx.accounts.user_token_account;
-        let mint_info = &mut &ctx.accounts.token_mint;
-        let token_program = &mut &ctx.accounts.token_program;
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
 
-        if total_amount_reap > 0 {
-            let cpi_accounts = Burn {
-                mint: mint_info.clone(),
-                to: src_account_info.clone(),
-                authority: ctx.accounts.buyer.to_account_info().clone(),
-            };
-            token::burn(
-                CpiContext::new(token_program.clone().to_account_info(), cpi_accounts),
-                total_amount_reap,
-            )?;
-        }
+    #[account(
+        mut,
+        seeds = [PROCEEDS_SEED.as_ref(), raffle.key().as_ref()],
+        bump = vault_bump,
+    )]
+    pub proceeds_vault: AccountInfo<'info>,
 
-        if total_amount_sol > 0 {
-            sol_transfer_user(
-                ctx.accounts.buyer.to_account_info(),
-                ctx.accounts.creator.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-                total_amount_sol,
-            )?;
-        }
+    pub system_program: Program<'info, System>,
+}
 
-        Ok(())
-    }
+#[derive(Accounts)]
+#[instruction(vault_bump: u8)]
+pub struct UnwrapProceeds<'info> {
+    pub raffle: AccountLoader<'info, RafflePool>,
 
-    /**
-     * @dev Reaveal winner function
-     * @Context has buyer and raffle account address
-     */
-    pub fn reveal_winner(ctx: Context<RevealWinner>) -> ProgramResult {
-        let timestamp = Clock::get()?.unix_timestamp;
-        let mut raffle = ctx.accounts.raffle.load_mut()?;
+    #[account(
+        mut,
+        seeds = [PROCEEDS_SEED.as_ref(), raffle.key().as_ref()],
+        bump = vault_bump,
+    )]
+    pub proceeds_vault: AccountInfo<'info>,
 
-        if timestamp < raffle.end_timestamp {
-            return Err(RaffleError::RaffleNotEnded.into());
-        }
-        if raffle.count < raffle.winner_count {
-            raffle.winner_count = raffle.count;
-        }
+    #[account(mut)]
+    pub proceeds_token_account: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+}
 
-        for j in 0..raffle.winner_count {
-            let (player_address, bump) = Pubkey::find_program_address(
-                &[RANDOM_SEED.as_bytes(), timestamp.to_string().as_bytes()],
-                &raffle::ID,
-            );
-            let char_vec: Vec<char> = player_address.to_string().chars().collect();
-            let mut mul = 1;
-            for i in 0..7 {
-                mul *= u64::from(char_vec[i as usize]);
-            }
-            mul += u64::from(char_vec[7]);
-            let winner_index = mul % raffle.count;
-            raffle.winner[j as usize] = raffle.entrants[winner_index as usize];
-            raffle.entrants[winner_index as usize] = raffle.entrants[(raffle.count - 1) as usize];
-            raffle.count -= 1;
-        }
+#[derive(Accounts)]
+#[instruction(vault_bump: u8)]
+pub struct ClaimProceeds<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
 
-        Ok(())
-    }
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
 
-    /**
-     * @dev Claim reward function
-     * @Context has claimer and global_authority account
-     * raffle account and the nft ATA of claimer and global_authority.
-     * @param global_bump: the global_authority's bump
-     */
-    pub fn claim_reward(ctx: Context<ClaimReward>, global_bump: u8) -> ProgramResult {
-        let timestamp = Clock::get()?.unix_timestamp;
-        let mut raffle = ctx.accounts.raffle.load_mut()?;
+    #[account(
+        mut,
+        seeds = [PROCEEDS_SEED.as_ref(), raffle.key().as_ref()],
+        bump = vault_bump,
+    )]
+    pub proceeds_vault: AccountInfo<'info>,
 
-        if timestamp < raffle.end_timestamp {
-            return Err(RaffleError::RaffleNotEnded.into());
-        }
-        if raffle.whitelisted == 1 {
-            if raffle.winner[0] != ctx.accounts.claimer.key() {
-                return Err(RaffleError::NotWinner.into());
-            }
-            // Transfer NFT to the winner's wallet
-            let src_token_account = &mut &ctx.accounts.src_nft_token_account;
-            let dest_token_account = &mut &ctx.accounts.claimer_nft_token_account;
-            let token_program = &mut &ctx.accounts.token_program;
-            let seeds = &[GLOBAL_AUTHORITY_SEED.as_bytes(), &[global_bump]];
-            let signer = &[&seeds[..]];
-            let cpi_accounts = Transfer {
-                from: src_token_account.to_account_info().clone(),
-                to: dest_token_account.to_account_info().clone(),
-                authority: ctx.accounts.global_authority.to_account_info(),
-            };
-            token::transfer(
-                CpiContext::new_with_signer(
-                    token_program.clone().to_account_info(),
-                    cpi_accounts,
-                    signer,
-                ),
-                1,
-            )?;
-            raffle.claimed_winner[0] = 1;
-        } else {
-            for i in 0..raffle.winner_count {
-                if raffle.winner[i as usize] == ctx.accounts.claimer.key() {
-                    raffle.claimed_winner[i as usize] = 1;
-                }
-            }
-        }
-        Ok(())
-    }
-    /**
-     * @dev Withdraw NFT function
-     * @Context has claimer and global_authority account
-     * raffle account and creator's nft ATA and global_authority's nft ATA
-     * @param global_bump: global_authority's bump
-     */
-    pub fn withdraw_nft(ctx: Context<WithdrawNft>, global_bump: u8) -> ProgramResult {
-        let timestamp = Clock::get()?.unix_timestamp;
-        let mut raffle = ctx.accounts.raffle.load_mut()?;
+    pub system_program: Program<'info, System>,
+}
 
-        if timestamp < raffle.end_timestamp {
-            return Err(RaffleError::RaffleNotEnded.into());
-        }
-        if raffle.creator != ctx.accounts.claimer.key() {
-            return Err(RaffleError::NotCreator.into());
-        }
-        if raffle.count != 0 {
-            return Err(RaffleError::OtherEntrants.into());
-        }
+#[derive(Accounts)]
+pub struct CancelUnsoldRaffle<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
 
-        // Transfer NFT to the creator's wallet after the raffle ends
-        let src_token_account = &mut &ctx.accounts.src_nft_token_account;
-        let dest_token_account = &mut &ctx.accounts.claimer_nft_token_account;
-        let token_program = &mut &ctx.accounts.token_program;
-        let seeds = &[GLOBAL_AUTHORITY_SEED.as_bytes(), &[global_bump]];
-        let signer = &[&seeds[..]];
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
 
-        let cpi_accounts = Transfer {
-            from: src_token_account.to_account_info().clone(),
-            to: dest_token_account.to_account_info().clone(),
-            authority: ctx.accounts.global_authority.to_account_info(),
-        };
-        token::transfer(
-            CpiContext::new_with_signer(
-                token_program.clone().to_account_info(),
-                cpi_accounts,
-                signer,
-            ),
-            1,
-        )?;
-        raffle.whitelisted = 3;
-        Ok(())
-    }
+    // the creator's CreatorStats PDA, whether or not it has been created
+    #[account(mut)]
+    pub creator_stats: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 #[instruction(global_bump: u8)]
-pub struct Initialize<'info> {
+pub struct CancelRefundableRaffle<'info> {
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub authority: Signer<'info>,
 
     #[account(
-        init_if_needed,
         seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
         bump = global_bump,
-        payer = admin
     )]
-    pub global_authority: Account<'info, GlobalPool>,
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
 
+    // the raffle creator's CreatorStats PDA, whether or not it has been created
+    #[account(mut)]
+    pub creator_stats: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
 #[instruction(global_bump: u8)]
-pub struct CreateRaffle<'info> {
-    #[account(mut)]
-    pub admin: Signer<'info>,
+pub struct CancelRaffle<'info> {
+    pub authority: Signer<'info>,
+
     #[account(
-        mut,
         seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
         bump = global_bump,
     )]
-    pub global_authority: Account<'info, GlobalPool>,
+    pub global_authority: AccountLoader<'info, GlobalPool>,
 
-    #[account(zero)]
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+}
+
+#[derive(Accounts)]
+#[instruction(vault_bump: u8, receipt_bump: u8)]
+pub struct ClaimTicketRefund<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(mut)]
     pub raffle: AccountLoader<'info, RafflePool>,
 
     #[account(
         mut,
-        constraint = owner_temp_nft_account.mint == *nft_mint_address.to_account_info().key,
-        constraint = owner_temp_nft_account.owner == *admin.key,
+        seeds = [RECEIPT_SEED.as_ref(), raffle.key().as_ref(), buyer.key().as_ref()],
+        bump = receipt_bump,
+        close = buyer
     )]
-    pub owner_temp_nft_account: CpiAccount<'info, TokenAccount>,
+    pub receipt: Account<'info, TicketReceipt>,
 
     #[account(
         mut,
-        constraint = dest_nft_token_account.mint == *nft_mint_address.to_account_info().key,
-        constraint = dest_nft_token_account.owner == *global_authority.to_account_info().key,
+        seeds = [PROCEEDS_SEED.as_ref(), raffle.key().as_ref()],
+        bump = vault_bump,
     )]
-    pub dest_nft_token_account: CpiAccount<'info, TokenAccount>,
-
-    pub nft_mint_address: AccountInfo<'info>,
+    pub proceeds_vault: AccountInfo<'info>,
 
-    pub token_program: Program<'info, Token>,
+    #[account(mut)]
+    pub user_token_account: AccountInfo<'info>,
+    #[account(mut)]
+    pub proceeds_token_account: AccountInfo<'info>,
+    pub token_mint: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(global_bump: u8)]
-pub struct BuyTickets<'info> {
-    #[account(mut)]
-    pub buyer: Signer<'info>,
-
+#[instruction(global_bump: u8, vault_bump: u8)]
+pub struct ReleaseEscrow<'info> {
     #[account(mut)]
     pub raffle: AccountLoader<'info, RafflePool>,
 
@@ -355,92 +6672,183 @@ pub struct BuyTickets<'info> {
         seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
         bump = global_bump,
     )]
-    pub global_authority: Account<'info, GlobalPool>,
-
+    pub global_authority: AccountLoader<'info, GlobalPool>,
     #[account(mut)]
-    pub creator: AccountInfo<'info>,
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [PROCEEDS_SEED.as_ref(), raffle.key().as_ref()],
+        bump = vault_bump,
+    )]
+    pub proceeds_vault: AccountInfo<'info>,
 
     #[account(mut)]
-    pub user_token_account: AccountInfo<'info>,
+    pub creator_token_account: AccountInfo<'info>,
+    #[account(mut)]
+    pub treasury_token_account: AccountInfo<'info>,
+    #[account(mut)]
+    pub proceeds_token_account: AccountInfo<'info>,
     #[account(mut)]
     pub token_mint: AccountInfo<'info>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct RevealWinner<'info> {
+#[instruction(global_bump: u8, vault_bump: u8)]
+pub struct CloseRaffle<'info> {
     #[account(mut)]
-    pub buyer: Signer<'info>,
+    pub creator: Signer<'info>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
+    )]
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    #[account(mut, close = creator)]
     pub raffle: AccountLoader<'info, RafflePool>,
+
+    #[account(
+        mut,
+        seeds = [PROCEEDS_SEED.as_ref(), raffle.key().as_ref()],
+        bump = vault_bump,
+    )]
+    pub proceeds_vault: AccountInfo<'info>,
+
+    pub token_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(global_bump: u8)]
-pub struct ClaimReward<'info> {
+#[instruction(vault_bump: u8)]
+pub struct ClaimSolReward<'info> {
     #[account(mut)]
     pub claimer: Signer<'info>,
 
+    #[account(mut)]
+    pub raffle: AccountLoader<'info, RafflePool>,
+
     #[account(
         mut,
-        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
-        bump = global_bump,
+        seeds = [VAULT_SEED.as_ref(), raffle.key().as_ref()],
+        bump = vault_bump,
     )]
-    pub global_authority: Account<'info, GlobalPool>,
+    pub vault: AccountInfo<'info>,
+    // the raffle creator's CreatorStats PDA, whether or not it has been created
+    #[account(mut)]
+    pub creator_stats: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(vault_bump: u8)]
+pub struct WithdrawSolPrize<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
 
     #[account(mut)]
     pub raffle: AccountLoader<'info, RafflePool>,
 
     #[account(
         mut,
-        constraint = claimer_nft_token_account.mint == *nft_mint_address.to_account_info().key,
-        constraint = claimer_nft_token_account.owner == *claimer.key,
+        seeds = [VAULT_SEED.as_ref(), raffle.key().as_ref()],
+        bump = vault_bump,
+    )]
+    pub vault: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8, proposal_bump: u8)]
+pub struct ProposeEmergencyWithdraw<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
     )]
-    pub claimer_nft_token_account: CpiAccount<'info, TokenAccount>,
+    pub global_authority: AccountLoader<'info, GlobalPool>,
+
+    pub raffle: AccountLoader<'info, RafflePool>,
 
     #[account(
-        mut,
-        constraint = src_nft_token_account.mint == *nft_mint_address.to_account_info().key,
-        constraint = src_nft_token_account.owner == *global_authority.to_account_info().key,
+        init,
+        seeds = [EMERGENCY_SEED.as_ref(), raffle.key().as_ref(), source_token_account.key().as_ref()],
+        bump = proposal_bump,
+        payer = admin
     )]
-    pub src_nft_token_account: CpiAccount<'info, TokenAccount>,
+    pub proposal: Account<'info, EmergencyWithdrawal>,
 
-    pub nft_mint_address: AccountInfo<'info>,
-    pub token_program: Program<'info, Token>,
+    pub source_token_account: AccountInfo<'info>,
+    pub dest_token_account: AccountInfo<'info>,
+    pub token_mint: AccountInfo<'info>,
+
+    pub token_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-#[instruction(global_bump: u8)]
-pub struct WithdrawNft<'info> {
+#[instruction(global_bump: u8, proposal_bump: u8)]
+pub struct ExecuteEmergencyWithdraw<'info> {
     #[account(mut)]
-    pub claimer: Signer<'info>,
+    pub admin: Signer<'info>,
 
     #[account(
-        mut,
         seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
         bump = global_bump,
     )]
-    pub global_authority: Account<'info, GlobalPool>,
+    pub global_authority: AccountLoader<'info, GlobalPool>,
 
-    #[account(mut)]
     pub raffle: AccountLoader<'info, RafflePool>,
 
     #[account(
         mut,
-        constraint = claimer_nft_token_account.mint == *nft_mint_address.to_account_info().key,
-        constraint = claimer_nft_token_account.owner == *claimer.key,
+        seeds = [EMERGENCY_SEED.as_ref(), raffle.key().as_ref(), source_token_account.key().as_ref()],
+        bump = proposal_bump,
+        close = admin
     )]
-    pub claimer_nft_token_account: CpiAccount<'info, TokenAccount>,
+    pub proposal: Account<'info, EmergencyWithdrawal>,
+
+    #[account(mut)]
+    pub source_token_account: AccountInfo<'info>,
+    #[account(mut)]
+    pub dest_token_account: AccountInfo<'info>,
+    pub token_mint: AccountInfo<'info>,
+
+    pub token_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(global_bump: u8)]
+pub struct CancelEmergencyWithdraw<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
 
     #[account(
-        mut,
-        constraint = src_nft_token_account.mint == *nft_mint_address.to_account_info().key,
-        constraint = src_nft_token_account.owner == *global_authority.to_account_info().key,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_ref()],
+        bump = global_bump,
     )]
-    pub src_nft_token_account: CpiAccount<'info, TokenAccount>,
+    pub global_authority: AccountLoader<'info, GlobalPool>,
 
-    pub nft_mint_address: AccountInfo<'info>,
-    pub token_program: Program<'info, Token>,
+    #[account(mut, close = admin)]
+    pub proposal: Account<'info, EmergencyWithdrawal>,
+}
+
+#[derive(Accounts)]
+pub struct ViewRaffle<'info> {
+    pub raffle: AccountLoader<'info, RafflePool>,
+}
+
+#[derive(Accounts)]
+pub struct ViewOdds<'info> {
+    pub raffle: AccountLoader<'info, RafflePool>,
+    // the wallet's TicketRecord PDA, whether or not it has been created
+    pub ticket_record: AccountInfo<'info>,
 }
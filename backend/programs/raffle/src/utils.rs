@@ -1,5 +1,18 @@
-use anchor_lang::prelude::*;
-use solana_program::program::{invoke, invoke_signed};
+use anchor_lang::{prelude::*, Discriminator};
+use solana_program::{
+    hash,
+    instruction::{AccountMeta, Instruction},
+    program::{invoke, invoke_signed},
+    system_instruction,
+    sysvar::{
+        self,
+        instructions::{load_current_index_checked, load_instruction_at_checked},
+    },
+};
+
+use crate::account::*;
+use crate::constants::*;
+use crate::error::RaffleError;
 
 // transfer sol
 pub fn sol_transfer_with_signer<'a>(
@@ -22,3 +35,1477 @@ pub fn sol_transfer_user<'a>(
     let ix = solana_program::system_instruction::transfer(source.key, destination.key, amount);
     invoke(&ix, &[source, destination, system_program])
 }
+
+// validate the arguments shared by the raffle creation instructions
+pub fn validate_raffle_args(
+    timestamp: i64,
+    end_timestamp: i64,
+    winner_count: u64,
+    commitment: &[u8; 32],
+    randomness_mode: u64,
+) -> Result<(), ProgramError> {
+    if winner_count > MAX_WINNERS as u64 {
+        return Err(RaffleError::TooManyWinners.into());
+    }
+    if timestamp > end_timestamp {
+        return Err(RaffleError::EndTimeError.into());
+    }
+    match RandomnessMode::from_u64(randomness_mode)? {
+        RandomnessMode::Switchboard => {
+            return Err(RaffleError::RandomnessModeUnsupported.into());
+        }
+        RandomnessMode::CommitReveal => {
+            if *commitment == [0; 32] {
+                return Err(RaffleError::InvalidCommitment.into());
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// the burnt and treasury shares of token payments cannot exceed the payment
+pub fn validate_token_split(burn_bps: u64, treasury_bps: u64) -> Result<(), ProgramError> {
+    let total_bps = burn_bps.checked_add(treasury_bps);
+    if total_bps.map_or(true, |bps| bps > MAX_FEE_BPS) {
+        return Err(RaffleError::InvalidFee.into());
+    }
+    Ok(())
+}
+
+// whether the admin has allowlisted the wallet, given the address of its FeeExemption PDA
+pub fn is_fee_exempt(fee_exemption: &AccountInfo, wallet: &Pubkey) -> Result<bool, ProgramError> {
+    let (address, _) =
+        Pubkey::find_program_address(&[FEE_EXEMPTION_SEED.as_ref(), wallet.as_ref()], &crate::ID);
+    if fee_exemption.key() != address {
+        return Err(RaffleError::InvalidFeeExemption.into());
+    }
+    Ok(*fee_exemption.owner == crate::ID && !fee_exemption.data_is_empty())
+}
+
+// charge the protocol's raffle creation fee to the treasury unless the payer is fee exempt
+pub fn pay_creation_fee<'a>(
+    global_authority: &mut GlobalPool,
+    payer: AccountInfo<'a>,
+    treasury: AccountInfo<'a>,
+    fee_exemption: &AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+) -> Result<(), ProgramError> {
+    if global_authority.creation_fee == 0 || is_fee_exempt(fee_exemption, &payer.key())? {
+        return Ok(());
+    }
+    if treasury.key() != global_authority.treasury {
+        return Err(RaffleError::InvalidTreasury.into());
+    }
+    let creation_fee = global_authority.creation_fee;
+    sol_transfer_user(payer, treasury, system_program, creation_fee)?;
+    global_authority.record_fees(creation_fee)
+}
+
+// the preamble of every create handler: refuse while the protocol is paused,
+// charge the creation fee and count the new raffle
+pub fn start_raffle_creation<'a>(
+    global_authority: &AccountLoader<'a, GlobalPool>,
+    payer: AccountInfo<'a>,
+    treasury: AccountInfo<'a>,
+    fee_exemption: &AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+) -> ProgramResult {
+    let mut global_authority = global_authority.load_mut()?;
+    global_authority.check_not_paused()?;
+    pay_creation_fee(
+        &mut global_authority,
+        payer,
+        treasury,
+        fee_exemption,
+        system_program,
+    )?;
+    global_authority.record_raffle()
+}
+
+// count a raffle created by the creator in their CreatorStats
+pub fn record_raffle_created<'a>(
+    creator: &AccountInfo<'a>,
+    creator_stats: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    let mut stats = load_creator_stats(creator.key, creator, creator_stats, system_program)?;
+    stats.raffles_created = stats
+        .raffles_created
+        .checked_add(1)
+        .ok_or(RaffleError::AmountOverflow)?;
+    save_creator_stats(creator_stats, &stats)
+}
+
+// count the prizes delivered to a winner in the creator's CreatorStats
+pub fn record_prizes_delivered<'a>(
+    creator: &Pubkey,
+    payer: &AccountInfo<'a>,
+    creator_stats: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    prizes: u64,
+) -> ProgramResult {
+    let mut stats = load_creator_stats(creator, payer, creator_stats, system_program)?;
+    stats.prizes_delivered = stats
+        .prizes_delivered
+        .checked_add(prizes)
+        .ok_or(RaffleError::AmountOverflow)?;
+    save_creator_stats(creator_stats, &stats)
+}
+
+// move lamports out of an account owned by this program
+pub fn sol_transfer_from_program_account(
+    source: &AccountInfo,
+    destination: &AccountInfo,
+    amount: u64,
+) -> Result<(), ProgramError> {
+    **source.try_borrow_mut_lamports()? = source
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(RaffleError::InvalidCalculation)?;
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(amount)
+        .ok_or(RaffleError::InvalidCalculation)?;
+    Ok(())
+}
+
+// pay the escrowed crank tip to the caller once the draw is complete
+pub fn pay_crank_tip(
+    raffle: &mut RafflePool,
+    raffle_info: &AccountInfo,
+    caller: &AccountInfo,
+) -> Result<(), ProgramError> {
+    if raffle.revealed != 1 || raffle.crank_tip == 0 {
+        return Ok(());
+    }
+    sol_transfer_from_program_account(raffle_info, caller, raffle.crank_tip)?;
+    raffle.crank_tip = 0;
+    Ok(())
+}
+
+// request randomness from the ORAO VRF program for the given seed
+pub fn orao_request<'a>(
+    orao_program: AccountInfo<'a>,
+    payer: AccountInfo<'a>,
+    network_state: AccountInfo<'a>,
+    treasury: AccountInfo<'a>,
+    request: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    seed: [u8; 32],
+) -> Result<(), ProgramError> {
+    let mut data = hash::hash(b"global:request").to_bytes()[..8].to_vec();
+    data.extend_from_slice(&seed);
+    let ix = Instruction {
+        program_id: *orao_program.key,
+        accounts: vec![
+            AccountMeta::new(*payer.key, true),
+            AccountMeta::new(*network_state.key, false),
+            AccountMeta::new(*treasury.key, false),
+            AccountMeta::new(*request.key, false),
+            AccountMeta::new_readonly(*system_program.key, false),
+        ],
+        data,
+    };
+    invoke(
+        &ix,
+        &[
+            payer,
+            network_state,
+            treasury,
+            request,
+            system_program,
+            orao_program,
+        ],
+    )
+}
+
+// read the fulfilled 64 byte randomness from the raffle's ORAO randomness account
+// layout: discriminator(8) + seed(32) + randomness(64) + responses
+pub fn orao_randomness(request: &AccountInfo, expected: &Pubkey) -> Result<[u8; 64], ProgramError> {
+    if *expected == Pubkey::default() || request.key != expected {
+        return Err(RaffleError::InvalidRandomnessAccount.into());
+    }
+    if *request.owner != ORAO_VRF_PROGRAM_ID.parse::<Pubkey>().unwrap() {
+        return Err(RaffleError::InvalidVrfProgram.into());
+    }
+    let data = request.try_borrow_data()?;
+    if data.len() < 104 {
+        return Err(RaffleError::InvalidRandomnessAccount.into());
+    }
+    let mut randomness = [0u8; 64];
+    randomness.copy_from_slice(&data[40..104]);
+    if randomness.iter().all(|byte| *byte == 0) {
+        return Err(RaffleError::RandomnessNotFulfilled.into());
+    }
+    Ok(randomness)
+}
+
+// read the most recent slot hash from the SlotHashes sysvar
+// layout: entries len(8) + [slot(8) + hash(32)]
+pub fn recent_slot_hash(slot_hashes: &AccountInfo) -> Result<[u8; 32], ProgramError> {
+    if *slot_hashes.key != sysvar::slot_hashes::ID {
+        return Err(RaffleError::InvalidSlotHashes.into());
+    }
+    let data = slot_hashes.try_borrow_data()?;
+    if data.len() < 48 {
+        return Err(RaffleError::InvalidSlotHashes.into());
+    }
+    let mut slot_hash = [0u8; 32];
+    slot_hash.copy_from_slice(&data[16..48]);
+    Ok(slot_hash)
+}
+
+// read the hash of the given slot from the SlotHashes sysvar, which keeps the last 512 slots
+// layout: entries len(8) + [slot(8) + hash(32)], newest slot first
+pub fn slot_hash_at(slot_hashes: &AccountInfo, slot: u64) -> Result<[u8; 32], ProgramError> {
+    if *slot_hashes.key != sysvar::slot_hashes::ID {
+        return Err(RaffleError::InvalidSlotHashes.into());
+    }
+    let data = slot_hashes.try_borrow_data()?;
+    if data.len() < 8 {
+        return Err(RaffleError::InvalidSlotHashes.into());
+    }
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&data[0..8]);
+    let entries = u64::from_le_bytes(bytes) as usize;
+    for i in 0..entries {
+        let entry = data
+            .get(8 + 40 * i..48 + 40 * i)
+            .ok_or(RaffleError::InvalidSlotHashes)?;
+        bytes.copy_from_slice(&entry[0..8]);
+        let entry_slot = u64::from_le_bytes(bytes);
+        if entry_slot == slot {
+            let mut slot_hash = [0u8; 32];
+            slot_hash.copy_from_slice(&entry[8..40]);
+            return Ok(slot_hash);
+        }
+        if entry_slot < slot {
+            break;
+        }
+    }
+    Err(RaffleError::SlotHashNotFound.into())
+}
+
+pub struct CnftLeaf {
+    pub root: [u8; 32],
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub nonce: u64,
+    pub index: u32,
+}
+
+// transfer a compressed NFT with the Bubblegum program
+// accounts: tree_authority, leaf_owner, leaf_delegate, new_leaf_owner, merkle_tree,
+// log_wrapper, compression_program, system_program followed by the proof path
+pub fn bubblegum_transfer<'a>(
+    bubblegum_program: &AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+    leaf: &CnftLeaf,
+    signers: &[&[&[u8]]],
+) -> Result<(), ProgramError> {
+    let mut data = hash::hash(b"global:transfer").to_bytes()[..8].to_vec();
+    data.extend_from_slice(&leaf.root);
+    data.extend_from_slice(&leaf.data_hash);
+    data.extend_from_slice(&leaf.creator_hash);
+    data.extend_from_slice(&leaf.nonce.to_le_bytes());
+    data.extend_from_slice(&leaf.index.to_le_bytes());
+
+    let metas = accounts
+        .iter()
+        .enumerate()
+        .map(|(i, account)| match i {
+            1 => AccountMeta::new_readonly(*account.key, true),
+            4 => AccountMeta::new(*account.key, false),
+            _ => AccountMeta::new_readonly(*account.key, false),
+        })
+        .collect();
+    let ix = Instruction {
+        program_id: *bubblegum_program.key,
+        accounts: metas,
+        data,
+    };
+    let mut infos = accounts.to_vec();
+    infos.push(bubblegum_program.clone());
+    invoke_signed(&ix, &infos, signers)
+}
+
+pub struct PnftTransfer<'a> {
+    pub token: AccountInfo<'a>,
+    pub token_owner: AccountInfo<'a>,
+    pub destination: AccountInfo<'a>,
+    pub destination_owner: AccountInfo<'a>,
+    pub mint: AccountInfo<'a>,
+    pub payer: AccountInfo<'a>,
+    pub system_program: AccountInfo<'a>,
+    pub token_program: AccountInfo<'a>,
+}
+
+impl<'a> PnftTransfer<'a> {
+    // transfer a programmable NFT with the Token Metadata program, the token owner signs
+    // pnft_accounts: metadata, edition, owner_token_record, destination_token_record,
+    // sysvar_instructions, spl_ata_program, authorization_rules_program,
+    // authorization_rules, token_metadata_program
+    pub fn invoke_signed(
+        self,
+        pnft_accounts: &[AccountInfo<'a>],
+        signers: &[&[&[u8]]],
+    ) -> Result<(), ProgramError> {
+        if pnft_accounts.len() != PNFT_ACCOUNTS_LEN {
+            return Err(RaffleError::InvalidPrizeAccounts.into());
+        }
+        let token_metadata_program = &pnft_accounts[8];
+        if *token_metadata_program.key != TOKEN_METADATA_PROGRAM_ID.parse::<Pubkey>().unwrap() {
+            return Err(RaffleError::InvalidPrizeAccounts.into());
+        }
+
+        let accounts = vec![
+            self.token,
+            self.token_owner.clone(),
+            self.destination,
+            self.destination_owner,
+            self.mint,
+            pnft_accounts[0].clone(),
+            pnft_accounts[1].clone(),
+            pnft_accounts[2].clone(),
+            pnft_accounts[3].clone(),
+            self.token_owner,
+            self.payer,
+            self.system_program,
+            pnft_accounts[4].clone(),
+            self.token_program,
+            pnft_accounts[5].clone(),
+            pnft_accounts[6].clone(),
+            pnft_accounts[7].clone(),
+        ];
+        let metas = accounts
+            .iter()
+            .enumerate()
+            .map(|(i, account)| match i {
+                0 | 2 | 5 | 7 | 8 => AccountMeta::new(*account.key, false),
+                9 => AccountMeta::new_readonly(*account.key, true),
+                10 => AccountMeta::new(*account.key, true),
+                _ => AccountMeta::new_readonly(*account.key, false),
+            })
+            .collect();
+
+        // Transfer instruction, TransferArgs::V1 { amount: 1, authorization_data: None }
+        let mut data = vec![49, 0];
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.push(0);
+
+        let ix = Instruction {
+            program_id: *token_metadata_program.key,
+            accounts: metas,
+            data,
+        };
+        let mut infos = accounts;
+        infos.push(token_metadata_program.clone());
+        invoke_signed(&ix, &infos, signers)
+    }
+}
+
+pub struct CoreTransfer<'a> {
+    pub asset: AccountInfo<'a>,
+    pub collection: AccountInfo<'a>,
+    pub payer: AccountInfo<'a>,
+    pub authority: AccountInfo<'a>,
+    pub new_owner: AccountInfo<'a>,
+    pub system_program: AccountInfo<'a>,
+}
+
+impl<'a> CoreTransfer<'a> {
+    // transfer an MPL Core asset, collection is the Core program itself when the asset has none
+    pub fn invoke_signed(
+        self,
+        core_program: &AccountInfo<'a>,
+        signers: &[&[&[u8]]],
+    ) -> Result<(), ProgramError> {
+        if *core_program.key != MPL_CORE_PROGRAM_ID.parse::<Pubkey>().unwrap() {
+            return Err(RaffleError::InvalidCoreProgram.into());
+        }
+        let ix = Instruction {
+            program_id: *core_program.key,
+            accounts: vec![
+                AccountMeta::new(*self.asset.key, false),
+                AccountMeta::new_readonly(*self.collection.key, false),
+                AccountMeta::new(*self.payer.key, true),
+                AccountMeta::new_readonly(*self.authority.key, true),
+                AccountMeta::new_readonly(*self.new_owner.key, false),
+                AccountMeta::new_readonly(*self.system_program.key, false),
+                AccountMeta::new_readonly(*core_program.key, false),
+            ],
+            // TransferV1 { compression_proof: None }
+            data: vec![14, 0],
+        };
+        invoke_signed(
+            &ix,
+            &[
+                self.asset,
+                self.collection,
+                self.payer,
+                self.authority,
+                self.new_owner,
+                self.system_program,
+                core_program.clone(),
+            ],
+            signers,
+        )
+    }
+}
+
+// check the token program is SPL Token or Token-2022 and owns the mint
+pub fn check_token_program(token_program: &AccountInfo, mint: &AccountInfo) -> ProgramResult {
+    if *token_program.key != spl_token::id()
+        && *token_program.key != TOKEN_2022_PROGRAM_ID.parse::<Pubkey>().unwrap()
+    {
+        return Err(RaffleError::InvalidTokenProgram.into());
+    }
+    if mint.owner != token_program.key {
+        return Err(RaffleError::InvalidTokenProgram.into());
+    }
+    Ok(())
+}
+
+// check a token account's mint and owner, Token-2022 accounts may carry extensions past the base layout
+pub fn check_token_account(
+    account: &AccountInfo,
+    token_program: &AccountInfo,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> ProgramResult {
+    if account.owner != token_program.key {
+        return Err(RaffleError::InvalidTokenAccount.into());
+    }
+    let data = account.try_borrow_data()?;
+    if data.len() < 165 || data[0..32] != mint.to_bytes() || data[32..64] != owner.to_bytes() {
+        return Err(RaffleError::InvalidTokenAccount.into());
+    }
+    Ok(())
+}
+
+// read the decimals of an SPL Token or Token-2022 mint
+pub fn token_decimals(mint: &AccountInfo) -> Result<u8, ProgramError> {
+    let data = mint.try_borrow_data()?;
+    let decimals = data.get(44).ok_or(RaffleError::InvalidTokenProgram)?;
+    Ok(*decimals)
+}
+
+pub struct TokenTransfer<'a> {
+    pub token_program: AccountInfo<'a>,
+    pub source: AccountInfo<'a>,
+    pub mint: AccountInfo<'a>,
+    pub destination: AccountInfo<'a>,
+    pub authority: AccountInfo<'a>,
+}
+
+impl<'a> TokenTransfer<'a> {
+    // transfer_checked through SPL Token or Token-2022
+    // hook_accounts are the extra accounts of the mint's transfer hook, if it has one
+    pub fn invoke_signed(
+        self,
+        amount: u64,
+        hook_accounts: &[AccountInfo<'a>],
+        signers: &[&[&[u8]]],
+    ) -> Result<(), ProgramError> {
+        let decimals = token_decimals(&self.mint)?;
+
+        let mut metas = vec![
+            AccountMeta::new(*self.source.key, false),
+            AccountMeta::new_readonly(*self.mint.key, false),
+            AccountMeta::new(*self.destination.key, false),
+            AccountMeta::new_readonly(*self.authority.key, true),
+        ];
+        for account in hook_accounts {
+            metas.push(AccountMeta {
+                pubkey: *account.key,
+                is_signer: false,
+                is_writable: account.is_writable,
+            });
+        }
+        let mut data = vec![12];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.push(decimals);
+
+        let ix = Instruction {
+            program_id: *self.token_program.key,
+            accounts: metas,
+            data,
+        };
+        let mut infos = vec![self.source, self.mint, self.destination, self.authority];
+        infos.extend_from_slice(hook_accounts);
+        infos.push(self.token_program);
+        invoke_signed(&ix, &infos, signers)
+    }
+}
+
+// check the holder's SPL Token or Token-2022 account holds at least one token of the mint
+pub fn check_token_holder(
+    token_account: &AccountInfo,
+    holder: &Pubkey,
+    mint: &Pubkey,
+) -> Result<bool, ProgramError> {
+    if *token_account.owner != spl_token::id()
+        && *token_account.owner != TOKEN_2022_PROGRAM_ID.parse::<Pubkey>().unwrap()
+    {
+        return Ok(false);
+    }
+    let data = token_account.try_borrow_data()?;
+    if data.len() < 165 || data[0..32] != mint.to_bytes() {
+        return Ok(false);
+    }
+    let mut amount = [0u8; 8];
+    amount.copy_from_slice(&data[64..72]);
+    Ok(data[32..64] == holder.to_bytes() && u64::from_le_bytes(amount) >= 1)
+}
+
+// check the holder owns an NFT whose metadata has the given verified collection
+pub fn check_collection_holder(
+    token_account: &AccountInfo,
+    metadata: &AccountInfo,
+    holder: &Pubkey,
+    collection: &Pubkey,
+) -> ProgramResult {
+    if *token_account.owner != spl_token::id()
+        && *token_account.owner != TOKEN_2022_PROGRAM_ID.parse::<Pubkey>().unwrap()
+    {
+        return Err(RaffleError::InvalidHolderNft.into());
+    }
+    let data = token_account.try_borrow_data()?;
+    if data.len() < 165 || data[32..64] != holder.to_bytes() || data[64..72] != 1u64.to_le_bytes() {
+        return Err(RaffleError::InvalidHolderNft.into());
+    }
+    let mint = Pubkey::new(&data[0..32]);
+
+    if verified_collection(&mint, metadata)? != Some(*collection) {
+        return Err(RaffleError::InvalidHolderNft.into());
+    }
+    Ok(())
+}
+
+// read the verified collection from the mint's Metaplex metadata, None if it has none
+pub fn verified_collection(
+    mint: &Pubkey,
+    metadata: &AccountInfo,
+) -> Result<Option<Pubkey>, ProgramError> {
+    let token_metadata_program = TOKEN_METADATA_PROGRAM_ID.parse::<Pubkey>().unwrap();
+    let (metadata_key, _) = Pubkey::find_program_address(
+        &[b"metadata", token_metadata_program.as_ref(), mint.as_ref()],
+        &token_metadata_program,
+    );
+    if *metadata.key != metadata_key || *metadata.owner != token_metadata_program {
+        return Err(RaffleError::InvalidMetadata.into());
+    }
+
+    // Metadata: key, update_authority, mint, name, symbol, uri, seller_fee_basis_points,
+    // creators option, primary_sale_happened, is_mutable, edition_nonce option,
+    // token_standard option, collection option (verified, key)
+    let data = metadata.try_borrow_data()?;
+    let mut offset = 1 + 32 + 32;
+    for _ in 0..3 {
+        let len = data
+            .get(offset..offset + 4)
+            .ok_or(RaffleError::InvalidMetadata)?;
+        offset += 4 + u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize;
+    }
+    offset += 2;
+    if *data.get(offset).ok_or(RaffleError::InvalidMetadata)? == 1 {
+        let len = data
+            .get(offset + 1..offset + 5)
+            .ok_or(RaffleError::InvalidMetadata)?;
+        offset += 4 + u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize * 34;
+    }
+    offset += 1 + 2;
+    for _ in 0..2 {
+        if *data.get(offset).ok_or(RaffleError::InvalidMetadata)? == 1 {
+            offset += 1;
+        }
+        offset += 1;
+    }
+    let verified_collection = data
+        .get(offset..offset + 34)
+        .ok_or(RaffleError::InvalidMetadata)?;
+    if verified_collection[0] != 1 || verified_collection[1] != 1 {
+        return Ok(None);
+    }
+    Ok(Some(Pubkey::new(&verified_collection[2..34])))
+}
+
+// read the amount the owner has staked from their staking program stake account
+pub fn staked_amount(
+    stake_account: &AccountInfo,
+    staking_program: &Pubkey,
+    owner: &Pubkey,
+) -> Result<u64, ProgramError> {
+    let (stake_key, _) =
+        Pubkey::find_program_address(&[STAKE_SEED.as_bytes(), owner.as_ref()], staking_program);
+    if *stake_account.key != stake_key || stake_account.owner != staking_program {
+        return Err(RaffleError::InvalidStakeAccount.into());
+    }
+    let data = stake_account.try_borrow_data()?;
+    if data.len() < 48 || data[8..40] != owner.to_bytes() {
+        return Err(RaffleError::InvalidStakeAccount.into());
+    }
+    let mut amount = [0u8; 8];
+    amount.copy_from_slice(&data[40..48]);
+    Ok(u64::from_le_bytes(amount))
+}
+
+// burn_checked through SPL Token or Token-2022
+pub fn token_burn<'a>(
+    token_program: AccountInfo<'a>,
+    account: AccountInfo<'a>,
+    mint: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    amount: u64,
+    signers: &[&[&[u8]]],
+) -> Result<(), ProgramError> {
+    let mut data = vec![15];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.push(token_decimals(&mint)?);
+
+    let ix = Instruction {
+        program_id: *token_program.key,
+        accounts: vec![
+            AccountMeta::new(*account.key, false),
+            AccountMeta::new(*mint.key, false),
+            AccountMeta::new_readonly(*authority.key, true),
+        ],
+        data,
+    };
+    invoke_signed(&ix, &[account, mint, authority, token_program], signers)
+}
+
+// check the token account belongs to the owner and is an empty non-native account
+// layout: mint(32) + owner(32) + amount(8) + delegate(36) + state(1) + is_native(4 + 8)
+pub fn check_closable_token_account(account: &AccountInfo, owner: &Pubkey) -> ProgramResult {
+    let data = account.try_borrow_data()?;
+    if data.len() < 165 || data[32..64] != owner.to_bytes() {
+        return Err(RaffleError::InvalidTokenAccount.into());
+    }
+    if data[64..72] != 0u64.to_le_bytes() || data[109..113] != [0u8; 4] {
+        return Err(RaffleError::TokenAccountNotEmpty.into());
+    }
+    Ok(())
+}
+
+// close an empty token account through SPL Token or Token-2022, sending its rent to destination
+pub fn token_close<'a>(
+    token_program: AccountInfo<'a>,
+    account: AccountInfo<'a>,
+    destination: AccountInfo<'a>,
+    owner: AccountInfo<'a>,
+    signers: &[&[&[u8]]],
+) -> Result<(), ProgramError> {
+    let ix = Instruction {
+        program_id: *token_program.key,
+        accounts: vec![
+            AccountMeta::new(*account.key, false),
+            AccountMeta::new(*destination.key, false),
+            AccountMeta::new_readonly(*owner.key, true),
+        ],
+        data: vec![9],
+    };
+    invoke_signed(&ix, &[account, destination, owner, token_program], signers)
+}
+
+// the amount to send so the recipient receives the given net amount
+// after the Token-2022 mint's transfer fee for the epoch, if it has one
+// layout: base mint padded to 165 + account type(1) + extensions [type(2) + len(2) + value]
+// TransferFeeConfig(1): authorities(64) + withheld(8) + older and newer fees
+// each fee: epoch(8) + maximum_fee(8) + basis_points(2)
+pub fn gross_transfer_amount(
+    mint: &AccountInfo,
+    net: u64,
+    epoch: u64,
+) -> Result<u64, ProgramError> {
+    if *mint.owner != TOKEN_2022_PROGRAM_ID.parse::<Pubkey>().unwrap() {
+        return Ok(net);
+    }
+    let data = mint.try_borrow_data()?;
+    let mut offset = 166;
+    while offset + 4 <= data.len() {
+        let extension_type = u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let len = u16::from_le_bytes([data[offset + 2], data[offset + 3]]) as usize;
+        offset += 4;
+        if extension_type == 1 {
+            let config = data
+                .get(offset..offset + 108)
+                .ok_or(RaffleError::InvalidPaymentMint)?;
+            // the newer fee applies from its epoch on
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&config[90..98]);
+            let fee = if epoch >= u64::from_le_bytes(bytes) {
+                &config[90..108]
+            } else {
+                &config[72..90]
+            };
+            bytes.copy_from_slice(&fee[8..16]);
+            let maximum_fee = u64::from_le_bytes(bytes);
+            let basis_points = u16::from_le_bytes([fee[16], fee[17]]) as u128;
+            if basis_points == 0 || net == 0 {
+                return Ok(net);
+            }
+            // uncapped mints set maximum_fee to u64::MAX, so the cap is added in u128
+            let capped = net as u128 + maximum_fee as u128;
+            let gross = if basis_points == MAX_FEE_BPS as u128 {
+                capped
+            } else {
+                let gross =
+                    (net as u128 * MAX_FEE_BPS as u128 + MAX_FEE_BPS as u128 - basis_points - 1)
+                        / (MAX_FEE_BPS as u128 - basis_points);
+                std::cmp::min(gross, capped)
+            };
+            if gross > u64::MAX as u128 {
+                return Err(RaffleError::AmountOverflow.into());
+            }
+            return Ok(gross as u64);
+        }
+        offset += len;
+    }
+    Ok(net)
+}
+
+// read the creators and their royalty shares from the mint's Metaplex metadata
+// Metadata: key, update_authority, mint, name, symbol, uri, seller_fee_basis_points,
+// creators option [address, verified, share]
+pub fn metadata_creators(
+    metadata: &AccountInfo,
+    mint: &Pubkey,
+) -> Result<Vec<(Pubkey, u8)>, ProgramError> {
+    let token_metadata_program = TOKEN_METADATA_PROGRAM_ID.parse::<Pubkey>().unwrap();
+    let (metadata_key, _) = Pubkey::find_program_address(
+        &[b"metadata", token_metadata_program.as_ref(), mint.as_ref()],
+        &token_metadata_program,
+    );
+    if *metadata.key != metadata_key || *metadata.owner != token_metadata_program {
+        return Err(RaffleError::InvalidRoyalty.into());
+    }
+
+    let data = metadata.try_borrow_data()?;
+    let mut offset = 1 + 32 + 32;
+    for _ in 0..3 {
+        let len = data
+            .get(offset..offset + 4)
+            .ok_or(RaffleError::InvalidRoyalty)?;
+        offset += 4 + u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize;
+    }
+    offset += 2;
+    let mut creators = vec![];
+    if *data.get(offset).ok_or(RaffleError::InvalidRoyalty)? == 1 {
+        let len = data
+            .get(offset + 1..offset + 5)
+            .ok_or(RaffleError::InvalidRoyalty)?;
+        offset += 5;
+        for _ in 0..u32::from_le_bytes([len[0], len[1], len[2], len[3]]) {
+            let creator = data
+                .get(offset..offset + 34)
+                .ok_or(RaffleError::InvalidRoyalty)?;
+            creators.push((Pubkey::new(&creator[0..32]), creator[33]));
+            offset += 34;
+        }
+    }
+    Ok(creators)
+}
+
+// read the supply and max supply of the mint's master edition
+pub fn master_edition_supply(
+    master_edition: &AccountInfo,
+    mint: &Pubkey,
+) -> Result<(u64, Option<u64>), ProgramError> {
+    let token_metadata_program = TOKEN_METADATA_PROGRAM_ID.parse::<Pubkey>().unwrap();
+    let (edition_key, _) = Pubkey::find_program_address(
+        &[
+            b"metadata",
+            token_metadata_program.as_ref(),
+            mint.as_ref(),
+            b"edition",
+        ],
+        &token_metadata_program,
+    );
+    if *master_edition.key != edition_key || *master_edition.owner != token_metadata_program {
+        return Err(RaffleError::InvalidMasterEdition.into());
+    }
+
+    // MasterEditionV2: key, supply, max_supply option
+    let data = master_edition.try_borrow_data()?;
+    if data.len() < 18 || data[0] != 6 {
+        return Err(RaffleError::InvalidMasterEdition.into());
+    }
+    let mut supply = [0u8; 8];
+    supply.copy_from_slice(&data[1..9]);
+    let max_supply = if data[9] == 1 {
+        let mut max_supply = [0u8; 8];
+        max_supply.copy_from_slice(&data[10..18]);
+        Some(u64::from_le_bytes(max_supply))
+    } else {
+        None
+    };
+    Ok((u64::from_le_bytes(supply), max_supply))
+}
+
+pub struct EditionPrint<'a> {
+    pub new_metadata: AccountInfo<'a>,
+    pub new_edition: AccountInfo<'a>,
+    pub master_edition: AccountInfo<'a>,
+    pub new_mint: AccountInfo<'a>,
+    pub edition_mark: AccountInfo<'a>,
+    pub new_mint_authority: AccountInfo<'a>,
+    pub payer: AccountInfo<'a>,
+    pub token_account_owner: AccountInfo<'a>,
+    pub token_account: AccountInfo<'a>,
+    pub update_authority: AccountInfo<'a>,
+    pub metadata: AccountInfo<'a>,
+    pub token_program: AccountInfo<'a>,
+    pub system_program: AccountInfo<'a>,
+    pub rent: AccountInfo<'a>,
+}
+
+impl<'a> EditionPrint<'a> {
+    // print a numbered edition from a master edition held by the token account owner
+    pub fn invoke_signed(
+        self,
+        token_metadata_program: &AccountInfo<'a>,
+        edition: u64,
+        signers: &[&[&[u8]]],
+    ) -> Result<(), ProgramError> {
+        if *token_metadata_program.key != TOKEN_METADATA_PROGRAM_ID.parse::<Pubkey>().unwrap() {
+            return Err(RaffleError::InvalidMasterEdition.into());
+        }
+        let accounts = vec![
+            self.new_metadata,
+            self.new_edition,
+            self.master_edition,
+            self.new_mint,
+            self.edition_mark,
+            self.new_mint_authority,
+            self.payer,
+            self.token_account_owner,
+            self.token_account,
+            self.update_authority,
+            self.metadata,
+            self.token_program,
+            self.system_program,
+            self.rent,
+        ];
+        let metas = accounts
+            .iter()
+            .enumerate()
+            .map(|(i, account)| match i {
+                0..=4 => AccountMeta::new(*account.key, false),
+                5 | 7 => AccountMeta::new_readonly(*account.key, true),
+                6 => AccountMeta::new(*account.key, true),
+                _ => AccountMeta::new_readonly(*account.key, false),
+            })
+            .collect();
+
+        // MintNewEditionFromMasterEditionViaToken { edition }
+        let mut data = vec![11];
+        data.extend_from_slice(&edition.to_le_bytes());
+
+        let ix = Instruction {
+            program_id: *token_metadata_program.key,
+            accounts: metas,
+            data,
+        };
+        let mut infos = accounts;
+        infos.push(token_metadata_program.clone());
+        invoke_signed(&ix, &infos, signers)
+    }
+}
+
+pub struct DelegatedFreeze<'a> {
+    pub delegate: AccountInfo<'a>,
+    pub token_account: AccountInfo<'a>,
+    pub edition: AccountInfo<'a>,
+    pub mint: AccountInfo<'a>,
+    pub token_program: AccountInfo<'a>,
+}
+
+impl<'a> DelegatedFreeze<'a> {
+    // freeze or thaw an NFT in its owner's wallet as the token account's delegate
+    pub fn invoke_signed(
+        self,
+        token_metadata_program: &AccountInfo<'a>,
+        thaw: bool,
+        signers: &[&[&[u8]]],
+    ) -> Result<(), ProgramError> {
+        if *token_metadata_program.key != TOKEN_METADATA_PROGRAM_ID.parse::<Pubkey>().unwrap() {
+            return Err(RaffleError::InvalidPrizeAccounts.into());
+        }
+        // FreezeDelegatedAccount or ThawDelegatedAccount
+        let ix = Instruction {
+            program_id: *token_metadata_program.key,
+            accounts: vec![
+                AccountMeta::new(*self.delegate.key, true),
+                AccountMeta::new(*self.token_account.key, false),
+                AccountMeta::new_readonly(*self.edition.key, false),
+                AccountMeta::new_readonly(*self.mint.key, false),
+                AccountMeta::new_readonly(*self.token_program.key, false),
+            ],
+            data: vec![if thaw { 27 } else { 26 }],
+        };
+        invoke_signed(
+            &ix,
+            &[
+                self.delegate,
+                self.token_account,
+                self.edition,
+                self.mint,
+                self.token_program,
+                token_metadata_program.clone(),
+            ],
+            signers,
+        )
+    }
+}
+
+// read a fresh and confident aggregate price from a Pyth price account
+pub fn pyth_price(feed: &AccountInfo, timestamp: i64) -> Result<(u64, i32), ProgramError> {
+    if *feed.owner != PYTH_PROGRAM_ID.parse::<Pubkey>().unwrap() {
+        return Err(RaffleError::InvalidPriceFeed.into());
+    }
+    let data = feed.try_borrow_data()?;
+    // magic, price account type and trading status
+    if data.len() < 240
+        || data[0..4] != 0xa1b2c3d4u32.to_le_bytes()
+        || data[8..12] != 3u32.to_le_bytes()
+        || data[224..228] != 1u32.to_le_bytes()
+    {
+        return Err(RaffleError::InvalidPriceFeed.into());
+    }
+
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&data[96..104]);
+    if timestamp - i64::from_le_bytes(bytes) > MAX_PRICE_AGE {
+        return Err(RaffleError::StalePrice.into());
+    }
+    bytes.copy_from_slice(&data[208..216]);
+    let price = i64::from_le_bytes(bytes);
+    if price <= 0 {
+        return Err(RaffleError::InvalidPriceFeed.into());
+    }
+    bytes.copy_from_slice(&data[216..224]);
+    let conf = u64::from_le_bytes(bytes);
+    if conf as u128 * 10000 > price as u128 * MAX_PRICE_CONF_BPS as u128 {
+        return Err(RaffleError::PriceUncertain.into());
+    }
+    let mut expo = [0u8; 4];
+    expo.copy_from_slice(&data[20..24]);
+    Ok((price as u64, i32::from_le_bytes(expo)))
+}
+
+// convert a USD cent amount to base units of an asset priced at price * 10^expo USD
+pub fn usd_to_amount(
+    cents: u64,
+    price: u64,
+    expo: i32,
+    decimals: u64,
+) -> Result<u64, ProgramError> {
+    let mut numerator = cents as u128 * 10u128.pow(decimals as u32);
+    let mut denominator = price as u128 * 100;
+    if expo < 0 {
+        numerator *= 10u128.pow(expo.unsigned_abs());
+    } else {
+        denominator *= 10u128.pow(expo as u32);
+    }
+    let amount = numerator / denominator;
+    if amount > u64::MAX as u128 {
+        return Err(RaffleError::InvalidCalculation.into());
+    }
+    Ok(amount as u64)
+}
+
+// the total price of the paid tickets less the discount, erroring instead of wrapping
+pub fn ticket_total(paid_tickets: u64, price: u64, discount_bps: u64) -> Result<u64, ProgramError> {
+    let total = (paid_tickets as u128 * price as u128)
+        .checked_mul((MAX_FEE_BPS - discount_bps) as u128)
+        .ok_or(RaffleError::PriceOverflow)?
+        / MAX_FEE_BPS as u128;
+    if total > u64::MAX as u128 {
+        return Err(RaffleError::PriceOverflow.into());
+    }
+    Ok(total as u64)
+}
+
+// the share of an amount in basis points, with a u128 intermediate
+pub fn bps_share(amount: u64, bps: u64) -> Result<u64, ProgramError> {
+    let share = amount as u128 * bps as u128 / MAX_FEE_BPS as u128;
+    if share > u64::MAX as u128 {
+        return Err(RaffleError::AmountOverflow.into());
+    }
+    Ok(share as u64)
+}
+
+// check the instruction before the current one is an ed25519 verification
+// of the given signer's signature over the given message
+pub fn verify_ed25519_signature(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<(), ProgramError> {
+    if *instructions.key != sysvar::instructions::ID {
+        return Err(RaffleError::InvalidVoucher.into());
+    }
+    let current = load_current_index_checked(instructions)?;
+    if current == 0 {
+        return Err(RaffleError::InvalidVoucher.into());
+    }
+    let ix = load_instruction_at_checked(current as usize - 1, instructions)?;
+    if ix.program_id != ED25519_PROGRAM_ID.parse::<Pubkey>().unwrap() {
+        return Err(RaffleError::InvalidVoucher.into());
+    }
+
+    // a single signature whose offsets all point into the ed25519 instruction's own data
+    let data = &ix.data;
+    if data.len() < 16 || data[0] != 1 {
+        return Err(RaffleError::InvalidVoucher.into());
+    }
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]) as usize;
+    let signature_offset = read_u16(2);
+    let public_key_offset = read_u16(6);
+    let message_offset = read_u16(10);
+    let message_size = read_u16(12);
+    if read_u16(4) != u16::MAX as usize
+        || read_u16(8) != u16::MAX as usize
+        || read_u16(14) != u16::MAX as usize
+        || data.len() < signature_offset + 64
+        || data.len() < public_key_offset + 32
+        || data.len() < message_offset + message_size
+    {
+        return Err(RaffleError::InvalidVoucher.into());
+    }
+    if data[public_key_offset..public_key_offset + 32] != signer.to_bytes()
+        || data[message_offset..message_offset + message_size] != *message
+    {
+        return Err(RaffleError::InvalidVoucher.into());
+    }
+    Ok(())
+}
+
+// create an empty account owned by this program at a PDA, failing if it already exists
+pub fn create_marker_account<'a>(
+    payer: AccountInfo<'a>,
+    account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    signers: &[&[&[u8]]; 1],
+) -> Result<(), ProgramError> {
+    create_program_account(payer, account, system_program, 0, signers)
+}
+
+// create a rent exempt account of the given size owned by this program at a PDA
+pub fn create_program_account<'a>(
+    payer: AccountInfo<'a>,
+    account: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    space: usize,
+    signers: &[&[&[u8]]; 1],
+) -> Result<(), ProgramError> {
+    let lamports = Rent::get()?.minimum_balance(space);
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            account.key,
+            lamports,
+            space as u64,
+            &crate::ID,
+        ),
+        &[payer, account, system_program],
+        signers,
+    )
+}
+
+// split off the entrant page passed as the last of the accounts, if there is one
+pub fn split_entrant_page<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<(&'a [AccountInfo<'info>], Option<&'a AccountInfo<'info>>), ProgramError> {
+    if let Some(page) = accounts.last() {
+        if *page.owner == crate::ID
+            && page.data_len() >= 8
+            && page.try_borrow_data()?[..8] == EntrantPage::discriminator()
+        {
+            return Ok((&accounts[..accounts.len() - 1], Some(page)));
+        }
+    }
+    Ok((accounts, None))
+}
+
+// the price feeds, the receipt and the rest of a ticket purchase's remaining accounts
+pub type PurchaseAccounts<'a, 'info> = (
+    &'a [AccountInfo<'info>],
+    Option<&'a AccountInfo<'info>>,
+    &'a [AccountInfo<'info>],
+);
+
+// split off the leading remaining accounts of a ticket purchase, the Pyth price feed
+// for USD priced raffles and the buyer's TicketReceipt PDA for refundable raffles after it,
+// ahead of the discount and referrer accounts so none of those is taken for the receipt
+pub fn split_receipt<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    usd_priced: bool,
+    refundable: bool,
+) -> Result<PurchaseAccounts<'a, 'info>, ProgramError> {
+    let feed_len = std::cmp::min(usd_priced as usize, accounts.len());
+    let (feeds, accounts) = accounts.split_at(feed_len);
+    if !refundable {
+        return Ok((feeds, None, accounts));
+    }
+    let receipt = accounts.first().ok_or(RaffleError::InvalidReceipt)?;
+    // an existing receipt must be a TicketReceipt, new ones are created on the first purchase
+    if !receipt.data_is_empty()
+        && (*receipt.owner != crate::ID
+            || receipt.data_len() < 8
+            || receipt.try_borrow_data()?[..8] != TicketReceipt::discriminator())
+    {
+        return Err(RaffleError::InvalidReceipt.into());
+    }
+    Ok((feeds, Some(receipt), &accounts[1..]))
+}
+
+// the raffle's entrant pages, passed in page order as the last of the remaining accounts
+pub fn entrant_pages<'info>(
+    raffle: &Pubkey,
+    page_count: u64,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<Vec<AccountLoader<'info, EntrantPage>>, ProgramError> {
+    let page_count = page_count as usize;
+    if remaining_accounts.len() < page_count {
+        return Err(RaffleError::InvalidEntrantPage.into());
+    }
+    let mut pages = Vec::with_capacity(page_count);
+    let first = remaining_accounts.len() - page_count;
+    for (i, info) in remaining_accounts[first..].iter().enumerate() {
+        pages.push(load_entrant_page(Some(info), raffle, i as u64)?);
+    }
+    Ok(pages)
+}
+
+// the raffle's entrant page with the given index
+pub fn load_entrant_page<'info>(
+    page: Option<&AccountInfo<'info>>,
+    raffle: &Pubkey,
+    page_index: u64,
+) -> Result<AccountLoader<'info, EntrantPage>, ProgramError> {
+    let page = page.ok_or(RaffleError::InvalidEntrantPage)?;
+    let page = AccountLoader::<EntrantPage>::try_from(page)?;
+    {
+        let data = page.load()?;
+        if data.raffle != *raffle || data.page_index != page_index {
+            return Err(RaffleError::InvalidEntrantPage.into());
+        }
+    }
+    Ok(page)
+}
+
+// the wallet's UserProfile PDA, created with the wallet paying its rent on first use
+pub fn load_user_profile<'a>(
+    wallet: &AccountInfo<'a>,
+    profile: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> Result<UserProfile, ProgramError> {
+    let (address, bump) = Pubkey::find_program_address(
+        &[USER_PROFILE_SEED.as_ref(), wallet.key.as_ref()],
+        &crate::ID,
+    );
+    if *profile.key != address {
+        return Err(RaffleError::InvalidUserProfile.into());
+    }
+    if profile.data_is_empty() {
+        let seeds = &[USER_PROFILE_SEED.as_bytes(), wallet.key.as_ref(), &[bump]];
+        create_program_account(
+            wallet.clone(),
+            profile.clone(),
+            system_program.clone(),
+            8 + std::mem::size_of::<UserProfile>(),
+            &[&seeds[..]],
+        )?;
+        return Ok(UserProfile {
+            wallet: *wallet.key,
+            ..Default::default()
+        });
+    }
+    // profiles from before the season counters grow to the current size, the wallet funds the rent
+    let space = 8 + std::mem::size_of::<UserProfile>();
+    if profile.data_len() < space {
+        let rent = Rent::get()?.minimum_balance(space);
+        if rent > profile.lamports() {
+            sol_transfer_user(
+                wallet.clone(),
+                profile.clone(),
+                system_program.clone(),
+                rent - profile.lamports(),
+            )?;
+        }
+        profile.realloc(space, true)?;
+    }
+    let data = profile.try_borrow_data()?;
+    UserProfile::try_deserialize(&mut &data[..])
+}
+
+pub fn save_user_profile(profile: &AccountInfo, user_profile: &UserProfile) -> ProgramResult {
+    let mut data = profile.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data;
+    user_profile.try_serialize(&mut writer)
+}
+
+// the creator's CreatorStats PDA, created with the payer paying its rent on first use
+pub fn load_creator_stats<'a>(
+    creator: &Pubkey,
+    payer: &AccountInfo<'a>,
+    stats: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> Result<CreatorStats, ProgramError> {
+    let (address, bump) =
+        Pubkey::find_program_address(&[CREATOR_STATS_SEED.as_ref(), creator.as_ref()], &crate::ID);
+    if *stats.key != address {
+        return Err(RaffleError::InvalidCreatorStats.into());
+    }
+    if stats.data_is_empty() {
+        let seeds = &[CREATOR_STATS_SEED.as_bytes(), creator.as_ref(), &[bump]];
+        create_program_account(
+            payer.clone(),
+            stats.clone(),
+            system_program.clone(),
+            8 + std::mem::size_of::<CreatorStats>(),
+            &[&seeds[..]],
+        )?;
+        return Ok(CreatorStats {
+            creator: *creator,
+            ..Default::default()
+        });
+    }
+    let data = stats.try_borrow_data()?;
+    CreatorStats::try_deserialize(&mut &data[..])
+}
+
+pub fn save_creator_stats(stats: &AccountInfo, creator_stats: &CreatorStats) -> ProgramResult {
+    let mut data = stats.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data;
+    creator_stats.try_serialize(&mut writer)
+}
+
+// pack the u64 claim flags of a legacy raffle into the claimed_winners bitmap,
+// moving the rest of the layout down over the freed bytes
+pub fn pack_legacy_claims(raffle: &AccountInfo) -> ProgramResult {
+    let mut data = raffle.try_borrow_mut_data()?;
+    let offset = 8 + LEGACY_CLAIMED_OFFSET;
+    let mut claimed_winners = 0u64;
+    for i in 0..MAX_WINNERS {
+        let mut flag = [0u8; 8];
+        flag.copy_from_slice(&data[offset + 8 * i..offset + 8 * i + 8]);
+        if u64::from_le_bytes(flag) == 1 {
+            claimed_winners |= 1 << i;
+        }
+    }
+    let len = data.len();
+    data.copy_within(offset + LEGACY_CLAIMED_LEN..len, offset + 8);
+    data[offset..offset + 8].copy_from_slice(&claimed_winners.to_le_bytes());
+    Ok(())
+}
+
+fn read_legacy_u64(data: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&data[8 + offset..8 + offset + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+// pack the u64 counters, whitelisted flag and status of a legacy raffle into their smaller fields,
+// moving the rest of the layout down over the freed bytes
+pub fn pack_legacy_counters(raffle: &AccountInfo) -> ProgramResult {
+    let mut data = raffle.try_borrow_mut_data()?;
+    let count = read_legacy_u64(&data, LEGACY_COUNTERS_OFFSET);
+    let winner_count = read_legacy_u64(&data, LEGACY_COUNTERS_OFFSET + 8);
+    let no_repeat = read_legacy_u64(&data, LEGACY_COUNTERS_OFFSET + 16);
+    let max_entrants = read_legacy_u64(&data, LEGACY_COUNTERS_OFFSET + 24);
+    let whitelisted = read_legacy_u64(&data, LEGACY_WHITELISTED_OFFSET);
+    let status = read_legacy_u64(&data, LEGACY_STATUS_OFFSET);
+
+    // remove the later fields first so the earlier offsets still hold
+    let len = data.len();
+    for &(offset, removed) in &[
+        (LEGACY_STATUS_OFFSET, 8),
+        (LEGACY_WHITELISTED_OFFSET, 8),
+        (LEGACY_COUNTERS_OFFSET + 16, 16),
+    ] {
+        data.copy_within(8 + offset + removed..len, 8 + offset);
+    }
+    let offset = 8 + LEGACY_COUNTERS_OFFSET;
+    data[offset..offset + 4].copy_from_slice(&(count as u32).to_le_bytes());
+    data[offset + 4..offset + 8]
+        .copy_from_slice(&(std::cmp::min(max_entrants, u32::MAX as u64) as u32).to_le_bytes());
+    data[offset + 8..offset + 12].copy_from_slice(&(no_repeat as u32).to_le_bytes());
+    data[offset + 12..offset + 14].copy_from_slice(&(winner_count as u16).to_le_bytes());
+    data[offset + 14] = whitelisted as u8;
+    data[offset + 15] = status as u8;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_u32(data: &[u8], offset: usize) -> u32 {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(&data[offset..offset + 4]);
+        u32::from_le_bytes(bytes)
+    }
+
+    // a Token-2022 mint with a TransferFeeConfig of the given older and newer
+    // (epoch, maximum_fee, basis_points) fees
+    fn transfer_fee_mint(older: (u64, u64, u16), newer: (u64, u64, u16)) -> Vec<u8> {
+        let mut data = vec![0u8; 166];
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&108u16.to_le_bytes());
+        data.extend_from_slice(&[0u8; 72]);
+        for (epoch, maximum_fee, basis_points) in [older, newer].iter() {
+            data.extend_from_slice(&epoch.to_le_bytes());
+            data.extend_from_slice(&maximum_fee.to_le_bytes());
+            data.extend_from_slice(&basis_points.to_le_bytes());
+        }
+        data
+    }
+
+    fn gross(owner: &Pubkey, mut data: Vec<u8>, net: u64, epoch: u64) -> u64 {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mint = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            owner,
+            false,
+            0,
+        );
+        gross_transfer_amount(&mint, net, epoch).unwrap()
+    }
+
+    #[test]
+    fn ticket_total_applies_the_discount() {
+        assert_eq!(ticket_total(3, 1_000, 0).unwrap(), 3_000);
+        assert_eq!(ticket_total(3, 1_000, 2_500).unwrap(), 2_250);
+        assert_eq!(ticket_total(3, 1_000, MAX_FEE_BPS).unwrap(), 0);
+        assert_eq!(ticket_total(1, u64::MAX, 0).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn ticket_total_rejects_overflow() {
+        assert!(ticket_total(2, u64::MAX, 0).is_err());
+        assert!(ticket_total(u64::MAX, u64::MAX, 0).is_err());
+    }
+
+    #[test]
+    fn bps_share_rounds_down() {
+        assert_eq!(bps_share(1_000, 250).unwrap(), 25);
+        assert_eq!(bps_share(7, 5_000).unwrap(), 3);
+        assert_eq!(bps_share(1_000, 0).unwrap(), 0);
+        assert_eq!(bps_share(u64::MAX, MAX_FEE_BPS).unwrap(), u64::MAX);
+        assert!(bps_share(u64::MAX, 2 * MAX_FEE_BPS).is_err());
+    }
+
+    #[test]
+    fn validate_token_split_rejects_overflowing_bps() {
+        assert!(validate_token_split(6_000, 4_000).is_ok());
+        assert!(validate_token_split(6_000, 4_001).is_err());
+        assert!(validate_token_split(u64::MAX, 1).is_err());
+    }
+
+    #[test]
+    fn gross_transfer_amount_ignores_spl_token_mints() {
+        let mint = transfer_fee_mint((0, 1_000_000, 100), (0, 1_000_000, 100));
+        assert_eq!(gross(&spl_token::id(), mint, 9_900, 0), 9_900);
+    }
+
+    #[test]
+    fn gross_transfer_amount_adds_the_transfer_fee() {
+        let token_2022 = TOKEN_2022_PROGRAM_ID.parse::<Pubkey>().unwrap();
+        let mint = transfer_fee_mint((0, 1_000_000, 100), (0, 1_000_000, 100));
+        assert_eq!(gross(&token_2022, mint.clone(), 9_900, 0), 10_000);
+        assert_eq!(gross(&token_2022, mint, 0, 0), 0);
+        // the maximum fee caps the fee
+        let mint = transfer_fee_mint((0, 50, 100), (0, 50, 100));
+        assert_eq!(gross(&token_2022, mint, 9_900, 0), 9_950);
+        let mint = transfer_fee_mint((0, 50, 10_000), (0, 50, 10_000));
+        assert_eq!(gross(&token_2022, mint, 9_900, 0), 9_950);
+    }
+
+    #[test]
+    fn gross_transfer_amount_applies_the_newer_fee_from_its_epoch() {
+        let token_2022 = TOKEN_2022_PROGRAM_ID.parse::<Pubkey>().unwrap();
+        let mint = transfer_fee_mint((0, 1_000_000, 0), (10, 1_000_000, 100));
+        assert_eq!(gross(&token_2022, mint.clone(), 9_900, 9), 9_900);
+        assert_eq!(gross(&token_2022, mint, 9_900, 10), 10_000);
+    }
+
+    #[test]
+    fn gross_transfer_amount_allows_an_uncapped_fee() {
+        let token_2022 = TOKEN_2022_PROGRAM_ID.parse::<Pubkey>().unwrap();
+        let mint = transfer_fee_mint((0, u64::MAX, 100), (0, u64::MAX, 100));
+        assert_eq!(gross(&token_2022, mint, 9_900, 0), 10_000);
+        let mint = transfer_fee_mint((0, u64::MAX, 5_000), (0, u64::MAX, 5_000));
+        assert_eq!(gross(&token_2022, mint, 9_900, 0), 19_800);
+    }
+
+    #[test]
+    fn usd_to_amount_converts_cents() {
+        // $1.50 of SOL at $100 with a negative exponent
+        assert_eq!(
+            usd_to_amount(150, 10_000_000_000, -8, 9).unwrap(),
+            15_000_000
+        );
+        // $1.50 of a 6 decimal token at $100 with a positive exponent
+        assert_eq!(usd_to_amount(150, 1, 2, 6).unwrap(), 15_000);
+        assert!(usd_to_amount(u64::MAX, 1, -8, 9).is_err());
+    }
+
+    #[test]
+    fn pack_legacy_counters_packs_and_shifts_the_layout() {
+        let mut data = vec![0u8; 8 + LEGACY_STATUS_OFFSET + 8 + 16];
+        let legacy = [
+            (LEGACY_COUNTERS_OFFSET, 7u64),
+            (LEGACY_COUNTERS_OFFSET + 8, 3),
+            (LEGACY_COUNTERS_OFFSET + 16, 2),
+            (LEGACY_COUNTERS_OFFSET + 24, u64::MAX),
+            (LEGACY_WHITELISTED_OFFSET, 1),
+            (LEGACY_STATUS_OFFSET, 4),
+        ];
+        for &(offset, value) in legacy.iter() {
+            data[8 + offset..8 + offset + 8].copy_from_slice(&value.to_le_bytes());
+        }
+        // the first bytes of the fields after the counters, whitelisted and status
+        data[8 + LEGACY_COUNTERS_OFFSET + 32] = 0xaa;
+        data[8 + LEGACY_WHITELISTED_OFFSET + 8] = 0xbb;
+        data[8 + LEGACY_STATUS_OFFSET + 8] = 0xcc;
+
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let raffle = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &crate::ID,
+            false,
+            0,
+        );
+        pack_legacy_counters(&raffle).unwrap();
+
+        let data = raffle.try_borrow_data().unwrap();
+        let offset = 8 + LEGACY_COUNTERS_OFFSET;
+        assert_eq!(read_u32(&data, offset), 7);
+        assert_eq!(read_u32(&data, offset + 4), u32::MAX);
+        assert_eq!(read_u32(&data, offset + 8), 2);
+        assert_eq!(
+            u16::from_le_bytes([data[offset + 12], data[offset + 13]]),
+            3
+        );
+        assert_eq!(data[offset + 14], 1);
+        assert_eq!(data[offset + 15], 4);
+        // each later field moves down by the bytes freed before it
+        assert_eq!(data[offset + 16], 0xaa);
+        assert_eq!(data[8 + LEGACY_WHITELISTED_OFFSET - 16], 0xbb);
+        assert_eq!(data[8 + LEGACY_STATUS_OFFSET - 24], 0xcc);
+    }
+}
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+use crate::account::*;
+use crate::constants::*;
+
+// the summary get_raffle_state writes as return data, borsh encoded
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RaffleState {
+    pub version: u8,
+    pub status: u8,
+    pub tickets_sold: u64,
+    pub tickets_remaining: u64,
+    pub ticket_price_sol: u64,
+    pub ticket_price_reap: u64,
+    pub end_timestamp: i64,
+    pub winner_count: u64,
+    pub winners_revealed: u64,
+}
+
+impl RaffleState {
+    pub fn new(raffle: &RafflePool) -> RaffleState {
+        RaffleState {
+            version: RAFFLE_STATE_VERSION,
+            status: raffle.status,
+            tickets_sold: raffle.count(),
+            tickets_remaining: raffle.tickets_remaining,
+            ticket_price_sol: raffle.ticket_price_sol,
+            ticket_price_reap: raffle.ticket_price_reap,
+            end_timestamp: raffle.end_timestamp,
+            winner_count: raffle.winner_count(),
+            // chunked reveals count their progress, a single reveal draws every winner at once
+            winners_revealed: if raffle.revealed == 1 {
+                raffle.winner_count()
+            } else {
+                raffle.revealed_count
+            },
+        }
+    }
+}
+
+// the odds get_winning_odds writes as return data, borsh encoded
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct WinningOdds {
+    pub tickets: u64,
+    pub total_tickets: u64,
+    pub win_probability: u64,
+}
+
+impl WinningOdds {
+    // the chance to win at least one winner slot in ODDS_SCALE units,
+    // estimated as if every slot was drawn independently from all tickets
+    pub fn new(tickets: u64, total_tickets: u64, winner_count: u64) -> WinningOdds {
+        let win_probability = if tickets == 0 || total_tickets == 0 {
+            0
+        } else {
+            let scale = ODDS_SCALE as u128;
+            let lose = (total_tickets - tickets.min(total_tickets)) as u128 * scale
+                / total_tickets as u128;
+            let mut lose_all = scale;
+            for _ in 0..winner_count {
+                lose_all = lose_all * lose / scale;
+            }
+            (scale - lose_all) as u64
+        };
+        WinningOdds {
+            tickets,
+            total_tickets,
+            win_probability,
+        }
+    }
+}